@@ -0,0 +1,24 @@
+alter table evaluation
+    add column message_id text generated always as (
+        document->'transaction'->'pacs002'->'f_i_to_f_i_pmt_sts_rpt'->'grp_hdr'->>'msg_id'
+    ) stored,
+
+    add column end_to_end_id text generated always as (
+        document->'transaction'->'pacs002'->'f_i_to_f_i_pmt_sts_rpt'->'tx_inf_and_sts'->0->>'orgnl_end_to_end_id'
+    ) stored,
+
+    add column debtor_account_id text generated always as (
+        document->'data_cache'->>'dbtr_acct_id'
+    ) stored,
+
+    add column review boolean generated always as (
+        (document->'aggregation_result'->>'review')::boolean
+    ) stored;
+
+-- ids are v7 uuids, so they follow insertion order and serve as the page cursor
+create index idx_evaluation_message_id on evaluation (message_id, end_to_end_id);
+create index idx_evaluation_created_at on evaluation (created_at, id);
+create index idx_evaluation_debtor_account_id on evaluation (debtor_account_id, id);
+create index idx_evaluation_review on evaluation (review, id);
+create index idx_evaluation_typology_results on evaluation
+    using gin ((document->'aggregation_result'->'typology_results') jsonb_path_ops);
@@ -0,0 +1,26 @@
+-- payment initiations are evaluated as well, so their ids are read from whichever document the
+-- evaluation holds
+drop index idx_evaluation_message_id;
+
+alter table evaluation
+    drop column message_id,
+    drop column end_to_end_id;
+
+alter table evaluation
+    add column message_id text generated always as (
+        coalesce(
+            document->'transaction'->'pacs002'->'f_i_to_f_i_pmt_sts_rpt'->'grp_hdr'->>'msg_id',
+            document->'transaction'->'pain001'->'cstmr_cdt_trf_initn'->'grp_hdr'->>'msg_id',
+            document->'transaction'->'pain013'->'cdtr_pmt_actvtn_req'->'grp_hdr'->>'msg_id'
+        )
+    ) stored,
+
+    add column end_to_end_id text generated always as (
+        coalesce(
+            document->'transaction'->'pacs002'->'f_i_to_f_i_pmt_sts_rpt'->'tx_inf_and_sts'->0->>'orgnl_end_to_end_id',
+            document->'transaction'->'pain001'->'cstmr_cdt_trf_initn'->'pmt_inf'->0->'cdt_trf_tx_inf'->0->'pmt_id'->>'end_to_end_id',
+            document->'transaction'->'pain013'->'cdtr_pmt_actvtn_req'->'pmt_inf'->0->'cdt_trf_tx'->0->'pmt_id'->>'end_to_end_id'
+        )
+    ) stored;
+
+create index idx_evaluation_message_id on evaluation (message_id, end_to_end_id);
@@ -1,10 +1,12 @@
 use std::sync::Arc;
 
 use serde::Deserialize;
+use warden_middleware::auth::AuthConfig;
 
 #[derive(Deserialize, Clone)]
 pub struct LocalConfig {
     pub nats: NatsConfig,
+    pub auth: AuthConfig,
 }
 
 #[derive(Deserialize, Clone)]
@@ -14,4 +16,6 @@ pub struct NatsConfig {
     pub name: Arc<str>,
     pub subjects: Arc<[String]>,
     pub durable_name: Arc<str>,
+    /// Prefix of the subject results are announced on, followed by the message id
+    pub result_subject: Arc<str>,
 }
@@ -1,10 +1,19 @@
 mod cnfg;
 mod processor;
+mod server;
 mod state;
 
+use std::net::{Ipv6Addr, SocketAddr};
+
 use anyhow::Result;
+use axum::http::header::CONTENT_TYPE;
 use clap::Parser;
-use tracing::{error, trace};
+use tonic::{
+    service::Routes,
+    transport::{Server, server::TcpIncoming},
+};
+use tower::{make::Shared, steer::Steer};
+use tracing::{error, info, trace};
 use warden_stack::{Configuration, Services, tracing::Tracing};
 
 use crate::state::AppState;
@@ -84,6 +93,72 @@ async fn main() -> Result<()> {
         .await?;
     trace!("migrations updated");
 
+    let (app, grpc_server) = server::serve(state.clone(), &config.grpc)?;
+
+    let addr = SocketAddr::from((Ipv6Addr::UNSPECIFIED, config.application.port));
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    info!(port = addr.port(), "starting evaluation api");
+
+    match &config.grpc.tls {
+        Some(tls) => {
+            // mTLS is for calls between services, so the http api is not served with it
+            let port = config.grpc.port.ok_or_else(|| {
+                anyhow::anyhow!("grpc.port must be set when grpc.tls is configured")
+            })?;
+            let grpc_listener =
+                tokio::net::TcpListener::bind(SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)))
+                    .await?;
+            info!(port, mutual = tls.mutual, "serving grpc over tls");
+
+            let grpc = Server::builder()
+                .tls_config(tls.server()?)?
+                .add_routes(Routes::from(grpc_server));
+
+            // both stop with the process once the consumer has shut down
+            tokio::spawn(async move {
+                if let Err(e) = grpc
+                    .serve_with_incoming(TcpIncoming::from(grpc_listener))
+                    .await
+                {
+                    error!("evaluation grpc: {e}");
+                }
+            });
+            tokio::spawn(async move {
+                if let Err(e) = axum::serve(listener, app).await {
+                    error!("evaluation api: {e}");
+                }
+            });
+        }
+        None => {
+            let service = Steer::new(
+                vec![app, grpc_server],
+                |req: &axum::extract::Request, _services: &[_]| {
+                    if req
+                        .headers()
+                        .get(CONTENT_TYPE)
+                        .map(|content_type| content_type.as_bytes())
+                        .filter(|content_type| content_type.starts_with(b"application/grpc"))
+                        .is_some()
+                    {
+                        // grpc service
+                        1
+                    } else {
+                        // http service
+                        0
+                    }
+                },
+            );
+
+            // stops with the process once the consumer has shut down
+            tokio::spawn(async move {
+                if let Err(e) = axum::serve(listener, Shared::new(service)).await {
+                    error!("evaluation api: {e}");
+                }
+            });
+        }
+    }
+
     processor::serve(state, provider).await?;
 
     Ok(())
@@ -1,14 +1,17 @@
 use async_nats::jetstream::Message;
 use opentelemetry::global;
 use opentelemetry_semantic_conventions::attribute;
-use tracing::{Instrument, Span, debug, error, info, info_span, instrument, trace};
+use tracing::{Instrument, Span, debug, error, info, info_span, instrument, trace, warn};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use uuid::Uuid;
 use warden_core::{
     configuration::routing::RoutingConfiguration,
     message::{AggregationResult, Payload, TypologyResult, payload::Transaction},
 };
-use warden_stack::{redis::AsyncCommands, tracing::telemetry::nats::extractor};
+use warden_stack::{
+    redis::AsyncCommands,
+    tracing::telemetry::nats::{extractor, injector},
+};
 
 use crate::state::AppHandle;
 
@@ -28,12 +31,16 @@ pub async fn handle(message: Message, state: AppHandle) -> anyhow::Result<()> {
 
     let mut payload: Payload = prost::Message::decode(message.payload.as_ref())?;
 
-    if let (Some(ref typology_result), Some(Transaction::Pacs002(document)), Some(routing)) = (
+    if let (Some(ref typology_result), Some(transaction), Some(routing)) = (
         payload.typology_result.take(),
         &payload.transaction,
         &payload.routing,
-    ) {
-        let cache_key = format!("tadp_{}_tp", document.f_i_to_f_i_pmt_sts_rpt.grp_hdr.msg_id);
+    ) && !matches!(transaction, Transaction::Pacs008(_))
+    {
+        // a report may carry several statuses, and an initiation several transfers, each
+        // routed separately
+        let (msg_id, end_to_end_id) = transaction.evaluation_ids();
+        let cache_key = format!("tadp_{msg_id}_{end_to_end_id}_tp");
         let (typology_results, review) =
             handle_typologies(typology_result, &state, &cache_key, routing).await?;
 
@@ -74,6 +81,11 @@ pub async fn handle(message: Message, state: AppHandle) -> anyhow::Result<()> {
         .await?;
         info!(%id, "evaluation added");
 
+        // nobody may be waiting, the stored evaluation is what counts
+        if let Err(e) = announce(&state, &payload, msg_id).await {
+            warn!(%id, "could not announce evaluation: {e}");
+        }
+
         let mut cache = state.services.cache.get().await?;
         let span = Span::current();
         span.set_attribute(attribute::DB_SYSTEM_NAME, "valkey");
@@ -97,6 +109,41 @@ pub async fn handle(message: Message, state: AppHandle) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Publishes the evaluation for clients waiting on the outcome of `msg_id`
+///
+/// Uses core NATS rather than a stream: a result that nobody is waiting for is not kept
+async fn announce(state: &AppHandle, payload: &Payload, msg_id: &str) -> anyhow::Result<()> {
+    let subject = format!("{}.{msg_id}", state.config.nats.result_subject);
+    debug!(%subject, "announcing evaluation");
+
+    let mut headers = async_nats::HeaderMap::new();
+    let cx = Span::current().context();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&cx, &mut injector::HeaderMap(&mut headers))
+    });
+
+    let span = info_span!("nats.publish");
+    span.set_attribute(
+        attribute::MESSAGING_DESTINATION_SUBSCRIPTION_NAME,
+        subject.to_string(),
+    );
+    span.set_attribute(attribute::MESSAGING_SYSTEM, "nats");
+
+    state
+        .services
+        .jetstream
+        .client()
+        .publish_with_headers(
+            subject,
+            headers,
+            prost::Message::encode_to_vec(payload).into(),
+        )
+        .instrument(span)
+        .await?;
+
+    Ok(())
+}
+
 async fn handle_typologies(
     payload: &TypologyResult,
     state: &AppHandle,
@@ -0,0 +1,37 @@
+pub mod error;
+mod http_svc;
+mod version;
+
+use http_svc::build_router;
+use tonic::service::Routes;
+use tower_http::trace::TraceLayer;
+use warden_core::{
+    FILE_DESCRIPTOR_SET, evaluation::query_evaluations_server::QueryEvaluationsServer,
+};
+use warden_middleware::grpc::interceptor::VerifyCaller;
+use warden_stack::grpc::GrpcConfig;
+
+use crate::state::AppHandle;
+
+pub fn serve(state: AppHandle, grpc: &GrpcConfig) -> anyhow::Result<(axum::Router, axum::Router)> {
+    let app = build_router(state.clone());
+
+    let service = QueryEvaluationsServer::with_interceptor(state, VerifyCaller::new(grpc));
+
+    let reflector = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .build_v1()?;
+
+    let grpc_server = Routes::new(service)
+        .add_service(reflector)
+        .into_axum_router()
+        .layer(
+            TraceLayer::new_for_grpc().make_span_with(|request: &axum::http::Request<_>| {
+                tracing::trace_span!(env!("CARGO_PKG_NAME"), "otel.kind" = "server",
+                    headers = ?request.headers()
+                )
+            }),
+        );
+
+    Ok((app, grpc_server))
+}
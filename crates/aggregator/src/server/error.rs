@@ -0,0 +1,75 @@
+use axum::{
+    Json,
+    http::{HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use tracing::error;
+use utoipa::ToSchema;
+
+/// Errors returned by the HTTP API
+///
+/// Rendered as an [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807) problem, in the shape the
+/// warden API uses
+#[derive(Debug)]
+pub struct AppError(anyhow::Error);
+
+/// Problem details as described in [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    /// URI reference identifying the problem type
+    #[serde(rename = "type")]
+    #[schema(example = "about:blank")]
+    pub r#type: String,
+    /// Short summary of the problem type
+    #[schema(example = "Bad Request")]
+    pub title: String,
+    /// HTTP status code
+    #[schema(example = 400)]
+    pub status: u16,
+    /// Explanation specific to this occurrence of the problem
+    #[schema(example = "invalid page_token")]
+    pub detail: String,
+}
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, detail) = match self.0.downcast_ref::<tonic::Status>() {
+            Some(status) if status.code() == tonic::Code::InvalidArgument => {
+                (StatusCode::BAD_REQUEST, status.message().to_owned())
+            }
+            _ => {
+                // do not leak internals
+                error!("{:?}", self.0);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "an unexpected error occurred".to_owned(),
+                )
+            }
+        };
+
+        let problem = ProblemDetails {
+            r#type: "about:blank".to_owned(),
+            title: status.canonical_reason().unwrap_or_default().to_owned(),
+            status: status.as_u16(),
+            detail,
+        };
+
+        let mut response = (status, Json(problem)).into_response();
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        response
+    }
+}
+
+impl<E> From<E> for AppError
+where
+    E: Into<anyhow::Error>,
+{
+    fn from(err: E) -> Self {
+        Self(err.into())
+    }
+}
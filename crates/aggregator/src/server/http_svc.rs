@@ -0,0 +1,157 @@
+mod routes;
+
+use axum::{Router, response::IntoResponse};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+};
+use utoipa_axum::router::OpenApiRouter;
+
+use crate::{server::version::Version, state::AppHandle};
+
+const TAG_EVALUATIONS: &str = "Evaluations";
+
+#[derive(OpenApi)]
+#[openapi(
+    tags(
+        (name = TAG_EVALUATIONS, description = "Query the outcome of evaluated transactions"),
+    ),
+    modifiers(&SecurityAddon),
+    security(("bearer" = []), ("api_key" = []))
+)]
+pub struct ApiDoc;
+
+/// Documents the credentials accepted by
+/// [require_authentication](warden_middleware::auth::require_authentication)
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+    }
+}
+
+/// Get health of the API.
+#[utoipa::path(
+    method(get),
+    path = "/",
+    responses(
+        (status = OK, description = "Success", body = str, content_type = "text/plain")
+    ),
+    security(())
+)]
+pub async fn health_check() -> impl IntoResponse {
+    let name = env!("CARGO_PKG_NAME");
+    let ver = env!("CARGO_PKG_VERSION");
+
+    format!("{name} v{ver} is live")
+}
+
+pub fn build_router(state: AppHandle) -> Router {
+    let router = warden_middleware::version::router::<Version>(
+        ApiDoc::openapi(),
+        OpenApiRouter::new().routes(utoipa_axum::routes!(health_check)),
+        |_| routes::router(state.clone()),
+        // evaluations name accounts and their outcomes, so only admins may read them
+        |versioned| {
+            warden_middleware::auth::require_authentication(
+                warden_middleware::auth::require_admin(versioned),
+                state.authenticator.clone(),
+            )
+        },
+    );
+
+    warden_middleware::apply(router)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+    use warden_stack::cache::RedisManager;
+
+    use crate::{
+        server::http_svc::{build_router, routes::test_config},
+        state::{AppState, Services},
+    };
+
+    #[sqlx::test]
+    async fn requires_admin(pool: PgPool) {
+        let mut config = test_config();
+        config.misc["auth"] = serde_json::json!({
+            "enabled": true,
+            "api-keys": [
+                {
+                    "name": "operations",
+                    // sha-256 of "admin-key"
+                    "sha256": "69a5265506c94c77b787a7d7377b7685a0eff82e33920a71e7ee22cd6154953e",
+                    "role": "admin",
+                },
+                {
+                    "name": "fsp001",
+                    // sha-256 of "fsp001-key"
+                    "sha256": "e7c43e83f38cabfcdff025b4b132b1122f82cbfc285f577de0fa507fabfc240a",
+                    "role": "participant",
+                    "participant": "fsp001",
+                },
+            ],
+        });
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &config,
+        )
+        .await
+        .unwrap();
+        let app = build_router(state);
+
+        let get = |key: Option<&str>| {
+            let mut request = Request::builder().uri("/api/v0/evaluations");
+            if let Some(key) = key {
+                request = request.header("X-API-Key", key);
+            }
+            request.body(Body::empty()).unwrap()
+        };
+
+        let response = app.clone().oneshot(get(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app.clone().oneshot(get(Some("fsp001-key"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app.clone().oneshot(get(Some("admin-key"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // the health check stays open
+        let response = app
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,27 @@
+mod evaluation;
+
+use utoipa_axum::{router::OpenApiRouter, routes};
+
+use crate::state::AppHandle;
+
+/// Every route served under the prefix of each version
+pub fn router(store: AppHandle) -> OpenApiRouter {
+    OpenApiRouter::new()
+        .routes(routes!(evaluation::get_evaluation))
+        .routes(routes!(evaluation::list_evaluations))
+        .with_state(store)
+}
+
+#[cfg(test)]
+pub(crate) fn test_config() -> warden_stack::Configuration {
+    use warden_stack::Configuration;
+
+    let config_path = "aggregator.toml";
+
+    let config = config::Config::builder()
+        .add_source(config::File::new(config_path, config::FileFormat::Toml))
+        .build()
+        .unwrap();
+
+    config.try_deserialize::<Configuration>().unwrap()
+}
@@ -0,0 +1,314 @@
+use std::str::FromStr;
+
+use axum::{
+    Json,
+    extract::{Query, State},
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+use warden_core::{
+    evaluation::{
+        GetEvaluationRequest, GetEvaluationResponse, ListEvaluationsRequest,
+        ListEvaluationsResponse, query_evaluations_server::QueryEvaluations,
+    },
+    google::protobuf::Timestamp,
+};
+use warden_middleware::version::Versioned;
+
+use crate::{
+    server::{
+        error::{AppError, ProblemDetails},
+        http_svc::TAG_EVALUATIONS,
+        version::Version,
+    },
+    state::AppHandle,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListParams {
+    /// Only evaluations created at or after this RFC 3339 time
+    from: Option<String>,
+    /// Only evaluations created before this RFC 3339 time
+    to: Option<String>,
+    /// Only evaluations with this review flag
+    review: Option<bool>,
+    /// Only evaluations with a result for this typology
+    typology_id: Option<String>,
+    /// Only evaluations of transfers from this debtor account
+    dbtr_acct_id: Option<String>,
+    /// Number of evaluations per page. Defaults to 50, at most 500
+    page_size: Option<u32>,
+    /// `next_page_token` of the previous page
+    page_token: Option<String>,
+}
+
+/// Get the evaluations of a message
+#[utoipa::path(
+    get,
+    path = "/evaluation",
+    responses(
+        (status = OK, body = GetEvaluationResponse),
+        (status = BAD_REQUEST, description = "msg_id is missing", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = UNAUTHORIZED, description = "the request carries no valid credentials", body = str, content_type = "text/plain"),
+        (status = FORBIDDEN, description = "the caller is not an admin", body = str, content_type = "text/plain"),
+        (status = INTERNAL_SERVER_ERROR, description = "the evaluations could not be read", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    params(
+        GetEvaluationRequest
+    ),
+    operation_id = "get_evaluation", // https://github.com/juhaku/utoipa/issues/1170
+    tag = TAG_EVALUATIONS,
+    )
+]
+#[axum::debug_handler]
+#[tracing::instrument(skip(state))]
+pub async fn get_evaluation(
+    Versioned(version): Versioned<Version>,
+    State(state): State<AppHandle>,
+    Query(body): Query<GetEvaluationRequest>,
+) -> Result<Json<GetEvaluationResponse>, AppError> {
+    let response = state
+        .get_evaluation(tonic::Request::new(body))
+        .await?
+        .into_inner();
+
+    Ok(Json(response))
+}
+
+/// List evaluations, newest first
+#[utoipa::path(
+    get,
+    path = "/evaluations",
+    responses(
+        (status = OK, body = ListEvaluationsResponse),
+        (status = BAD_REQUEST, description = "a time or the page token is malformed", body = ProblemDetails, content_type = "application/problem+json"),
+        (status = UNAUTHORIZED, description = "the request carries no valid credentials", body = str, content_type = "text/plain"),
+        (status = FORBIDDEN, description = "the caller is not an admin", body = str, content_type = "text/plain"),
+        (status = INTERNAL_SERVER_ERROR, description = "the evaluations could not be read", body = ProblemDetails, content_type = "application/problem+json"),
+    ),
+    params(
+        ListParams
+    ),
+    operation_id = "list_evaluations", // https://github.com/juhaku/utoipa/issues/1170
+    tag = TAG_EVALUATIONS,
+    )
+]
+#[axum::debug_handler]
+#[tracing::instrument(skip(state))]
+pub async fn list_evaluations(
+    Versioned(version): Versioned<Version>,
+    State(state): State<AppHandle>,
+    Query(params): Query<ListParams>,
+) -> Result<Json<ListEvaluationsResponse>, AppError> {
+    let timestamp = |value: Option<String>| {
+        value
+            .as_deref()
+            .map(Timestamp::from_str)
+            .transpose()
+            .map_err(|e| tonic::Status::invalid_argument(format!("invalid time: {e}")))
+    };
+
+    let request = ListEvaluationsRequest {
+        from: timestamp(params.from)?,
+        to: timestamp(params.to)?,
+        review: params.review,
+        typology_id: params.typology_id,
+        dbtr_acct_id: params.dbtr_acct_id,
+        page_size: params.page_size.unwrap_or_default(),
+        page_token: params.page_token,
+    };
+
+    let response = state
+        .list_evaluations(tonic::Request::new(request))
+        .await?
+        .into_inner();
+
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::{self, Body},
+        http::{Request, StatusCode, header},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+    use warden_core::{
+        evaluation::{GetEvaluationResponse, ListEvaluationsResponse},
+        iso20022::pacs002::Pacs002Document,
+        message::{AggregationResult, DataCache, Payload, TypologyResult, payload::Transaction},
+    };
+    use warden_stack::cache::RedisManager;
+
+    use crate::{
+        server::{
+            error::PROBLEM_JSON,
+            http_svc::{build_router, routes::test_config},
+        },
+        state::{AppState, Services},
+    };
+
+    fn evaluation(msg_id: &str, dbtr_acct_id: &str, review: bool) -> Payload {
+        let document: Pacs002Document = serde_json::from_value(serde_json::json!({
+            "f_i_to_f_i_pmt_sts_rpt": {
+                "grp_hdr": {
+                    "msg_id": msg_id,
+                    "cre_dt_tm": "2025-08-16T11:34:51Z"
+                },
+                "orgnl_grp_inf_and_sts": [],
+                "splmtry_data": [],
+                "tx_inf_and_sts": [
+                    {
+                        "orgnl_end_to_end_id": format!("{msg_id}-e2e"),
+                        "tx_sts": "ACCC",
+                        "sts_rsn_inf": [],
+                        "splmtry_data": [],
+                        "chrgs_inf": []
+                    }
+                ]
+            }
+        }))
+        .unwrap();
+
+        Payload {
+            tx_tp: "pacs.002.001.12".to_string(),
+            transaction: Some(Transaction::Pacs002(document)),
+            data_cache: Some(DataCache {
+                dbtr_acct_id: dbtr_acct_id.to_string(),
+                ..Default::default()
+            }),
+            aggregation_result: Some(AggregationResult {
+                id: "001@1.0.0".to_string(),
+                version: "1.0.0".to_string(),
+                typology_results: vec![TypologyResult {
+                    id: "typology-processor@1.0.0".to_string(),
+                    version: "1.0.0".to_string(),
+                    review,
+                    ..Default::default()
+                }],
+                review,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test]
+    async fn query(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool.clone(),
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+
+        for (msg_id, dbtr_acct_id, review) in [
+            ("msg-1", "acct-1", false),
+            ("msg-2", "acct-1", true),
+            ("msg-3", "acct-2", true),
+        ] {
+            sqlx::query("insert into evaluation (id, document) values ($1, $2)")
+                .bind(uuid::Uuid::now_v7())
+                .bind(sqlx::types::Json(evaluation(msg_id, dbtr_acct_id, review)))
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let app = build_router(state);
+
+        let get = |uri: &str| {
+            Request::builder()
+                .method("GET")
+                .uri(uri)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(get("/api/v0/evaluation?msg_id=msg-2"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let response: GetEvaluationResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response.evaluations.len(), 1);
+        assert!(
+            response.evaluations[0]
+                .payload
+                .as_ref()
+                .and_then(|value| value.aggregation_result.as_ref())
+                .unwrap()
+                .review
+        );
+
+        let response = app
+            .clone()
+            .oneshot(get("/api/v0/evaluations?review=true&page_size=1"))
+            .await
+            .unwrap();
+        let body = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let first: ListEvaluationsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(first.evaluations.len(), 1);
+        let token = first.next_page_token.unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(get(&format!(
+                "/api/v0/evaluations?review=true&page_size=1&page_token={token}"
+            )))
+            .await
+            .unwrap();
+        let body = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let second: ListEvaluationsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(second.evaluations.len(), 1);
+        assert!(second.next_page_token.is_none());
+        assert_ne!(first.evaluations[0].id, second.evaluations[0].id);
+
+        let response = app
+            .clone()
+            .oneshot(get(
+                "/api/v0/evaluations?dbtr_acct_id=acct-1&typology_id=typology-processor@1.0.0",
+            ))
+            .await
+            .unwrap();
+        let body = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let response: ListEvaluationsResponse = serde_json::from_slice(&body).unwrap();
+        assert_eq!(response.evaluations.len(), 2);
+
+        let response = app
+            .oneshot(get("/api/v0/evaluations?page_token=nope"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+
+        let body = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(problem["status"], 400);
+        assert_eq!(problem["detail"], "invalid page_token");
+    }
+}
@@ -0,0 +1,31 @@
+use std::fmt;
+
+use utoipa::ToSchema;
+use warden_middleware::version::ApiVersion;
+
+/// A version of the HTTP API
+///
+/// Each version is served under its own prefix, e.g. `/api/v0`, and the version that handled a
+/// request is available to its handler through [Versioned](warden_middleware::version::Versioned)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+pub enum Version {
+    V0,
+}
+
+impl Version {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Version::V0 => "v0",
+        }
+    }
+}
+
+impl ApiVersion for Version {
+    const ALL: &'static [Self] = &[Version::V0];
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -1,7 +1,10 @@
+mod evaluation;
+
 use sqlx::PgPool;
 use std::{ops::Deref, sync::Arc};
 
 use async_nats::jetstream::Context;
+use warden_middleware::auth::Authenticator;
 use warden_stack::{Configuration, cache::RedisManager};
 
 use crate::cnfg::LocalConfig;
@@ -17,6 +20,8 @@ pub struct Services {
 pub struct AppState {
     pub services: Services,
     pub config: LocalConfig,
+    /// Validates the credentials of API requests
+    pub authenticator: Arc<Authenticator>,
 }
 
 #[derive(Clone)]
@@ -35,8 +40,13 @@ impl AppState {
         services: Services,
         configuration: &Configuration,
     ) -> anyhow::Result<AppHandle> {
-        let config = serde_json::from_value(configuration.misc.clone())?;
-
-        Ok(AppHandle(Arc::new(Self { services, config })))
+        let config: LocalConfig = serde_json::from_value(configuration.misc.clone())?;
+        let authenticator = Authenticator::new(&config.auth)?;
+
+        Ok(AppHandle(Arc::new(Self {
+            services,
+            config,
+            authenticator: Arc::new(authenticator),
+        })))
     }
 }
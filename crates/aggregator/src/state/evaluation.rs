@@ -0,0 +1,155 @@
+use opentelemetry_semantic_conventions::attribute;
+use time::OffsetDateTime;
+use tonic::{Request, Response, Status, async_trait};
+use tracing::{Instrument, debug, error, info_span, instrument};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+use warden_core::{
+    evaluation::{
+        Evaluation, GetEvaluationRequest, GetEvaluationResponse, ListEvaluationsRequest,
+        ListEvaluationsResponse, query_evaluations_server::QueryEvaluations,
+    },
+    message::Payload,
+};
+
+use crate::state::AppHandle;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+
+struct EvaluationRow {
+    id: Uuid,
+    created_at: Option<OffsetDateTime>,
+    document: sqlx::types::Json<Payload>,
+}
+
+impl From<EvaluationRow> for Evaluation {
+    fn from(value: EvaluationRow) -> Self {
+        Self {
+            id: value.id.to_string(),
+            created_at: value.created_at.map(Into::into),
+            payload: Some(value.document.0),
+        }
+    }
+}
+
+/// Logs `e`, and hides it from the caller
+fn database_error(e: sqlx::Error) -> Status {
+    error!("could not read evaluations: {e}");
+    Status::internal("could not read evaluations")
+}
+
+#[async_trait]
+impl QueryEvaluations for AppHandle {
+    #[instrument(skip(self, request), Err(Debug))]
+    async fn get_evaluation(
+        &self,
+        request: Request<GetEvaluationRequest>,
+    ) -> Result<Response<GetEvaluationResponse>, Status> {
+        let data = request.into_inner();
+        if data.msg_id.is_empty() {
+            return Err(Status::invalid_argument("msg_id is required"));
+        }
+
+        let span = info_span!("get.evaluations.evaluation");
+        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+        span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+        span.set_attribute(attribute::DB_COLLECTION_NAME, "evaluation");
+        span.set_attribute(attribute::DB_OPERATION_PARAMETER, data.msg_id.to_string());
+        span.set_attribute("otel.kind", "client");
+
+        let evaluations = sqlx::query_as!(
+            EvaluationRow,
+            r#"select id, created_at, document as "document: sqlx::types::Json<Payload>"
+            from evaluation
+            where message_id = $1 and ($2::text is null or end_to_end_id = $2)
+            order by id"#,
+            data.msg_id,
+            data.end_to_end_id,
+        )
+        .fetch_all(&self.services.postgres)
+        .instrument(span)
+        .await
+        .map_err(database_error)?;
+
+        debug!(count = evaluations.len(), "found evaluations");
+
+        Ok(Response::new(GetEvaluationResponse {
+            evaluations: evaluations.into_iter().map(Evaluation::from).collect(),
+        }))
+    }
+
+    #[instrument(skip(self, request), Err(Debug))]
+    async fn list_evaluations(
+        &self,
+        request: Request<ListEvaluationsRequest>,
+    ) -> Result<Response<ListEvaluationsResponse>, Status> {
+        let data = request.into_inner();
+
+        let page_size = match data.page_size {
+            0 => DEFAULT_PAGE_SIZE,
+            size => size.min(MAX_PAGE_SIZE),
+        };
+
+        let from = data.from.map(OffsetDateTime::try_from).transpose();
+        let to = data.to.map(OffsetDateTime::try_from).transpose();
+        let (Ok(from), Ok(to)) = (from, to) else {
+            return Err(Status::invalid_argument("invalid time range"));
+        };
+
+        // the cursor is the id of the last evaluation on the previous page
+        let after = data
+            .page_token
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| Status::invalid_argument("invalid page_token"))?;
+
+        let span = info_span!("get.evaluations.evaluation");
+        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+        span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+        span.set_attribute(attribute::DB_COLLECTION_NAME, "evaluation");
+        span.set_attribute("otel.kind", "client");
+
+        let mut evaluations = sqlx::query_as!(
+            EvaluationRow,
+            r#"select id, created_at, document as "document: sqlx::types::Json<Payload>"
+            from evaluation
+            where ($1::timestamptz is null or created_at >= $1)
+              and ($2::timestamptz is null or created_at < $2)
+              and ($3::boolean is null or review = $3)
+              and ($4::text is null or document->'aggregation_result'->'typology_results'
+                    @> jsonb_build_array(jsonb_build_object('id', $4::text)))
+              and ($5::text is null or debtor_account_id = $5)
+              and ($6::uuid is null or id < $6)
+            order by id desc
+            limit $7"#,
+            from,
+            to,
+            data.review,
+            data.typology_id,
+            data.dbtr_acct_id,
+            after,
+            i64::from(page_size) + 1,
+        )
+        .fetch_all(&self.services.postgres)
+        .instrument(span)
+        .await
+        .map_err(database_error)?;
+
+        // one more row than requested tells whether there is another page
+        let next_page_token = if evaluations.len() > page_size as usize {
+            evaluations.truncate(page_size as usize);
+            evaluations.last().map(|value| value.id.to_string())
+        } else {
+            None
+        };
+
+        debug!(count = evaluations.len(), "listed evaluations");
+
+        Ok(Response::new(ListEvaluationsResponse {
+            evaluations: evaluations.into_iter().map(Evaluation::from).collect(),
+            next_page_token,
+        }))
+    }
+}
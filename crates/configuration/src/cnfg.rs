@@ -1,10 +1,12 @@
 use std::sync::Arc;
 
 use serde::Deserialize;
+use warden_middleware::auth::AuthConfig;
 
 #[derive(Deserialize, Clone)]
 pub struct LocalConfig {
     pub nats: JetstreamConfig,
+    pub auth: AuthConfig,
 }
 
 #[derive(Deserialize, Clone)]
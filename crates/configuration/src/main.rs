@@ -8,6 +8,10 @@ use crate::{server::error::AppError, state::AppState};
 use axum::http::header::CONTENT_TYPE;
 use clap::Parser;
 use tokio::signal;
+use tonic::{
+    service::Routes,
+    transport::{Server, server::TcpIncoming},
+};
 use tower::{make::Shared, steer::Steer};
 use tracing::{error, info, trace};
 use warden_stack::{
@@ -92,35 +96,67 @@ async fn main() -> Result<(), AppError> {
         .await?;
     trace!("migrations updated");
 
-    let (app, grpc_server) = server::serve(state)?;
-
-    let service = Steer::new(
-        vec![app, grpc_server],
-        |req: &axum::extract::Request, _services: &[_]| {
-            if req
-                .headers()
-                .get(CONTENT_TYPE)
-                .map(|content_type| content_type.as_bytes())
-                .filter(|content_type| content_type.starts_with(b"application/grpc"))
-                .is_some()
-            {
-                // grpc service
-                1
-            } else {
-                // http service
-                0
-            }
-        },
-    );
+    let (app, grpc_server) = server::serve(state, &config.grpc)?;
 
     let addr = SocketAddr::from((Ipv6Addr::UNSPECIFIED, config.application.port));
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
     info!(port = addr.port(), "starting config-api");
 
-    axum::serve(listener, Shared::new(service))
-        .with_graceful_shutdown(shutdown_signal(provider))
-        .await?;
+    match &config.grpc.tls {
+        Some(tls) => {
+            // mTLS is for calls between services, so the http api is not served with it
+            let port = config.grpc.port.ok_or_else(|| {
+                anyhow::anyhow!("grpc.port must be set when grpc.tls is configured")
+            })?;
+            let grpc_listener =
+                tokio::net::TcpListener::bind(SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)))
+                    .await?;
+            info!(port, mutual = tls.mutual, "serving grpc over tls");
+
+            let grpc = Server::builder()
+                .tls_config(tls.server()?)?
+                .add_routes(Routes::from(grpc_server));
+
+            // stops with the process once the http api has shut down
+            tokio::spawn(async move {
+                if let Err(e) = grpc
+                    .serve_with_incoming(TcpIncoming::from(grpc_listener))
+                    .await
+                {
+                    error!("grpc: {e}");
+                }
+            });
+
+            axum::serve(listener, app)
+                .with_graceful_shutdown(shutdown_signal(provider))
+                .await?;
+        }
+        None => {
+            let service = Steer::new(
+                vec![app, grpc_server],
+                |req: &axum::extract::Request, _services: &[_]| {
+                    if req
+                        .headers()
+                        .get(CONTENT_TYPE)
+                        .map(|content_type| content_type.as_bytes())
+                        .filter(|content_type| content_type.starts_with(b"application/grpc"))
+                        .is_some()
+                    {
+                        // grpc service
+                        1
+                    } else {
+                        // http service
+                        0
+                    }
+                },
+            );
+
+            axum::serve(listener, Shared::new(service))
+                .with_graceful_shutdown(shutdown_signal(provider))
+                .await?;
+        }
+    }
 
     Ok(())
 }
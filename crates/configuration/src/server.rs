@@ -22,14 +22,19 @@ use warden_core::{
         },
     },
 };
-use warden_middleware::grpc::interceptor::MyInterceptor;
+use warden_middleware::grpc::interceptor::VerifyCaller;
+use warden_stack::grpc::GrpcConfig;
 
 use crate::{server::error::AppError, state::AppHandle};
 
-pub fn serve(state: AppHandle) -> Result<(axum::Router, axum::Router), AppError> {
+pub fn serve(
+    state: AppHandle,
+    grpc: &GrpcConfig,
+) -> Result<(axum::Router, axum::Router), AppError> {
     let app = build_router(state.clone());
+    let interceptor = VerifyCaller::new(grpc);
 
-    let service = QueryRoutingServer::with_interceptor(state.clone(), MyInterceptor);
+    let service = QueryRoutingServer::with_interceptor(state.clone(), interceptor.clone());
 
     let routing_reflector = tonic_reflection::server::Builder::configure()
         .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
@@ -38,23 +43,23 @@ pub fn serve(state: AppHandle) -> Result<(axum::Router, axum::Router), AppError>
     let grpc_server = Routes::new(service)
         .add_service(MutateRoutingServer::with_interceptor(
             state.clone(),
-            MyInterceptor,
+            interceptor.clone(),
         ))
         .add_service(MutateRuleConfigurationServer::with_interceptor(
             state.clone(),
-            MyInterceptor,
+            interceptor.clone(),
         ))
         .add_service(QueryRuleConfigurationServer::with_interceptor(
             state.clone(),
-            MyInterceptor,
+            interceptor.clone(),
         ))
         .add_service(QueryTypologiesServer::with_interceptor(
             state.clone(),
-            MyInterceptor,
+            interceptor.clone(),
         ))
         .add_service(MutateTypologiesServer::with_interceptor(
             state.clone(),
-            MyInterceptor,
+            interceptor.clone(),
         ))
         .add_service(routing_reflector)
         .into_axum_router()
@@ -8,6 +8,12 @@ pub struct AppError(anyhow::Error);
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        if let Some(status) = self.0.downcast_ref::<tonic::Status>()
+            && status.code() == tonic::Code::InvalidArgument
+        {
+            return (StatusCode::BAD_REQUEST, status.message().to_owned()).into_response();
+        }
+
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Something went wrong: {}", self.0),
@@ -1,14 +1,13 @@
 mod routes;
 
 use axum::{Router, response::IntoResponse};
-use utoipa::OpenApi;
+use utoipa::{
+    Modify, OpenApi,
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+};
 use utoipa_axum::router::OpenApiRouter;
-#[cfg(feature = "redoc")]
-use utoipa_redoc::Servable;
-#[cfg(feature = "scalar")]
-use utoipa_scalar::Servable as _;
 
-use crate::state::AppHandle;
+use crate::{server::version::Version, state::AppHandle};
 
 const TAG_ROUTING: &str = "Routing";
 const TAG_RULES: &str = "Rules";
@@ -18,17 +17,43 @@ const TAG_TYPOLOGIES: &str = "Typologies";
 #[openapi(
     tags(
         (name = TAG_ROUTING, description = "Operations related to routing configuration"),
-    )
+    ),
+    modifiers(&SecurityAddon),
+    security(("bearer" = []), ("api_key" = []))
 )]
 pub struct ApiDoc;
 
+/// Documents the credentials accepted by
+/// [require_authentication](warden_middleware::auth::require_authentication)
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+    }
+}
+
 /// Get health of the API.
 #[utoipa::path(
     method(get),
     path = "/",
     responses(
         (status = OK, description = "Success", body = str, content_type = "text/plain")
-    )
+    ),
+    security(())
 )]
 pub async fn health_check() -> impl IntoResponse {
     let name = env!("CARGO_PKG_NAME");
@@ -38,29 +63,19 @@ pub async fn health_check() -> impl IntoResponse {
 }
 
 pub fn build_router(state: AppHandle) -> Router {
-    let (router, _api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
-        .routes(utoipa_axum::routes!(health_check))
-        .nest("/api", routes::router(state))
-        .split_for_parts();
-
-    #[cfg(feature = "swagger")]
-    let router = router.merge(
-        utoipa_swagger_ui::SwaggerUi::new("/swagger-ui")
-            .url("/api-docs/swaggerdoc.json", _api.clone()),
+    let router = warden_middleware::version::router::<Version>(
+        ApiDoc::openapi(),
+        OpenApiRouter::new().routes(utoipa_axum::routes!(health_check)),
+        |_| routes::router(state.clone()),
+        // reads are open to every caller, changes are reserved for admins
+        |versioned| {
+            warden_middleware::auth::require_authentication(
+                warden_middleware::auth::require_admin_for_writes(versioned),
+                state.authenticator.clone(),
+            )
+        },
     );
 
-    #[cfg(feature = "redoc")]
-    let router = router.merge(utoipa_redoc::Redoc::with_url("/redoc", _api.clone()));
-
-    #[cfg(feature = "rapidoc")]
-    let router = router.merge(
-        utoipa_rapidoc::RapiDoc::with_openapi("/api-docs/rapidoc.json", _api.clone())
-            .path("/rapidoc"),
-    );
-
-    #[cfg(feature = "scalar")]
-    let router = router.merge(utoipa_scalar::Scalar::with_url("/scalar", _api));
-
     warden_middleware::apply(router)
 }
 
@@ -106,4 +121,61 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::OK);
     }
+
+    #[sqlx::test]
+    async fn writes_require_admin(pool: PgPool) {
+        let mut config = test_config();
+        config.misc["auth"] = serde_json::json!({
+            "enabled": true,
+            "api-keys": [
+                {
+                    "name": "fsp001",
+                    // sha-256 of "fsp001-key"
+                    "sha256": "e7c43e83f38cabfcdff025b4b132b1122f82cbfc285f577de0fa507fabfc240a",
+                    "role": "participant",
+                    "participant": "fsp001",
+                },
+            ],
+        });
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &config,
+        )
+        .await
+        .unwrap();
+        let app = build_router(state);
+
+        let post = |key: Option<&str>| {
+            let mut request = Request::builder()
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .uri("/api/v0/rule");
+            if let Some(key) = key {
+                request = request.header("X-API-Key", key);
+            }
+            request.body(Body::from("{}")).unwrap()
+        };
+
+        let response = app.clone().oneshot(post(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app.clone().oneshot(post(Some("fsp001-key"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // the health check stays open
+        let response = app
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
 }
@@ -6,6 +6,7 @@ use utoipa_axum::{router::OpenApiRouter, routes};
 
 use crate::state::AppHandle;
 
+/// Every route served under the prefix of each version
 pub fn router(store: AppHandle) -> OpenApiRouter {
     OpenApiRouter::new()
         .routes(routes!(
@@ -3,6 +3,7 @@ use tonic::IntoRequest;
 use warden_core::configuration::routing::{
     DeleteConfigurationRequest, RoutingConfiguration, mutate_routing_server::MutateRouting,
 };
+use warden_middleware::version::Versioned;
 
 use crate::{
     server::{error::AppError, http_svc::TAG_ROUTING, version::Version},
@@ -12,14 +13,13 @@ use crate::{
 /// Delete routing configuration
 #[utoipa::path(
     delete,
-    path = "/{version}/routing/{id}",
+    path = "/routing/{id}",
     responses((
         status = OK,
         body = RoutingConfiguration
     )),
     operation_id = "delete_routing_configuration", // https://github.com/juhaku/utoipa/issues/1170
     params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3"),
         ("id" = String, Path, description = "Identifier for item to delete"),
     ),
     tag = TAG_ROUTING,
@@ -28,7 +28,7 @@ use crate::{
 #[axum::debug_handler]
 #[tracing::instrument(skip(state))]
 pub async fn delete(
-    version: Version,
+    Versioned(version): Versioned<Version>,
     State(state): State<AppHandle>,
     Path(id): Path<String>,
     axum::Json(body): axum::Json<RoutingConfiguration>,
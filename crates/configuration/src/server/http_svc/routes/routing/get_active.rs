@@ -4,6 +4,7 @@ use warden_core::{
     configuration::routing::{RoutingConfiguration, query_routing_server::QueryRouting},
     google,
 };
+use warden_middleware::version::Versioned;
 
 use crate::{
     server::{error::AppError, http_svc::TAG_ROUTING, version::Version},
@@ -18,17 +19,14 @@ use crate::{
         body = RoutingConfiguration
     )),
     operation_id = "get_active_routing", // https://github.com/juhaku/utoipa/issues/1170
-    path = "/{version}/routing",
-    params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3")
-    ),
+    path = "/routing",
     tag = TAG_ROUTING,
     )
 ]
 #[axum::debug_handler]
 #[tracing::instrument(skip(state), err(Debug), fields(method = "GET"))]
 pub async fn active_routing(
-    version: Version,
+    Versioned(version): Versioned<Version>,
     State(state): State<AppHandle>,
 ) -> Result<impl IntoResponse, AppError> {
     let config = state
@@ -2,6 +2,7 @@ use axum::{extract::State, response::IntoResponse};
 use warden_core::configuration::routing::{
     RoutingConfiguration, mutate_routing_server::MutateRouting,
 };
+use warden_middleware::version::Versioned;
 
 use crate::{
     server::{error::AppError, http_svc::TAG_ROUTING, version::Version},
@@ -16,17 +17,14 @@ use crate::{
         body = RoutingConfiguration
     )),
     operation_id = "post_routing_configuration", // https://github.com/juhaku/utoipa/issues/1170
-    path = "/{version}/routing",
-    params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3")
-    ),
+    path = "/routing",
     tag = TAG_ROUTING,
 )
 ]
 #[axum::debug_handler]
 #[tracing::instrument(skip(state), err(Debug), fields(method = "POST"))]
 pub async fn post_routing(
-    version: Version,
+    Versioned(version): Versioned<Version>,
     State(state): State<AppHandle>,
     axum::Json(body): axum::Json<RoutingConfiguration>,
 ) -> Result<impl IntoResponse, AppError> {
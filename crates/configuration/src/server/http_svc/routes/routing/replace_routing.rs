@@ -2,6 +2,7 @@ use axum::extract::{Path, State};
 use warden_core::configuration::routing::{
     RoutingConfiguration, UpdateRoutingRequest, mutate_routing_server::MutateRouting,
 };
+use warden_middleware::version::Versioned;
 
 use crate::{
     server::{error::AppError, http_svc::TAG_ROUTING, version::Version},
@@ -15,9 +16,8 @@ use crate::{
         status = OK,
         body = RoutingConfiguration
     )),
-    path = "/{version}/routing/{id}",
+    path = "/routing/{id}",
     params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3"),
         ("id" = String, Path, description = "Identifier for item to replace"),
     ),
     operation_id = "replace_routing_configuration", // https://github.com/juhaku/utoipa/issues/1170
@@ -27,7 +27,7 @@ use crate::{
 #[axum::debug_handler]
 #[tracing::instrument(skip(state))]
 pub async fn replace(
-    version: Version,
+    Versioned(version): Versioned<Version>,
     State(state): State<AppHandle>,
     Path(id): Path<String>,
     axum::Json(body): axum::Json<RoutingConfiguration>,
@@ -2,6 +2,7 @@ use axum::{extract::State, response::IntoResponse};
 use warden_core::configuration::rule::{
     RuleConfiguration, mutate_rule_configuration_server::MutateRuleConfiguration,
 };
+use warden_middleware::version::Versioned;
 
 use crate::{
     server::{error::AppError, http_svc::TAG_RULES, version::Version},
@@ -11,14 +12,11 @@ use crate::{
 /// Create rule configuration
 #[utoipa::path(
     post,
-    path = "/{version}/rule",
-    params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3"),
+    path = "/rule",
+    responses(
+        (status = CREATED, body = RuleConfiguration),
+        (status = BAD_REQUEST, description = "The configuration is invalid, such as a query template that would not run"),
     ),
-    responses((
-        status = CREATED,
-        body = RuleConfiguration,
-    )),
     operation_id = "create_rule_configuration", // https://github.com/juhaku/utoipa/issues/1170
     tag = TAG_RULES,
     )
@@ -26,7 +24,7 @@ use crate::{
 #[axum::debug_handler]
 #[tracing::instrument(skip(state))]
 pub async fn create_rule(
-    version: Version,
+    Versioned(version): Versioned<Version>,
     State(state): State<AppHandle>,
     axum::Json(body): axum::Json<RuleConfiguration>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -125,4 +123,60 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::CREATED);
     }
+
+    #[sqlx::test]
+    async fn post_rule_invalid_template(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+
+        let app = build_router(state);
+
+        let rule = serde_json::json!({
+              "id": "template-001",
+              "version": "1.0.0",
+              "description": "Number of holders of the debtor account",
+              "configuration": {
+                "parameters": {
+                  "query": "select count(*) from entity where id = {debtor}"
+                },
+                "bands": [
+                  {
+                    "sub_rule_ref": ".01",
+                    "reason": "The debtor account has holders"
+                  }
+                ]
+              }
+        });
+
+        let body = serde_json::to_vec(&rule).unwrap();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/rule")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 }
@@ -4,6 +4,7 @@ use warden_core::configuration::rule::{
     DeleteRuleConfigurationRequest, RuleConfiguration,
     mutate_rule_configuration_server::MutateRuleConfiguration,
 };
+use warden_middleware::version::Versioned;
 
 use crate::{
     server::{error::AppError, http_svc::TAG_RULES, version::Version},
@@ -13,13 +14,12 @@ use crate::{
 /// Delete rule configuration
 #[utoipa::path(
     delete,
-    path = "/{version}/rule",
+    path = "/rule",
     responses((
         status = OK,
         body = RuleConfiguration
     )),
     params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3"),
         DeleteRuleConfigurationRequest,
     ),
     operation_id = "delete_rule_configuration", // https://github.com/juhaku/utoipa/issues/1170
@@ -29,7 +29,7 @@ use crate::{
 #[axum::debug_handler]
 #[tracing::instrument(skip(state))]
 pub async fn delete_rule_config(
-    version: Version,
+    Versioned(version): Versioned<Version>,
     State(state): State<AppHandle>,
     Query(body): Query<DeleteRuleConfigurationRequest>,
 ) -> Result<axum::Json<RuleConfiguration>, AppError> {
@@ -3,6 +3,7 @@ use warden_core::configuration::rule::{
     RuleConfiguration, RuleConfigurationRequest,
     query_rule_configuration_server::QueryRuleConfiguration,
 };
+use warden_middleware::version::Versioned;
 
 use crate::{
     server::{error::AppError, http_svc::TAG_RULES, version::Version},
@@ -12,13 +13,12 @@ use crate::{
 /// Get rule configuration
 #[utoipa::path(
     get,
-    path = "/{version}/rule",
+    path = "/rule",
     responses((
         status = OK,
         body = RuleConfiguration
     )),
     params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3"),
         RuleConfigurationRequest
     ),
     operation_id = "get_rule_configuration", // https://github.com/juhaku/utoipa/issues/1170
@@ -28,7 +28,7 @@ use crate::{
 #[axum::debug_handler]
 #[tracing::instrument(skip(state))]
 pub async fn get_rule(
-    version: Version,
+    Versioned(version): Versioned<Version>,
     State(state): State<AppHandle>,
     Query(body): Query<RuleConfigurationRequest>,
 ) -> Result<axum::Json<Option<RuleConfiguration>>, AppError> {
@@ -4,6 +4,7 @@ use warden_core::configuration::rule::{
     RuleConfiguration, RuleConfigurationRequest, UpdateRuleRequest,
     mutate_rule_configuration_server::MutateRuleConfiguration,
 };
+use warden_middleware::version::Versioned;
 
 use crate::{
     server::{error::AppError, http_svc::TAG_RULES, version::Version},
@@ -13,15 +14,14 @@ use crate::{
 /// Update the routing configuration
 #[utoipa::path(
     put,
-    path = "/{version}/rule",
+    path = "/rule",
     params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3"),
         RuleConfigurationRequest
     ),
-    responses((
-        status = OK,
-        body = RuleConfiguration
-    )),
+    responses(
+        (status = OK, body = RuleConfiguration),
+        (status = BAD_REQUEST, description = "The configuration is invalid, such as a query template that would not run"),
+    ),
     operation_id = "update rule configuration", // https://github.com/juhaku/utoipa/issues/1170
     tag = TAG_RULES,
 )
@@ -29,7 +29,7 @@ use crate::{
 #[axum::debug_handler]
 #[tracing::instrument(skip(state))]
 pub async fn update_rule_config(
-    version: Version,
+    Versioned(version): Versioned<Version>,
     Query(params): Query<RuleConfigurationRequest>,
     State(state): State<AppHandle>,
     axum::Json(body): axum::Json<RuleConfiguration>,
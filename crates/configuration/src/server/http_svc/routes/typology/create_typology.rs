@@ -2,6 +2,7 @@ use axum::{extract::State, response::IntoResponse};
 use warden_core::configuration::typology::{
     TypologyConfiguration, mutate_typologies_server::MutateTypologies,
 };
+use warden_middleware::version::Versioned;
 
 use crate::{
     server::{error::AppError, http_svc::TAG_TYPOLOGIES, version::Version},
@@ -11,10 +12,7 @@ use crate::{
 /// Create rule configuration
 #[utoipa::path(
     post,
-    path = "/{version}/typology",
-    params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3"),
-    ),
+    path = "/typology",
     responses((
         status = CREATED,
         body = TypologyConfiguration,
@@ -26,7 +24,7 @@ use crate::{
 #[axum::debug_handler]
 #[tracing::instrument(skip(state))]
 pub async fn create_typology(
-    version: Version,
+    Versioned(version): Versioned<Version>,
     State(state): State<AppHandle>,
     axum::Json(body): axum::Json<TypologyConfiguration>,
 ) -> Result<impl IntoResponse, AppError> {
@@ -6,20 +6,19 @@ use warden_core::configuration::typology::{
 };
 
 use crate::{
-    server::{error::AppError, http_svc::TAG_TYPOLOGIES, version::Version},
+    server::{error::AppError, http_svc::TAG_TYPOLOGIES},
     state::AppHandle,
 };
 
 /// Get the typology configuration
 #[utoipa::path(
     delete,
-    path = "/{version}/typology",
+    path = "/typology",
     responses((
         status = OK,
         body = TypologyConfiguration
     )),
     params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3"),
         DeleteTypologyConfigurationRequest
     ),
     operation_id = "delete_typology_configuration", // https://github.com/juhaku/utoipa/issues/1170
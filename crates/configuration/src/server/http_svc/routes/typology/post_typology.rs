@@ -3,6 +3,7 @@ use warden_core::configuration::typology::{
     TypologyConfiguration, TypologyConfigurationRequest, UpdateTypologyConfigRequest,
     mutate_typologies_server::MutateTypologies,
 };
+use warden_middleware::version::Versioned;
 
 use crate::{
     server::{error::AppError, http_svc::TAG_TYPOLOGIES, version::Version},
@@ -12,9 +13,8 @@ use crate::{
 /// Update typology configuration
 #[utoipa::path(
     put,
-    path = "/{version}/typology",
+    path = "/typology",
     params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3"),
         TypologyConfigurationRequest,
     ),
     responses((
@@ -28,7 +28,7 @@ use crate::{
 #[axum::debug_handler]
 #[tracing::instrument(skip(state))]
 pub async fn update(
-    version: Version,
+    Versioned(version): Versioned<Version>,
     Query(params): Query<TypologyConfigurationRequest>,
     State(state): State<AppHandle>,
     axum::Json(body): axum::Json<TypologyConfiguration>,
@@ -1,35 +1,31 @@
-use std::collections::HashMap;
+use std::fmt;
 
-use axum::{
-    RequestPartsExt,
-    extract::{FromRequestParts, Path},
-    http::{StatusCode, request::Parts},
-    response::{IntoResponse, Response},
-};
 use utoipa::ToSchema;
+use warden_middleware::version::ApiVersion;
 
-#[derive(Debug, ToSchema)]
+/// A version of the HTTP API
+///
+/// Each version is served under its own prefix, e.g. `/api/v0`, and the version that handled a
+/// request is available to its handler through [Versioned](warden_middleware::version::Versioned)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
 pub enum Version {
     V0,
 }
 
-impl<S> FromRequestParts<S> for Version
-where
-    S: Send + Sync,
-{
-    type Rejection = Response;
-
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let params: Path<HashMap<String, String>> =
-            parts.extract().await.map_err(IntoResponse::into_response)?;
+impl Version {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Version::V0 => "v0",
+        }
+    }
+}
 
-        let version = params
-            .get("version")
-            .ok_or_else(|| (StatusCode::NOT_FOUND, "version param missing").into_response())?;
+impl ApiVersion for Version {
+    const ALL: &'static [Self] = &[Version::V0];
+}
 
-        match version.as_str() {
-            "v0" => Ok(Version::V0),
-            _ => Err((StatusCode::NOT_FOUND, "unknown version").into_response()),
-        }
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
     }
 }
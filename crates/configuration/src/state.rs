@@ -11,6 +11,7 @@ use std::{ops::Deref, sync::Arc};
 use tracing::{Instrument, info_span, instrument, trace};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use warden_core::configuration::ReloadEvent;
+use warden_middleware::auth::Authenticator;
 use warden_stack::{Configuration, cache::RedisManager, redis::AsyncCommands};
 
 use crate::{
@@ -40,6 +41,8 @@ pub struct Services {
 pub struct AppState {
     pub services: Services,
     pub app_config: LocalConfig,
+    /// Validates the credentials of API requests
+    pub authenticator: Arc<Authenticator>,
 }
 
 impl AppState {
@@ -58,9 +61,12 @@ impl AppState {
 
         create_stream(&services.jetstream, &local_config.nats).await?;
 
+        let authenticator = Authenticator::new(&local_config.auth).map_err(anyhow::Error::new)?;
+
         Ok(AppHandle(Arc::new(Self {
             services,
             app_config: local_config,
+            authenticator: Arc::new(authenticator),
         })))
     }
 }
@@ -16,7 +16,8 @@ impl ToRedisArgs for CacheKey<'_> {
         let value = match self {
             CacheKey::ActiveRouting => "routing.active".into(),
             CacheKey::Routing(uuid) => format!("routing.{uuid}"),
-            CacheKey::Rule { id, version } => format!("rule.{id}.{version}"),
+            // entries cached while `Case.value` was field 3 would decode without their values
+            CacheKey::Rule { id, version } => format!("rule.v2.{id}.{version}"),
             CacheKey::Typology { id, version } => format!("typology.{id}.{version}"),
         };
 
@@ -1,5 +1,12 @@
+use tonic::Status;
 use uuid::Uuid;
-use warden_core::configuration::rule::{RuleConfiguration, RuleConfigurationRequest};
+use warden_core::{
+    configuration::{
+        rule::{Band, Case, RuleConfiguration, RuleConfigurationRequest},
+        template::QueryRule,
+    },
+    google::protobuf::value::Kind,
+};
 
 use crate::state::cache_key::CacheKey;
 
@@ -22,3 +29,166 @@ impl<'a> From<&'a RuleConfigurationRequest> for CacheKey<'a> {
         }
     }
 }
+
+/// Rejects a configuration the rule-executor would be unable to evaluate
+fn validate(configuration: &RuleConfiguration) -> Result<(), Status> {
+    let Some(config) = configuration.configuration.as_ref() else {
+        return Ok(());
+    };
+
+    validate_bands(&config.bands).map_err(Status::invalid_argument)?;
+    validate_cases(&config.cases).map_err(Status::invalid_argument)?;
+
+    let parameters = config.parameters.clone().map(serde_json::Value::from);
+
+    if let Some(parameters) = parameters
+        && QueryRule::is_defined_by(&parameters)
+    {
+        QueryRule::from_parameters(parameters)
+            .and_then(|rule| rule.template(&config.time_frames))
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Either every band sets its severity or none does, in which case bands are taken to be listed
+/// from the least to the most suspicious
+fn validate_bands(bands: &[Band]) -> Result<(), String> {
+    let severities = bands.iter().filter(|band| band.severity.is_some()).count();
+
+    if severities == 0 || severities == bands.len() {
+        Ok(())
+    } else {
+        Err("either every band or none must set a severity".to_owned())
+    }
+}
+
+/// Cases must match numbers, strings or bools, each value at most once, and have exactly one
+/// default case for everything else
+fn validate_cases(cases: &[Case]) -> Result<(), String> {
+    if cases.is_empty() {
+        return Ok(());
+    }
+
+    let mut defaults = 0;
+    for (i, case) in cases.iter().enumerate() {
+        let Some(kind) = case.value.as_ref().map(|value| value.kind.as_ref()) else {
+            defaults += 1;
+            continue;
+        };
+
+        if !matches!(
+            kind,
+            Some(Kind::NumberValue(_) | Kind::StringValue(_) | Kind::BoolValue(_))
+        ) {
+            return Err(format!(
+                "case {} must match a number, string or bool",
+                case.sub_rule_ref
+            ));
+        }
+
+        if let Some(duplicate) = cases[..i]
+            .iter()
+            .find(|other| other.value.as_ref().map(|value| value.kind.as_ref()) == Some(kind))
+        {
+            return Err(format!(
+                "cases {} and {} match the same value",
+                duplicate.sub_rule_ref, case.sub_rule_ref
+            ));
+        }
+    }
+
+    match defaults {
+        0 => Err("cases need a default case, without a value".to_owned()),
+        1 => Ok(()),
+        _ => Err("cases can only have one default case".to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use warden_core::google::protobuf::Value;
+
+    use super::*;
+
+    fn case(sub_rule_ref: &str, kind: Option<Kind>) -> Case {
+        Case {
+            sub_rule_ref: sub_rule_ref.to_owned(),
+            reason: String::default(),
+            value: kind.map(|kind| Value { kind: Some(kind) }),
+        }
+    }
+
+    fn band(sub_rule_ref: &str, severity: Option<i32>) -> Band {
+        Band {
+            sub_rule_ref: sub_rule_ref.to_owned(),
+            severity,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn requires_severity_on_every_band_or_none() {
+        let ordered = [band(".01", None), band(".02", None)];
+        assert_eq!(validate_bands(&ordered), Ok(()));
+
+        // a new payee of rule 030 is more suspicious than a familiar one
+        let ranked = [band(".01", Some(1)), band(".02", Some(0))];
+        assert_eq!(validate_bands(&ranked), Ok(()));
+
+        let partial = [band(".01", Some(1)), band(".02", None)];
+        assert!(validate_bands(&partial).is_err());
+    }
+
+    #[test]
+    fn accepts_cases_with_default() {
+        let cases = [
+            case(".01", Some(Kind::StringValue("MOBL".into()))),
+            case(".02", Some(Kind::BoolValue(true))),
+            case(".03", Some(Kind::NumberValue(1.0))),
+            case(".04", Some(Kind::StringValue("1".into()))),
+            case(".00", None),
+        ];
+
+        assert_eq!(validate_cases(&cases), Ok(()));
+        assert_eq!(validate_cases(&[]), Ok(()));
+    }
+
+    #[test]
+    fn rejects_duplicate_values() {
+        let cases = [
+            case(".01", Some(Kind::NumberValue(1.0))),
+            case(".02", Some(Kind::NumberValue(1.0))),
+            case(".00", None),
+        ];
+
+        assert_eq!(
+            validate_cases(&cases),
+            Err("cases .01 and .02 match the same value".to_owned())
+        );
+    }
+
+    #[test]
+    fn requires_one_default() {
+        let cases = [case(".01", Some(Kind::BoolValue(true)))];
+        assert!(validate_cases(&cases).is_err());
+
+        let cases = [
+            case(".01", Some(Kind::BoolValue(true))),
+            case(".00", None),
+            case(".02", None),
+        ];
+        assert!(validate_cases(&cases).is_err());
+    }
+
+    #[test]
+    fn rejects_other_values() {
+        let cases = [
+            case(".01", Some(Kind::ListValue(Default::default()))),
+            case(".00", None),
+        ];
+
+        assert!(validate_cases(&cases).is_err());
+    }
+}
@@ -12,7 +12,10 @@ use warden_core::configuration::{
 };
 
 use crate::state::{
-    AppHandle, cache_key::CacheKey, invalidate_cache, publish_reload, rule::RuleRow,
+    AppHandle,
+    cache_key::CacheKey,
+    invalidate_cache, publish_reload,
+    rule::{RuleRow, validate},
 };
 
 #[async_trait]
@@ -22,6 +25,8 @@ impl MutateRuleConfiguration for AppHandle {
         request: Request<RuleConfiguration>,
     ) -> Result<Response<RuleConfiguration>, Status> {
         let request = request.into_inner();
+        validate(&request)?;
+
         let span = info_span!("create.configuration.rule");
         span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
         span.set_attribute(attribute::DB_OPERATION_NAME, "insert");
@@ -59,6 +64,7 @@ impl MutateRuleConfiguration for AppHandle {
         let request = request.into_inner();
 
         let config = request.configuration.expect("configuration to be provided");
+        validate(&config)?;
 
         let span = info_span!("update.configuration.routing");
         span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
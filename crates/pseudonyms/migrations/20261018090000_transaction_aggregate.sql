@@ -0,0 +1,108 @@
+-- Counts and sums of transactions per account, in hourly and daily buckets (UTC). Rows are kept
+-- up to date by the pseudonyms service, in the transaction that inserts the relationship
+create table transaction_aggregate (
+    account varchar not null references account(id),
+    direction varchar not null check (direction in ('source', 'destination')),
+    resolution varchar not null check (resolution in ('hour', 'day')),
+    bucket timestamptz not null,
+    tx_tp varchar not null,
+    tx_sts varchar,
+    amt_ccy varchar(3) not null,
+    count bigint not null,
+    amt numeric not null,
+    unique nulls not distinct (account, direction, tx_tp, resolution, bucket, tx_sts, amt_ccy)
+);
+
+insert into transaction_aggregate (
+    account,
+    direction,
+    resolution,
+    bucket,
+    tx_tp,
+    tx_sts,
+    amt_ccy,
+    count,
+    amt
+)
+select
+    a.account,
+    a.direction,
+    r.resolution,
+    date_trunc(r.resolution, tr.cre_dt_tm, 'UTC'),
+    tr.tx_tp,
+    tr.tx_sts,
+    tr.amt_ccy,
+    count(*),
+    sum(tr.amt_unit::numeric + tr.amt_nanos::numeric / 1000000000)
+from transaction_relationship tr
+cross join lateral (
+    values (tr.source, 'source'), (tr.destination, 'destination')
+) as a(account, direction)
+cross join (values ('hour'), ('day')) as r(resolution)
+where a.account is not null
+group by 1, 2, 3, 4, 5, 6, 7;
+
+-- the edges of a window are read from the raw rows
+create index idx_transaction_destination_time
+  on transaction_relationship (destination, cre_dt_tm desc);
+
+-- Transactions of an account between from_time and to_time (both inclusive), by status and
+-- currency. Whole days and hours come from transaction_aggregate, and only the edges of the
+-- window that do not fill an hour are read from transaction_relationship
+create function transaction_window(
+    for_account varchar,
+    for_direction varchar,
+    for_tx_tp varchar,
+    from_time timestamptz,
+    to_time timestamptz
+)
+returns table (tx_sts varchar, amt_ccy varchar, count bigint, amt numeric)
+language sql stable
+as $$
+    with bounds as (
+        select
+            date_trunc('hour', from_time - interval '1 microsecond', 'UTC') + interval '1 hour' as hour_start,
+            date_trunc('hour', to_time, 'UTC') as hour_end,
+            date_trunc('day', from_time - interval '1 microsecond', 'UTC') + interval '24 hours' as day_start,
+            date_trunc('day', to_time, 'UTC') as day_end
+    ),
+    aggregated as (
+        select ta.tx_sts, ta.amt_ccy, ta.count, ta.amt
+        from transaction_aggregate ta, bounds b
+        where ta.account = for_account
+          and ta.direction = for_direction
+          and ta.tx_tp = for_tx_tp
+          and (
+              (ta.resolution = 'day' and ta.bucket >= b.day_start and ta.bucket < b.day_end)
+              or (
+                  ta.resolution = 'hour'
+                  and ta.bucket >= b.hour_start
+                  and ta.bucket < b.hour_end
+                  and not (ta.bucket >= b.day_start and ta.bucket < b.day_end)
+              )
+          )
+    ),
+    edges as (
+        select
+            tr.tx_sts,
+            tr.amt_ccy,
+            1::bigint as count,
+            tr.amt_unit::numeric + tr.amt_nanos::numeric / 1000000000 as amt
+        from transaction_relationship tr, bounds b
+        where (
+              (for_direction = 'source' and tr.source = for_account)
+              or (for_direction = 'destination' and tr.destination = for_account)
+          )
+          and tr.tx_tp = for_tx_tp
+          and tr.cre_dt_tm >= from_time
+          and tr.cre_dt_tm <= to_time
+          and (tr.cre_dt_tm < b.hour_start or tr.cre_dt_tm >= b.hour_end)
+    )
+    select w.tx_sts, w.amt_ccy, sum(w.count)::bigint, sum(w.amt)
+    from (
+        select * from aggregated
+        union all
+        select * from edges
+    ) as w
+    group by w.tx_sts, w.amt_ccy
+$$;
@@ -0,0 +1,3 @@
+-- the holders of an account are looked up by the account
+create index idx_account_holder_destination
+  on account_holder (destination);
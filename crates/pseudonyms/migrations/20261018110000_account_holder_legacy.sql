@@ -0,0 +1,9 @@
+-- Links made while the debtor was recorded as a holder of the creditor's account cannot be told
+-- apart from correct ones, so they are marked as legacy. Replaying the stored transactions
+-- through CreatePseudonym relinks every party with its own account and clears the mark, and
+-- is idempotent for the relationships and their aggregates. What is still legacy afterwards
+-- was wrong, and can be removed with:
+--
+--   delete from account_holder where legacy;
+alter table account_holder add column legacy boolean not null default true;
+alter table account_holder alter column legacy set default false;
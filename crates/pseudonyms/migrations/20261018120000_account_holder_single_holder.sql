@@ -0,0 +1,7 @@
+-- Every transaction linked its creditor with the creditor account, so an account with a single
+-- holder is held by the creditor of every transaction into it. Those links were correct, and
+-- need not wait for the replay (`warden replay-pseudonyms`) to clear their mark
+update account_holder set legacy = false
+where legacy and destination in (
+    select destination from account_holder group by destination having count(*) = 1
+);
@@ -1,7 +1,8 @@
-mod interceptor;
-use interceptor::MyInterceptor;
 use tokio::signal;
-use warden_core::pseudonyms::transaction_relationship::mutate_pseudonym_server::MutatePseudonymServer;
+use warden_core::pseudonyms::transaction_relationship::{
+    mutate_pseudonym_server::MutatePseudonymServer, query_pseudonyms_server::QueryPseudonymsServer,
+};
+use warden_middleware::grpc::interceptor::VerifyCaller;
 
 use tonic::transport::{Server, server::TcpIncoming};
 use tracing::info;
@@ -20,11 +21,21 @@ pub async fn serve(state: AppHandle, tx: tokio::sync::oneshot::Sender<u16>) -> a
 
     info!(addr = ?socket_addr, "starting server");
 
-    Server::builder()
+    let mut server = Server::builder();
+    if let Some(tls) = &state.config.grpc.tls {
+        info!(mutual = tls.mutual, "serving over tls");
+        server = server.tls_config(tls.server()?)?;
+    }
+
+    server
         .trace_fn(|_| tracing::trace_span!(env!("CARGO_PKG_NAME"), "otel.kind" = "server"))
         .add_service(MutatePseudonymServer::with_interceptor(
             state.clone(),
-            MyInterceptor,
+            VerifyCaller::new(&state.config.grpc),
+        ))
+        .add_service(QueryPseudonymsServer::with_interceptor(
+            state.clone(),
+            VerifyCaller::new(&state.config.grpc),
         ))
         .serve_with_incoming_shutdown(TcpIncoming::from(listener), shutdown_signal(state))
         .await?;
@@ -1,4 +1,5 @@
 mod mutate;
+mod query;
 
 use std::{
     net::{Ipv6Addr, SocketAddr},
@@ -26,6 +26,19 @@ impl MutatePseudonym for AppHandle {
             .transaction_relationship
             .ok_or_else(|| tonic::Status::data_loss("transaction_relationship"))?;
 
+        for (field, value) in [
+            ("debtor_id", &body.debtor_id),
+            ("debtor_account_id", &body.debtor_account_id),
+            ("creditor_id", &body.creditor_id),
+            ("creditor_account_id", &body.creditor_account_id),
+        ] {
+            if value.trim().is_empty() {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "{field} is required"
+                )));
+            }
+        }
+
         debug!("starting database transaction");
         let mut tx = self
             .services
@@ -71,31 +84,37 @@ impl MutatePseudonym for AppHandle {
         let cre_dt_tm = transaction_relationship.cre_dt_tm.expect("cre_dt_tm");
         let cre_dt_tm = OffsetDateTime::try_from(cre_dt_tm).expect("offset date time conv");
 
+        // a transfer between accounts of the same entity names it twice
+        let mut entities = vec![body.debtor_id.to_string(), body.creditor_id.to_string()];
+        entities.dedup();
+
         trace!("inserting entity");
         sqlx::query!(
             "insert into entity (id, cre_dt_tm)
             select * from unnest($1::text[], $2::timestamptz[])
             on conflict (id)
-            do update set cre_dt_tm = excluded.cre_dt_tm
+            do update set cre_dt_tm = greatest(entity.cre_dt_tm, excluded.cre_dt_tm)
             ",
-            &[body.creditor_id.to_string(), body.debtor_id.to_string()],
-            &[cre_dt_tm, cre_dt_tm]
+            &entities,
+            &vec![cre_dt_tm; entities.len()]
         )
         .execute(&mut *tx)
         .instrument(span)
         .await
         .map_err(|e| tonic::Status::internal(e.to_string()))?;
 
-        let account_holders = &[
+        // each party holds their own account
+        let mut account_holders = vec![
             (
                 body.debtor_id.to_string(),
-                body.creditor_account_id.to_string(),
+                body.debtor_account_id.to_string(),
             ),
             (
                 body.creditor_id.to_string(),
                 body.creditor_account_id.to_string(),
             ),
         ];
+        account_holders.dedup();
         let mut deb_holder = vec![];
         let mut cred_holder = vec![];
         let mut dts = vec![];
@@ -117,7 +136,9 @@ impl MutatePseudonym for AppHandle {
             "insert into account_holder (source, destination, cre_dt_tm)
             select * from unnest($1::text[], $2::text[], $3::timestamptz[])
             on conflict (source, destination)
-            do update set cre_dt_tm = excluded.cre_dt_tm
+            do update set
+                cre_dt_tm = greatest(account_holder.cre_dt_tm, excluded.cre_dt_tm),
+                legacy = false
             ",
             &deb_holder,
             &cred_holder,
@@ -146,7 +167,7 @@ impl MutatePseudonym for AppHandle {
             .amt
             .ok_or_else(|| tonic::Status::data_loss("amt"))?;
 
-        sqlx::query!(
+        let inserted = sqlx::query!(
             "
             insert into transaction_relationship (
                 source,
@@ -164,6 +185,7 @@ impl MutatePseudonym for AppHandle {
                 tx_sts
             )
             values ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            on conflict (msg_id, end_to_end_id, tx_tp, pmt_inf_id) do nothing
             ",
             transaction_relationship.from,
             transaction_relationship.to,
@@ -182,7 +204,67 @@ impl MutatePseudonym for AppHandle {
         .execute(&mut *tx)
         .instrument(span)
         .await
-        .map_err(|e| tonic::Status::internal(e.to_string()))?;
+        .map_err(|e| tonic::Status::internal(e.to_string()))?
+        .rows_affected();
+
+        // a replayed transaction relinks its parties, but is only counted once
+        if inserted == 0 {
+            debug!("transaction relationship exists, leaving aggregates as they are");
+        } else {
+            trace!("updating transaction aggregates");
+            let span = info_span!("create.pseudonyms.transaction_aggregate");
+            span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+            span.set_attribute(attribute::DB_OPERATION_NAME, "upsert");
+            span.set_attribute(attribute::DB_COLLECTION_NAME, "transaction_aggregate");
+            span.set_attribute(
+                attribute::DB_QUERY_TEXT,
+                "insert into transaction_aggregate",
+            );
+
+            sqlx::query!(
+                "
+                insert into transaction_aggregate (
+                    account,
+                    direction,
+                    resolution,
+                    bucket,
+                    tx_tp,
+                    tx_sts,
+                    amt_ccy,
+                    count,
+                    amt
+                )
+                select
+                    a.account,
+                    a.direction,
+                    r.resolution,
+                    date_trunc(r.resolution, $3::timestamptz, 'UTC'),
+                    $4::varchar,
+                    $5::varchar,
+                    $6::varchar,
+                    1,
+                    $7::bigint::numeric + $8::integer::numeric / 1000000000
+                from (values ($1::varchar, 'source'), ($2::varchar, 'destination')) as a(account, direction)
+                cross join (values ('hour'), ('day')) as r(resolution)
+                on conflict (account, direction, tx_tp, resolution, bucket, tx_sts, amt_ccy)
+                do update set
+                    count = transaction_aggregate.count + excluded.count,
+                    amt = transaction_aggregate.amt + excluded.amt
+                ",
+                transaction_relationship.from,
+                transaction_relationship.to,
+                cre_dt_tm,
+                transaction_relationship.tx_tp,
+                transaction_relationship.tx_sts,
+                amt.currency_code,
+                amt.units,
+                amt.nanos,
+            )
+            .execute(&mut *tx)
+            .instrument(span)
+            .await
+            .map_err(|e| tonic::Status::internal(e.to_string()))?;
+        }
 
         let span = info_span!("transaction.commit");
         span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
@@ -0,0 +1,362 @@
+use time::OffsetDateTime;
+use tonic::{Request, Response, Status};
+use tracing::{Instrument, debug, info_span, instrument};
+use warden_core::{
+    google::r#type::{LatLng, Money},
+    pseudonyms::{
+        account::Account,
+        account_holder::AccountHolder,
+        entity::Entity,
+        transaction_relationship::{
+            GetAccountRequest, GetEntityRequest, ListAccountHoldersRequest,
+            ListAccountHoldersResponse, ListEntityAccountsRequest,
+            ListTransactionRelationshipsRequest, ListTransactionRelationshipsResponse,
+            TransactionRelationship, query_pseudonyms_server::QueryPseudonyms,
+        },
+    },
+};
+use warden_stack::{
+    opentelemetry_semantic_conventions::attribute, tracing_opentelemetry::OpenTelemetrySpanExt,
+};
+
+use crate::state::AppHandle;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+
+struct AccountHolderRow {
+    source: String,
+    destination: String,
+    cre_dt_tm: OffsetDateTime,
+}
+
+impl From<AccountHolderRow> for AccountHolder {
+    fn from(value: AccountHolderRow) -> Self {
+        Self {
+            entity_id: value.source,
+            account_id: value.destination,
+            cre_dt_tm: Some(value.cre_dt_tm.into()),
+        }
+    }
+}
+
+struct TransactionRelationshipRow {
+    source: Option<String>,
+    destination: Option<String>,
+    amt_unit: i64,
+    amt_ccy: String,
+    amt_nanos: i32,
+    cre_dt_tm: OffsetDateTime,
+    end_to_end_id: String,
+    msg_id: String,
+    pmt_inf_id: String,
+    tx_tp: String,
+    lat: Option<f64>,
+    lon: Option<f64>,
+    tx_sts: Option<String>,
+}
+
+impl From<TransactionRelationshipRow> for TransactionRelationship {
+    fn from(value: TransactionRelationshipRow) -> Self {
+        Self {
+            from: value.source.unwrap_or_default(),
+            to: value.destination.unwrap_or_default(),
+            amt: Some(Money {
+                currency_code: value.amt_ccy,
+                units: value.amt_unit,
+                nanos: value.amt_nanos,
+            }),
+            cre_dt_tm: Some(value.cre_dt_tm.into()),
+            end_to_end_id: value.end_to_end_id,
+            latlng: value
+                .lat
+                .zip(value.lon)
+                .map(|(latitude, longitude)| LatLng {
+                    latitude,
+                    longitude,
+                }),
+            msg_id: value.msg_id,
+            pmt_inf_id: value.pmt_inf_id,
+            tx_tp: value.tx_tp,
+            tx_sts: value.tx_sts,
+        }
+    }
+}
+
+/// The last relationship on a page, which the next page starts after
+///
+/// Relationships are ordered by time and then by their primary key, and the token is the hex
+/// encoded JSON of those columns
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PageToken {
+    /// Microseconds since the epoch, the precision postgres keeps
+    cre_dt_tm: i64,
+    msg_id: String,
+    end_to_end_id: String,
+    tx_tp: String,
+    pmt_inf_id: String,
+}
+
+impl PageToken {
+    fn encode(&self) -> String {
+        serde_json::to_vec(self)
+            .unwrap_or_default()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    fn cre_dt_tm(&self) -> Option<OffsetDateTime> {
+        OffsetDateTime::from_unix_timestamp_nanos(i128::from(self.cre_dt_tm) * 1000).ok()
+    }
+
+    fn decode(token: &str) -> Option<Self> {
+        let bytes = (0..token.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(token.get(i..i + 2)?, 16).ok())
+            .collect::<Option<Vec<_>>>()?;
+
+        serde_json::from_slice(&bytes).ok()
+    }
+}
+
+impl From<&TransactionRelationshipRow> for PageToken {
+    fn from(value: &TransactionRelationshipRow) -> Self {
+        Self {
+            cre_dt_tm: (value.cre_dt_tm.unix_timestamp_nanos() / 1000) as i64,
+            msg_id: value.msg_id.to_owned(),
+            end_to_end_id: value.end_to_end_id.to_owned(),
+            tx_tp: value.tx_tp.to_owned(),
+            pmt_inf_id: value.pmt_inf_id.to_owned(),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl QueryPseudonyms for AppHandle {
+    #[instrument(skip(self, request), err(Debug))]
+    async fn get_account(
+        &self,
+        request: Request<GetAccountRequest>,
+    ) -> Result<Response<Account>, Status> {
+        let data = request.into_inner();
+        if data.id.is_empty() {
+            return Err(Status::invalid_argument("id is required"));
+        }
+
+        let span = info_span!("get.pseudonyms.account");
+        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+        span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+        span.set_attribute(attribute::DB_COLLECTION_NAME, "account");
+        span.set_attribute(attribute::DB_QUERY_PARAMETER, data.id.to_string());
+        span.set_attribute("otel.kind", "client");
+
+        let account = sqlx::query_scalar!("select id from account where id = $1", data.id)
+            .fetch_optional(&self.services.postgres)
+            .instrument(span)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found("account"))?;
+
+        Ok(Response::new(Account { id: account }))
+    }
+
+    #[instrument(skip(self, request), err(Debug))]
+    async fn get_entity(
+        &self,
+        request: Request<GetEntityRequest>,
+    ) -> Result<Response<Entity>, Status> {
+        let data = request.into_inner();
+        if data.id.is_empty() {
+            return Err(Status::invalid_argument("id is required"));
+        }
+
+        let span = info_span!("get.pseudonyms.entity");
+        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+        span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+        span.set_attribute(attribute::DB_COLLECTION_NAME, "entity");
+        span.set_attribute(attribute::DB_QUERY_PARAMETER, data.id.to_string());
+        span.set_attribute("otel.kind", "client");
+
+        let entity = sqlx::query!("select id, cre_dt_tm from entity where id = $1", data.id)
+            .fetch_optional(&self.services.postgres)
+            .instrument(span)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .ok_or_else(|| Status::not_found("entity"))?;
+
+        Ok(Response::new(Entity {
+            id: entity.id,
+            cre_dt_tm: Some(entity.cre_dt_tm.into()),
+        }))
+    }
+
+    #[instrument(skip(self, request), err(Debug))]
+    async fn list_entity_accounts(
+        &self,
+        request: Request<ListEntityAccountsRequest>,
+    ) -> Result<Response<ListAccountHoldersResponse>, Status> {
+        let data = request.into_inner();
+        if data.entity_id.is_empty() {
+            return Err(Status::invalid_argument("entity_id is required"));
+        }
+
+        let span = info_span!("get.pseudonyms.account_holder");
+        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+        span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+        span.set_attribute(attribute::DB_COLLECTION_NAME, "account_holder");
+        span.set_attribute(attribute::DB_QUERY_PARAMETER, data.entity_id.to_string());
+        span.set_attribute("otel.kind", "client");
+
+        let account_holders = sqlx::query_as!(
+            AccountHolderRow,
+            "select source, destination, cre_dt_tm from account_holder
+            where source = $1
+            order by cre_dt_tm desc, destination",
+            data.entity_id,
+        )
+        .fetch_all(&self.services.postgres)
+        .instrument(span)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+        debug!(count = account_holders.len(), "found accounts");
+
+        Ok(Response::new(ListAccountHoldersResponse {
+            account_holders: account_holders
+                .into_iter()
+                .map(AccountHolder::from)
+                .collect(),
+        }))
+    }
+
+    #[instrument(skip(self, request), err(Debug))]
+    async fn list_account_holders(
+        &self,
+        request: Request<ListAccountHoldersRequest>,
+    ) -> Result<Response<ListAccountHoldersResponse>, Status> {
+        let data = request.into_inner();
+        if data.account_id.is_empty() {
+            return Err(Status::invalid_argument("account_id is required"));
+        }
+
+        let span = info_span!("get.pseudonyms.account_holder");
+        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+        span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+        span.set_attribute(attribute::DB_COLLECTION_NAME, "account_holder");
+        span.set_attribute(attribute::DB_QUERY_PARAMETER, data.account_id.to_string());
+        span.set_attribute("otel.kind", "client");
+
+        let account_holders = sqlx::query_as!(
+            AccountHolderRow,
+            "select source, destination, cre_dt_tm from account_holder
+            where destination = $1
+            order by cre_dt_tm desc, source",
+            data.account_id,
+        )
+        .fetch_all(&self.services.postgres)
+        .instrument(span)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+        debug!(count = account_holders.len(), "found account holders");
+
+        Ok(Response::new(ListAccountHoldersResponse {
+            account_holders: account_holders
+                .into_iter()
+                .map(AccountHolder::from)
+                .collect(),
+        }))
+    }
+
+    #[instrument(skip(self, request), err(Debug))]
+    async fn list_transaction_relationships(
+        &self,
+        request: Request<ListTransactionRelationshipsRequest>,
+    ) -> Result<Response<ListTransactionRelationshipsResponse>, Status> {
+        let data = request.into_inner();
+        if data.account_id.is_empty() {
+            return Err(Status::invalid_argument("account_id is required"));
+        }
+
+        let page_size = match data.page_size {
+            0 => DEFAULT_PAGE_SIZE,
+            size => size.min(MAX_PAGE_SIZE),
+        };
+
+        let from = data.from.map(OffsetDateTime::try_from).transpose();
+        let to = data.to.map(OffsetDateTime::try_from).transpose();
+        let (Ok(from), Ok(to)) = (from, to) else {
+            return Err(Status::invalid_argument("invalid time range"));
+        };
+
+        let after = data
+            .page_token
+            .as_deref()
+            .map(|value| {
+                PageToken::decode(value)
+                    .and_then(|token| Some((token.cre_dt_tm()?, token)))
+                    .ok_or_else(|| Status::invalid_argument("invalid page_token"))
+            })
+            .transpose()?;
+        let after = after.as_ref();
+
+        let span = info_span!("get.pseudonyms.transaction_relationship");
+        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+        span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+        span.set_attribute(attribute::DB_COLLECTION_NAME, "transaction_relationship");
+        span.set_attribute(attribute::DB_QUERY_PARAMETER, data.account_id.to_string());
+        span.set_attribute("otel.kind", "client");
+
+        let mut transaction_relationships = sqlx::query_as!(
+            TransactionRelationshipRow,
+            "select source, destination, amt_unit, amt_ccy, amt_nanos, cre_dt_tm, end_to_end_id,
+                msg_id, pmt_inf_id, tx_tp, lat, lon, tx_sts
+            from transaction_relationship
+            where (source = $1 or destination = $1)
+              and ($2::timestamptz is null or cre_dt_tm >= $2)
+              and ($3::timestamptz is null or cre_dt_tm < $3)
+              and ($4::timestamptz is null
+                or (cre_dt_tm, msg_id, end_to_end_id, tx_tp, pmt_inf_id)
+                  < ($4, $5::text, $6::text, $7::text, $8::text))
+            order by cre_dt_tm desc, msg_id desc, end_to_end_id desc, tx_tp desc, pmt_inf_id desc
+            limit $9",
+            data.account_id,
+            from,
+            to,
+            after.map(|(cre_dt_tm, _)| *cre_dt_tm),
+            after.map(|(_, token)| token.msg_id.as_str()),
+            after.map(|(_, token)| token.end_to_end_id.as_str()),
+            after.map(|(_, token)| token.tx_tp.as_str()),
+            after.map(|(_, token)| token.pmt_inf_id.as_str()),
+            i64::from(page_size) + 1,
+        )
+        .fetch_all(&self.services.postgres)
+        .instrument(span)
+        .await
+        .map_err(|e| Status::internal(e.to_string()))?;
+
+        // one more row than requested tells whether there is another page
+        let next_page_token = if transaction_relationships.len() > page_size as usize {
+            transaction_relationships.truncate(page_size as usize);
+            transaction_relationships
+                .last()
+                .map(|value| PageToken::from(value).encode())
+        } else {
+            None
+        };
+
+        debug!(
+            count = transaction_relationships.len(),
+            "listed transaction relationships"
+        );
+
+        Ok(Response::new(ListTransactionRelationshipsResponse {
+            transaction_relationships: transaction_relationships
+                .into_iter()
+                .map(TransactionRelationship::from)
+                .collect(),
+            next_page_token,
+        }))
+    }
+}
@@ -1 +1,2 @@
 mod mutate;
+mod query;
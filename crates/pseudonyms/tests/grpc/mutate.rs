@@ -1 +1,2 @@
+mod caller;
 mod create;
@@ -0,0 +1,62 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use tonic::{Code, IntoRequest};
+use warden_core::pseudonyms::transaction_relationship::CreatePseudonymRequest;
+use warden_middleware::grpc::interceptor::SERVICE_TOKEN_HEADER;
+use warden_stack::grpc::Caller;
+
+use crate::helpers::TestApp;
+
+// sha256 of "warden-token"
+const WARDEN_TOKEN: &str = "cc15b1adf37e86ad84ca9fca097d883fb4ef66771d5e5cd5ab73270f4f699b34";
+
+#[sqlx::test]
+async fn unknown_caller(pool: PgPool) -> Result<()> {
+    let mut app = TestApp::with_config(pool, |config| {
+        config.grpc.callers = vec![Caller {
+            name: "warden".to_string(),
+            sha256: WARDEN_TOKEN.to_string(),
+        }];
+    })
+    .await;
+
+    let response = app
+        .mutate
+        .create_pseudonym(CreatePseudonymRequest::default().into_request())
+        .await;
+    assert!(response.is_err_and(|value| value.code() == Code::Unauthenticated));
+
+    let mut request = CreatePseudonymRequest::default().into_request();
+    request
+        .metadata_mut()
+        .insert(SERVICE_TOKEN_HEADER, "fsp001-token".parse()?);
+    let response = app.mutate.create_pseudonym(request).await;
+    assert!(response.is_err_and(|value| value.code() == Code::Unauthenticated));
+
+    // let through, then rejected for the empty request
+    let mut request = CreatePseudonymRequest::default().into_request();
+    request
+        .metadata_mut()
+        .insert(SERVICE_TOKEN_HEADER, "warden-token".parse()?);
+    let response = app.mutate.create_pseudonym(request).await;
+    assert!(response.is_err_and(|value| value.code() == Code::DataLoss));
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn no_callers(pool: PgPool) -> Result<()> {
+    let mut app = TestApp::with_config(pool, |config| {
+        config.grpc.allow_anonymous = false;
+    })
+    .await;
+
+    let mut request = CreatePseudonymRequest::default().into_request();
+    request
+        .metadata_mut()
+        .insert(SERVICE_TOKEN_HEADER, "warden-token".parse()?);
+    let response = app.mutate.create_pseudonym(request).await;
+    assert!(response.is_err_and(|value| value.code() == Code::Unauthenticated));
+
+    Ok(())
+}
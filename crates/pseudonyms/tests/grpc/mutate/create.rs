@@ -1,9 +1,10 @@
 use anyhow::Result;
 use sqlx::PgPool;
+use time::{Duration, OffsetDateTime};
 use tonic::{Code, IntoRequest};
 use warden_core::pseudonyms::transaction_relationship::CreatePseudonymRequest;
 
-use crate::helpers::TestApp;
+use crate::helpers::{TestApp, create_request};
 
 #[sqlx::test]
 async fn data_loss_tr(pool: PgPool) -> Result<()> {
@@ -21,3 +22,195 @@ async fn data_loss_tr(pool: PgPool) -> Result<()> {
 
     Ok(())
 }
+
+#[sqlx::test]
+async fn maintains_aggregates(pool: PgPool) -> Result<()> {
+    let mut app = TestApp::new(pool.clone()).await;
+
+    // 2026-01-01T10:15:00Z, with two transactions in its hour and one in the next
+    let start = OffsetDateTime::from_unix_timestamp(1_767_262_500)?;
+    for (end_to_end_id, cre_dt_tm, units, tx_sts) in [
+        ("e2e-1", start, 10, Some("ACCC")),
+        ("e2e-2", start + Duration::minutes(30), 20, Some("ACCC")),
+        ("e2e-3", start + Duration::hours(1), 40, None),
+    ] {
+        app.mutate
+            .create_pseudonym(
+                create_request(end_to_end_id, cre_dt_tm, units, tx_sts).into_request(),
+            )
+            .await?;
+    }
+
+    let (count, amt): (i64, f64) = sqlx::query_as(
+        "select count, amt::float8 from transaction_aggregate
+         where account = 'cdtr-acct' and direction = 'destination'
+           and resolution = 'hour' and bucket = date_trunc('hour', $1::timestamptz, 'UTC')
+           and tx_sts = 'ACCC'",
+    )
+    .bind(start)
+    .fetch_one(&pool)
+    .await?;
+    assert_eq!((count, amt), (2, 31.0));
+
+    let (count, amt): (i64, f64) = sqlx::query_as(
+        "select sum(count)::bigint, sum(amt)::float8 from transaction_aggregate
+         where account = 'dbtr-acct' and direction = 'source' and resolution = 'day'",
+    )
+    .fetch_one(&pool)
+    .await?;
+    assert_eq!((count, amt), (3, 71.5));
+
+    // windows that cut through buckets count the same as the raw rows
+    for (from, to, expected) in [
+        (start - Duration::days(2), start + Duration::days(2), 3),
+        (start, start + Duration::hours(1), 3),
+        (start + Duration::minutes(1), start + Duration::hours(1), 2),
+        (
+            start + Duration::minutes(1),
+            start + Duration::minutes(59),
+            1,
+        ),
+        (start - Duration::hours(3), start + Duration::minutes(29), 1),
+    ] {
+        let count: i64 = sqlx::query_scalar(
+            "select coalesce(sum(w.count), 0)::bigint
+             from transaction_window('cdtr-acct', 'destination', 'pacs.008.001.12', $1, $2) w",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_one(&pool)
+        .await?;
+        assert_eq!(count, expected, "{from} to {to}");
+    }
+
+    Ok(())
+}
+
+async fn account_holders(pool: &PgPool) -> Result<Vec<(String, String)>> {
+    Ok(sqlx::query_as(
+        "select source, destination from account_holder where not legacy order by source",
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+#[sqlx::test]
+async fn links_parties_to_own_accounts(pool: PgPool) -> Result<()> {
+    let mut app = TestApp::new(pool.clone()).await;
+
+    let cre_dt_tm = OffsetDateTime::from_unix_timestamp(1_767_262_500)?;
+    app.mutate
+        .create_pseudonym(create_request("e2e-1", cre_dt_tm, 10, Some("ACCC")).into_request())
+        .await?;
+
+    assert_eq!(
+        account_holders(&pool).await?,
+        [
+            ("cdtr".to_owned(), "cdtr-acct".to_owned()),
+            ("dbtr".to_owned(), "dbtr-acct".to_owned()),
+        ]
+    );
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn replays_are_idempotent(pool: PgPool) -> Result<()> {
+    let mut app = TestApp::new(pool.clone()).await;
+
+    // a link made before the debtor was paired with their own account
+    sqlx::query("insert into entity (id, cre_dt_tm) values ('dbtr', now())")
+        .execute(&pool)
+        .await?;
+    sqlx::query("insert into account (id) values ('cdtr-acct')")
+        .execute(&pool)
+        .await?;
+    sqlx::query(
+        "insert into account_holder (source, destination, cre_dt_tm, legacy)
+         values ('dbtr', 'cdtr-acct', now(), true)",
+    )
+    .execute(&pool)
+    .await?;
+
+    let cre_dt_tm = OffsetDateTime::from_unix_timestamp(1_767_262_500)?;
+    for _ in 0..2 {
+        app.mutate
+            .create_pseudonym(create_request("e2e-1", cre_dt_tm, 10, Some("ACCC")).into_request())
+            .await?;
+    }
+
+    let relationships: i64 = sqlx::query_scalar("select count(*) from transaction_relationship")
+        .fetch_one(&pool)
+        .await?;
+    assert_eq!(relationships, 1);
+
+    let counted: i64 = sqlx::query_scalar(
+        "select count from transaction_aggregate
+         where account = 'dbtr-acct' and direction = 'source' and resolution = 'day'",
+    )
+    .fetch_one(&pool)
+    .await?;
+    assert_eq!(counted, 1);
+
+    // replaying an older transaction does not move the entity back in time
+    let rewound: bool = sqlx::query_scalar(
+        "select cre_dt_tm < now() - interval '1 day' from entity where id = 'dbtr'",
+    )
+    .fetch_one(&pool)
+    .await?;
+    assert!(!rewound);
+
+    // only the wrong link is left to clean up
+    let legacy: Vec<(String, String)> =
+        sqlx::query_as("select source, destination from account_holder where legacy")
+            .fetch_all(&pool)
+            .await?;
+    assert_eq!(legacy, [("dbtr".to_owned(), "cdtr-acct".to_owned())]);
+    assert_eq!(account_holders(&pool).await?.len(), 2);
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn links_transfers_to_self_once(pool: PgPool) -> Result<()> {
+    let mut app = TestApp::new(pool.clone()).await;
+
+    let mut request = create_request(
+        "e2e-1",
+        OffsetDateTime::from_unix_timestamp(1_767_262_500)?,
+        10,
+        Some("ACCC"),
+    );
+    request.creditor_id = request.debtor_id.to_owned();
+    request.creditor_account_id = request.debtor_account_id.to_owned();
+    if let Some(transaction_relationship) = request.transaction_relationship.as_mut() {
+        transaction_relationship.to = transaction_relationship.from.to_owned();
+    }
+
+    app.mutate.create_pseudonym(request.into_request()).await?;
+
+    assert_eq!(
+        account_holders(&pool).await?,
+        [("dbtr".to_owned(), "dbtr-acct".to_owned())]
+    );
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn requires_parties(pool: PgPool) -> Result<()> {
+    let mut app = TestApp::new(pool).await;
+
+    let mut request = create_request(
+        "e2e-1",
+        OffsetDateTime::from_unix_timestamp(1_767_262_500)?,
+        10,
+        Some("ACCC"),
+    );
+    request.debtor_account_id = String::default();
+
+    let response = app.mutate.create_pseudonym(request.into_request()).await;
+    assert!(response.is_err_and(|value| value.code() == Code::InvalidArgument));
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use anyhow::Result;
+use sqlx::PgPool;
+use time::{Duration, OffsetDateTime};
+use tonic::{Code, IntoRequest};
+use warden_core::pseudonyms::transaction_relationship::{
+    GetAccountRequest, GetEntityRequest, ListAccountHoldersRequest, ListEntityAccountsRequest,
+    ListTransactionRelationshipsRequest,
+};
+
+use crate::helpers::{TestApp, create_request};
+
+#[sqlx::test]
+async fn get_account_and_entity(pool: PgPool) -> Result<()> {
+    let mut app = TestApp::new(pool).await;
+
+    let cre_dt_tm = OffsetDateTime::from_unix_timestamp(1_767_262_500)?;
+    app.mutate
+        .create_pseudonym(create_request("e2e-1", cre_dt_tm, 10, Some("ACCC")).into_request())
+        .await?;
+
+    let account = app
+        .query
+        .get_account(GetAccountRequest {
+            id: "cdtr-acct".to_owned(),
+        })
+        .await?
+        .into_inner();
+    assert_eq!(account.id, "cdtr-acct");
+
+    let entity = app
+        .query
+        .get_entity(GetEntityRequest {
+            id: "dbtr".to_owned(),
+        })
+        .await?
+        .into_inner();
+    assert_eq!(entity.id, "dbtr");
+    assert_eq!(entity.cre_dt_tm, Some(cre_dt_tm.into()));
+
+    let response = app
+        .query
+        .get_entity(GetEntityRequest {
+            id: "unknown".to_owned(),
+        })
+        .await;
+    assert!(response.is_err_and(|value| value.code() == Code::NotFound));
+
+    let response = app.query.get_account(GetAccountRequest::default()).await;
+    assert!(response.is_err_and(|value| value.code() == Code::InvalidArgument));
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn list_account_holders(pool: PgPool) -> Result<()> {
+    let mut app = TestApp::new(pool).await;
+
+    let cre_dt_tm = OffsetDateTime::from_unix_timestamp(1_767_262_500)?;
+    app.mutate
+        .create_pseudonym(create_request("e2e-1", cre_dt_tm, 10, Some("ACCC")).into_request())
+        .await?;
+
+    let accounts = app
+        .query
+        .list_entity_accounts(ListEntityAccountsRequest {
+            entity_id: "cdtr".to_owned(),
+        })
+        .await?
+        .into_inner()
+        .account_holders;
+    let accounts: Vec<_> = accounts
+        .iter()
+        .map(|value| (value.entity_id.as_str(), value.account_id.as_str()))
+        .collect();
+    assert_eq!(accounts, [("cdtr", "cdtr-acct")]);
+
+    let holders = app
+        .query
+        .list_account_holders(ListAccountHoldersRequest {
+            account_id: "cdtr-acct".to_owned(),
+        })
+        .await?
+        .into_inner()
+        .account_holders;
+    let holders: Vec<_> = holders
+        .iter()
+        .map(|value| (value.entity_id.as_str(), value.account_id.as_str()))
+        .collect();
+    assert_eq!(holders, [("cdtr", "cdtr-acct")]);
+
+    let holders = app
+        .query
+        .list_account_holders(ListAccountHoldersRequest {
+            account_id: "unknown".to_owned(),
+        })
+        .await?
+        .into_inner()
+        .account_holders;
+    assert!(holders.is_empty());
+
+    Ok(())
+}
+
+#[sqlx::test]
+async fn list_transaction_relationships(pool: PgPool) -> Result<()> {
+    let mut app = TestApp::new(pool).await;
+
+    let start = OffsetDateTime::from_unix_timestamp(1_767_262_500)?;
+    for (i, end_to_end_id) in ["e2e-1", "e2e-2", "e2e-3", "e2e-4"].into_iter().enumerate() {
+        let cre_dt_tm = start + Duration::minutes(i as i64);
+        app.mutate
+            .create_pseudonym(create_request(end_to_end_id, cre_dt_tm, 10, None).into_request())
+            .await?;
+    }
+
+    // the first transaction is outside the window
+    let mut request = ListTransactionRelationshipsRequest {
+        account_id: "dbtr-acct".to_owned(),
+        from: Some((start + Duration::seconds(30)).into()),
+        page_size: 2,
+        ..Default::default()
+    };
+
+    let mut pages = vec![];
+    loop {
+        let response = app
+            .query
+            .list_transaction_relationships(request.clone())
+            .await?
+            .into_inner();
+        pages.push(
+            response
+                .transaction_relationships
+                .into_iter()
+                .map(|value| value.end_to_end_id)
+                .collect::<Vec<_>>(),
+        );
+
+        match response.next_page_token {
+            Some(token) => request.page_token = Some(token),
+            None => break,
+        }
+    }
+    assert_eq!(pages, [vec!["e2e-4", "e2e-3"], vec!["e2e-2"]]);
+
+    request.page_token = Some("not a token".to_owned());
+    let response = app.query.list_transaction_relationships(request).await;
+    assert!(response.is_err_and(|value| value.code() == Code::InvalidArgument));
+
+    Ok(())
+}
@@ -1,7 +1,15 @@
 use sqlx::PgPool;
+use time::OffsetDateTime;
 use tokio::sync::oneshot;
 use tonic::transport::Channel;
-use warden_core::pseudonyms::transaction_relationship::mutate_pseudonym_client::MutatePseudonymClient;
+use warden_core::{
+    google::r#type::Money,
+    pseudonyms::transaction_relationship::{
+        CreatePseudonymRequest, TransactionRelationship,
+        mutate_pseudonym_client::MutatePseudonymClient,
+        query_pseudonyms_client::QueryPseudonymsClient,
+    },
+};
 use warden_pseudonyms::state::{AppHandle, AppState, Services};
 use warden_stack::{Configuration, cache::RedisManager};
 
@@ -10,10 +18,15 @@ use std::sync::Arc;
 pub struct TestApp {
     _state: AppHandle,
     pub mutate: MutatePseudonymClient<Channel>,
+    pub query: QueryPseudonymsClient<Channel>,
 }
 
 impl TestApp {
     pub async fn new(pool: PgPool) -> Self {
+        Self::with_config(pool, |_| {}).await
+    }
+
+    pub async fn with_config(pool: PgPool, configure: impl FnOnce(&mut Configuration)) -> Self {
         let (tx, rx) = oneshot::channel();
         // Set port to 0 so tests can spawn multiple servers on OS assigned ports.
         //
@@ -26,6 +39,7 @@ impl TestApp {
 
         let mut config = config.try_deserialize::<Configuration>().unwrap();
         config.application.port = 0;
+        configure(&mut config);
 
         let cache = RedisManager::new(&config.cache).await.unwrap();
 
@@ -46,9 +60,45 @@ impl TestApp {
             .await
             .expect("expect server to be running");
 
+        let query_client = QueryPseudonymsClient::connect(addr.to_string())
+            .await
+            .expect("expect server to be running");
+
         Self {
             _state: state,
             mutate: mutation_client,
+            query: query_client,
         }
     }
 }
+
+/// A transfer of `units` ZAR and fifty cents from `dbtr-acct` to `cdtr-acct`
+pub fn create_request(
+    end_to_end_id: &str,
+    cre_dt_tm: OffsetDateTime,
+    units: i64,
+    tx_sts: Option<&str>,
+) -> CreatePseudonymRequest {
+    CreatePseudonymRequest {
+        transaction_relationship: Some(TransactionRelationship {
+            from: "dbtr-acct".to_owned(),
+            to: "cdtr-acct".to_owned(),
+            amt: Some(Money {
+                currency_code: "ZAR".to_owned(),
+                units,
+                nanos: 500_000_000,
+            }),
+            cre_dt_tm: Some(cre_dt_tm.into()),
+            end_to_end_id: end_to_end_id.to_owned(),
+            msg_id: "msg".to_owned(),
+            pmt_inf_id: "pmt-inf".to_owned(),
+            tx_tp: "pacs.008.001.12".to_owned(),
+            tx_sts: tx_sts.map(ToOwned::to_owned),
+            ..Default::default()
+        }),
+        debtor_id: "dbtr".to_owned(),
+        debtor_account_id: "dbtr-acct".to_owned(),
+        creditor_id: "cdtr".to_owned(),
+        creditor_account_id: "cdtr-acct".to_owned(),
+    }
+}
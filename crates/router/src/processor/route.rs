@@ -34,6 +34,12 @@ pub async fn route(message: async_nats::jetstream::Message, state: AppHandle) ->
                 warden_core::message::payload::Transaction::Pacs002(pacs002_document) => {
                     &pacs002_document.f_i_to_f_i_pmt_sts_rpt.grp_hdr.msg_id
                 }
+                warden_core::message::payload::Transaction::Pain001(pain001_document) => {
+                    &pain001_document.cstmr_cdt_trf_initn.grp_hdr.msg_id
+                }
+                warden_core::message::payload::Transaction::Pain013(pain013_document) => {
+                    &pain013_document.cdtr_pmt_actvtn_req.grp_hdr.msg_id
+                }
             };
             span.record("msg_id", msg_id);
 
@@ -3,7 +3,6 @@ use std::sync::Arc;
 use async_nats::jetstream::Context;
 use moka::future::Cache;
 use tokio::sync::RwLock;
-use tonic::transport::Endpoint;
 use tracing::error;
 use warden_core::configuration::routing::{
     RoutingConfiguration, query_routing_client::QueryRoutingClient,
@@ -31,8 +30,9 @@ pub struct AppState {
 impl AppState {
     pub async fn new(services: Services, configuration: Configuration) -> anyhow::Result<Self> {
         let config: LocalConfig = serde_json::from_value(configuration.misc.clone())?;
-        let channel = Endpoint::new(config.config_endpoint.to_string())?
-            .connect()
+        let channel = configuration
+            .grpc
+            .connect(config.config_endpoint.to_string())
             .await
             .inspect_err(|e| {
                 error!(
@@ -41,7 +41,8 @@ impl AppState {
                 )
             })?;
 
-        let query_routing_client = QueryRoutingClient::with_interceptor(channel, MyInterceptor);
+        let query_routing_client =
+            QueryRoutingClient::with_interceptor(channel, MyInterceptor::new(&configuration.grpc)?);
 
         Ok(Self {
             services,
@@ -6,6 +6,8 @@ use serde::Deserialize;
 #[serde(rename_all = "kebab-case")]
 pub struct LocalConfig {
     pub config_endpoint: Arc<str>,
+    /// Milliseconds a rule query may run before it is cancelled
+    pub query_timeout: u64,
     pub nats: Nats,
 }
 
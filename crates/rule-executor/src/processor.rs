@@ -1,6 +1,6 @@
 mod publish;
 mod reload;
-mod rule;
+pub(crate) mod rule;
 
 use std::sync::Arc;
 
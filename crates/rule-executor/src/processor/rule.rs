@@ -1,9 +1,21 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+mod common;
 mod configuration;
 mod determine_outcome;
+mod exit;
+mod registry;
+mod rule_004;
+mod rule_030;
 mod rule_901;
+mod rule_902;
+mod rule_903;
+mod rule_904;
+mod template;
+mod time_frame;
+
+pub(crate) use registry::{Registry, Rule};
 
 use async_nats::jetstream;
 use opentelemetry::global;
@@ -55,6 +67,12 @@ pub async fn process_rule(message: jetstream::Message, state: AppHandle) -> Resu
         warden_core::message::payload::Transaction::Pacs002(pacs002_document) => {
             &pacs002_document.f_i_to_f_i_pmt_sts_rpt.grp_hdr.msg_id
         }
+        warden_core::message::payload::Transaction::Pain001(pain001_document) => {
+            &pain001_document.cstmr_cdt_trf_initn.grp_hdr.msg_id
+        }
+        warden_core::message::payload::Transaction::Pain013(pain013_document) => {
+            &pain013_document.cdtr_pmt_actvtn_req.grp_hdr.msg_id
+        }
     };
     span.record("msg_id", msg_id);
 
@@ -67,7 +85,15 @@ pub async fn process_rule(message: jetstream::Message, state: AppHandle) -> Resu
         .await
         .unwrap();
 
-    match rule_901::process_901(&config, &payload, state.clone()).await {
+    let result = match state.registry.resolve(&config) {
+        Some(rule) => rule.run(&config, &payload, state.clone()).await,
+        None => {
+            error!(registered = ?state.registry, "rule has no implementation");
+            Ok(common::not_implemented(&config))
+        }
+    };
+
+    match result {
         Ok(res) => {
             debug!(outcome = ?res.reason, "rule executed");
             payload.rule_result = Some(res);
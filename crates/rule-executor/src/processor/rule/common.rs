@@ -0,0 +1,262 @@
+use anyhow::{Result, anyhow};
+use serde::de::DeserializeOwned;
+use time::OffsetDateTime;
+use tracing::error;
+use warden_core::{
+    configuration::rule::{Band, Case, RuleConfiguration},
+    message::{DataCache, Payload, RuleResult, payload::Transaction},
+};
+
+use crate::processor::rule::exit::Exit;
+
+/// A successful pacs.002 a rule is evaluated against
+pub(super) struct StatusReport<'a> {
+    /// When the status report was created
+    pub cre_dt_tm: OffsetDateTime,
+    pub data_cache: &'a DataCache,
+}
+
+/// An empty result for `configuration`
+pub(super) fn rule_result(configuration: &RuleConfiguration) -> RuleResult {
+    RuleResult {
+        id: configuration.id.to_string(),
+        version: configuration.version.to_string(),
+        ..Default::default()
+    }
+}
+
+pub(super) fn bands(configuration: &RuleConfiguration) -> Result<&[Band]> {
+    configuration
+        .configuration
+        .as_ref()
+        .map(|value| value.bands.as_slice())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("no bands available"))
+}
+
+pub(super) fn cases(configuration: &RuleConfiguration) -> Result<&[Case]> {
+    configuration
+        .configuration
+        .as_ref()
+        .map(|value| value.cases.as_slice())
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| anyhow!("no cases available"))
+}
+
+/// The `parameters` of `configuration`. Rules without any read them as `()`
+pub(super) fn parameters<T: DeserializeOwned>(configuration: &RuleConfiguration) -> Result<T> {
+    let parameters = configuration
+        .configuration
+        .as_ref()
+        .and_then(|value| value.parameters.clone())
+        .map(serde_json::Value::from)
+        .unwrap_or_default();
+
+    let params = serde_json::from_value(parameters)
+        .inspect_err(|e| error!("failed to deserailise params: {e:?}"))?;
+
+    Ok(params)
+}
+
+/// The result for a rule the executor has no implementation of. The `.err` exit condition is
+/// used when the rule configures one
+pub(super) fn not_implemented(configuration: &RuleConfiguration) -> RuleResult {
+    let rule_result = rule_result(configuration);
+
+    Exit::NotImplemented
+        .resolve(configuration, rule_result.clone())
+        .unwrap_or_else(|_| RuleResult {
+            sub_rule_ref: Exit::NotImplemented.sub_rule_ref().to_owned(),
+            reason: format!(
+                "rule {}@{} is not implemented by this executor",
+                configuration.id, configuration.version
+            ),
+            ..rule_result
+        })
+}
+
+/// The status report in `payload`, exiting unless it reports a successful transaction
+pub(super) fn status_report(payload: &Payload) -> Result<StatusReport<'_>> {
+    let Some(Transaction::Pacs002(pacs002_document)) = payload.transaction.as_ref() else {
+        return Err(Exit::UnsupportedTransaction.into());
+    };
+
+    let tx_sts = pacs002_document
+        .f_i_to_f_i_pmt_sts_rpt
+        .tx_inf_and_sts
+        .first()
+        .ok_or(Exit::MissingData("tx_inf_and_sts"))?;
+
+    if tx_sts.tx_sts().ne("ACCC") {
+        return Err(Exit::UnsuccessfulTransaction.into());
+    }
+
+    let cre_dt_tm = pacs002_document
+        .f_i_to_f_i_pmt_sts_rpt
+        .grp_hdr
+        .cre_dt_tm
+        .try_into()?;
+
+    let data_cache = payload
+        .data_cache
+        .as_ref()
+        .ok_or(Exit::MissingData("data_cache"))?;
+
+    Ok(StatusReport {
+        cre_dt_tm,
+        data_cache,
+    })
+}
+
+/// Account the test status reports are sent to
+#[cfg(test)]
+pub(super) const DEBTOR: &str = "debtor-account";
+/// Account the test status reports are sent from
+#[cfg(test)]
+pub(super) const CREDITOR: &str = "creditor-account";
+
+/// A rule configuration with `bands`, given as lower limit, upper limit and sub rule ref
+#[cfg(test)]
+pub(super) fn test_configuration(
+    id: &str,
+    bands: &[(Option<f64>, Option<f64>, &str)],
+    time_frames: &[f64],
+    parameters: serde_json::Value,
+) -> RuleConfiguration {
+    use warden_core::configuration::rule::{Config, OutcomeResult, Timeframe};
+
+    RuleConfiguration {
+        id: id.to_owned(),
+        version: "1.0.0".to_owned(),
+        configuration: Some(Config {
+            parameters: Some(parameters.try_into().unwrap()),
+            exit_conditions: vec![OutcomeResult {
+                sub_rule_ref: ".x01".to_owned(),
+                reason: "Insufficient transaction history".to_owned(),
+            }],
+            bands: bands
+                .iter()
+                .map(|(lower_limit, upper_limit, sub_rule_ref)| Band {
+                    sub_rule_ref: (*sub_rule_ref).to_owned(),
+                    reason: String::default(),
+                    lower_limit: *lower_limit,
+                    upper_limit: *upper_limit,
+                    severity: None,
+                })
+                .collect(),
+            time_frames: time_frames
+                .iter()
+                .map(|threshold| Timeframe {
+                    threshold: *threshold,
+                })
+                .collect(),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// A successful status report for a transfer of `amount` from [DEBTOR] to [CREDITOR]
+#[cfg(test)]
+pub(super) fn test_payload(cre_dt_tm: &str, amount: f64, ccy: &str) -> Payload {
+    use warden_core::iso20022::{pacs002::Pacs002Document, pacs008};
+
+    let document: Pacs002Document = serde_json::from_value(serde_json::json!({
+        "f_i_to_f_i_pmt_sts_rpt": {
+            "grp_hdr": {
+                "msg_id": uuid::Uuid::now_v7().to_string(),
+                "cre_dt_tm": cre_dt_tm
+            },
+            "orgnl_grp_inf_and_sts": [],
+            "splmtry_data": [],
+            "tx_inf_and_sts": [
+                {
+                    "tx_sts": "ACCC",
+                    "sts_rsn_inf": [],
+                    "splmtry_data": [],
+                    "chrgs_inf": []
+                }
+            ]
+        }
+    }))
+    .unwrap();
+
+    Payload {
+        transaction: Some(Transaction::Pacs002(document)),
+        data_cache: Some(DataCache {
+            dbtr_acct_id: DEBTOR.to_owned(),
+            cdtr_acct_id: CREDITOR.to_owned(),
+            instd_amt: Some(pacs008::ActiveOrHistoricCurrencyAndAmount {
+                ccy: ccy.to_owned(),
+                value: amount,
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Records earlier status reports, given as source, destination, creation time, amount, currency
+/// and status, along with their aggregates as the pseudonyms service does
+#[cfg(test)]
+pub(super) async fn seed(
+    pool: &sqlx::PgPool,
+    relationships: &[(&str, &str, &str, i64, &str, &str)],
+) {
+    let tx_tp = warden_core::iso20022::TransactionType::PACS002.to_string();
+
+    for (source, destination, cre_dt_tm, amount, ccy, tx_sts) in relationships {
+        let id = uuid::Uuid::now_v7().to_string();
+
+        sqlx::query("insert into account (id) values ($1), ($2) on conflict do nothing")
+            .bind(source)
+            .bind(destination)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        sqlx::query(
+            "insert into transaction_relationship (
+                source, destination, amt_unit, amt_ccy, amt_nanos, cre_dt_tm,
+                end_to_end_id, msg_id, pmt_inf_id, tx_tp, tx_sts
+            ) values ($1, $2, $3, $4, 0, $5::timestamptz, $6, $6, $6, $7, $8)",
+        )
+        .bind(source)
+        .bind(destination)
+        .bind(amount)
+        .bind(ccy)
+        .bind(cre_dt_tm)
+        .bind(&id)
+        .bind(&tx_tp)
+        .bind(tx_sts)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        sqlx::query(
+            "insert into transaction_aggregate (
+                account, direction, resolution, bucket, tx_tp, tx_sts, amt_ccy, count, amt
+            )
+            select
+                a.account, a.direction, r.resolution,
+                date_trunc(r.resolution, $3::timestamptz, 'UTC'), $4, $5, $6, 1, $7::numeric
+            from (values ($1::varchar, 'source'), ($2::varchar, 'destination'))
+                as a(account, direction)
+            cross join (values ('hour'), ('day')) as r(resolution)
+            on conflict (account, direction, tx_tp, resolution, bucket, tx_sts, amt_ccy)
+            do update set
+                count = transaction_aggregate.count + excluded.count,
+                amt = transaction_aggregate.amt + excluded.amt",
+        )
+        .bind(source)
+        .bind(destination)
+        .bind(cre_dt_tm)
+        .bind(&tx_tp)
+        .bind(tx_sts)
+        .bind(ccy)
+        .bind(amount)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+}
@@ -1,23 +1,93 @@
 use tracing::trace;
-use warden_core::{configuration::rule::Band, message::RuleResult};
+use warden_core::{
+    configuration::rule::{Band, Case},
+    google::protobuf::value::Kind,
+    message::RuleResult,
+};
 
-pub(super) fn determine_outcome(value: i64, bands: &[Band], rule_result: &mut RuleResult) {
-    trace!("calculating outcome");
-    for band in bands {
-        let value_f64 = value as f64;
-
-        if band.lower_limit.is_none_or(|lower| value_f64 >= lower)
-            && band.upper_limit.is_none_or(|upper| value_f64 < upper)
-        {
-            rule_result.sub_rule_ref = band.sub_rule_ref.to_owned();
-            rule_result.reason = band.reason.to_owned();
-            break;
+/// A result matched against cases
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum CaseValue<'a> {
+    Number(f64),
+    Text(&'a str),
+    Bool(bool),
+}
+
+impl From<f64> for CaseValue<'_> {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl<'a> From<&'a str> for CaseValue<'a> {
+    fn from(value: &'a str) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl From<bool> for CaseValue<'_> {
+    fn from(value: bool) -> Self {
+        Self::Bool(value)
+    }
+}
+
+impl CaseValue<'_> {
+    fn matches(&self, case: &Case) -> bool {
+        match (
+            self,
+            case.value.as_ref().and_then(|value| value.kind.as_ref()),
+        ) {
+            (Self::Number(value), Some(Kind::NumberValue(case))) => value == case,
+            (Self::Text(value), Some(Kind::StringValue(case))) => value == case,
+            (Self::Bool(value), Some(Kind::BoolValue(case))) => value == case,
+            _ => false,
         }
     }
 }
 
+pub(super) fn determine_outcome(
+    value: impl Into<f64>,
+    bands: &[Band],
+    rule_result: &mut RuleResult,
+) {
+    trace!("calculating outcome");
+    if let Some(index) = band_index(value.into(), bands) {
+        rule_result.sub_rule_ref = bands[index].sub_rule_ref.to_owned();
+        rule_result.reason = bands[index].reason.to_owned();
+    }
+}
+
+/// The first band `value` falls in
+pub(super) fn band_index(value: f64, bands: &[Band]) -> Option<usize> {
+    bands.iter().position(|band| {
+        band.lower_limit.is_none_or(|lower| value >= lower)
+            && band.upper_limit.is_none_or(|upper| value < upper)
+    })
+}
+
+/// Picks the case whose value is exactly `value`, or the default case when none is
+pub(super) fn determine_case<'a>(
+    value: impl Into<CaseValue<'a>>,
+    cases: &[Case],
+    rule_result: &mut RuleResult,
+) {
+    trace!("matching case");
+    let value = value.into();
+    let case = cases
+        .iter()
+        .find(|case| value.matches(case))
+        .or_else(|| cases.iter().find(|case| case.value.is_none()));
+
+    if let Some(case) = case {
+        rule_result.sub_rule_ref = case.sub_rule_ref.to_owned();
+        rule_result.reason = case.reason.to_owned();
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use warden_core::google::protobuf::Value;
+
     use super::*;
 
     fn make_band(lower: Option<f64>, upper: Option<f64>, sub_ref: &str, reason: &str) -> Band {
@@ -26,6 +96,7 @@ mod tests {
             upper_limit: upper,
             sub_rule_ref: sub_ref.to_string(),
             reason: reason.to_string(),
+            severity: None,
         }
     }
 
@@ -103,6 +174,73 @@ mod tests {
         assert_eq!(rule_result.reason, "Any value");
     }
 
+    fn make_case(value: Option<Kind>, sub_ref: &str, reason: &str) -> Case {
+        Case {
+            value: value.map(|kind| Value { kind: Some(kind) }),
+            sub_rule_ref: sub_ref.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    #[test]
+    fn matches_case_exactly() {
+        let cases = vec![
+            make_case(Some(Kind::NumberValue(1000.0)), ".01", "Multiple of 1000"),
+            make_case(Some(Kind::NumberValue(0.0)), ".02", "Not round"),
+        ];
+        let mut rule_result = RuleResult::default();
+
+        determine_case(0.0, &cases, &mut rule_result);
+        assert_eq!(rule_result.sub_rule_ref, ".02");
+
+        determine_case(1000.0, &cases, &mut rule_result);
+        assert_eq!(rule_result.sub_rule_ref, ".01");
+
+        let mut rule_result = RuleResult::default();
+        determine_case(100.0, &cases, &mut rule_result);
+        assert_eq!(rule_result, RuleResult::default());
+    }
+
+    #[test]
+    fn matches_strings_and_bools() {
+        let cases = vec![
+            make_case(
+                Some(Kind::StringValue("MOBL".into())),
+                ".01",
+                "Mobile channel",
+            ),
+            make_case(Some(Kind::BoolValue(true)), ".02", "New account"),
+            make_case(None, ".00", "Anything else"),
+        ];
+        let mut rule_result = RuleResult::default();
+
+        determine_case("MOBL", &cases, &mut rule_result);
+        assert_eq!(rule_result.sub_rule_ref, ".01");
+
+        determine_case(true, &cases, &mut rule_result);
+        assert_eq!(rule_result.sub_rule_ref, ".02");
+
+        determine_case(false, &cases, &mut rule_result);
+        assert_eq!(rule_result.sub_rule_ref, ".00");
+    }
+
+    #[test]
+    fn falls_back_to_default_case() {
+        let cases = vec![
+            make_case(None, ".00", "Anything else"),
+            make_case(Some(Kind::StringValue("1".into())), ".01", "Text one"),
+        ];
+        let mut rule_result = RuleResult::default();
+
+        // values only match cases of their own type
+        determine_case(1.0, &cases, &mut rule_result);
+        assert_eq!(rule_result.sub_rule_ref, ".00");
+
+        // the default is only taken when nothing else matches, wherever it is listed
+        determine_case("1", &cases, &mut rule_result);
+        assert_eq!(rule_result.sub_rule_ref, ".01");
+    }
+
     #[test]
     fn stops_after_first_match() {
         let bands = vec![
@@ -0,0 +1,130 @@
+use std::fmt;
+
+use anyhow::{Result, anyhow};
+use warden_core::{configuration::rule::RuleConfiguration, message::RuleResult};
+
+/// Why a rule stopped without evaluating the transaction
+///
+/// Rules return these as errors, e.g. `.ok_or(Exit::InsufficientHistory)?`, and they are turned
+/// into the exit condition the rule configures for them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Exit {
+    /// `.x00`, the transaction was not successful
+    UnsuccessfulTransaction,
+    /// `.x01`, there is not enough history to evaluate
+    InsufficientHistory,
+    /// `.x02`, data the rule needs, such as the data cache or a field of it, is missing
+    MissingData(&'static str),
+    /// `.x03`, the rule cannot evaluate this type of transaction
+    UnsupportedTransaction,
+    /// `.err`, the executor has no implementation of the rule
+    NotImplemented,
+}
+
+impl Exit {
+    /// The sub rule reference of the exit condition
+    pub(super) fn sub_rule_ref(&self) -> &'static str {
+        match self {
+            Exit::UnsuccessfulTransaction => ".x00",
+            Exit::InsufficientHistory => ".x01",
+            Exit::MissingData(_) => ".x02",
+            Exit::UnsupportedTransaction => ".x03",
+            Exit::NotImplemented => ".err",
+        }
+    }
+
+    /// The result with the exit condition `configuration` has for this exit
+    pub(super) fn resolve(
+        &self,
+        configuration: &RuleConfiguration,
+        mut rule_result: RuleResult,
+    ) -> Result<RuleResult> {
+        let sub_rule_ref = self.sub_rule_ref();
+        let exit_condition = configuration
+            .configuration
+            .as_ref()
+            .and_then(|value| {
+                value
+                    .exit_conditions
+                    .iter()
+                    .find(|value| value.sub_rule_ref.eq(sub_rule_ref))
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "rule {}@{} exited because {self}, but has no {sub_rule_ref} exit condition configured",
+                    configuration.id,
+                    configuration.version,
+                )
+            })?;
+
+        rule_result.reason = exit_condition.reason.to_owned();
+        rule_result.sub_rule_ref = exit_condition.sub_rule_ref.to_owned();
+
+        Ok(rule_result)
+    }
+}
+
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exit::UnsuccessfulTransaction => write!(f, "the transaction was not successful"),
+            Exit::InsufficientHistory => write!(f, "there is not enough history"),
+            Exit::MissingData(field) => write!(f, "{field} is missing"),
+            Exit::UnsupportedTransaction => write!(f, "the transaction type is not supported"),
+            Exit::NotImplemented => write!(f, "the rule is not implemented"),
+        }
+    }
+}
+
+impl std::error::Error for Exit {}
+
+#[cfg(test)]
+mod tests {
+    use warden_core::configuration::rule::{Config, OutcomeResult};
+
+    use super::*;
+
+    fn configuration() -> RuleConfiguration {
+        RuleConfiguration {
+            id: "901".to_owned(),
+            version: "1.0.0".to_owned(),
+            configuration: Some(Config {
+                exit_conditions: vec![OutcomeResult {
+                    sub_rule_ref: ".x01".to_owned(),
+                    reason: "Insufficient transaction history".to_owned(),
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resolves_configured_exit() {
+        let rule_result = Exit::InsufficientHistory
+            .resolve(&configuration(), RuleResult::default())
+            .unwrap();
+
+        assert_eq!(rule_result.sub_rule_ref, ".x01");
+        assert_eq!(rule_result.reason, "Insufficient transaction history");
+    }
+
+    #[test]
+    fn names_missing_exit() {
+        let e = Exit::MissingData("instd_amt")
+            .resolve(&configuration(), RuleResult::default())
+            .unwrap_err();
+
+        assert_eq!(
+            e.to_string(),
+            "rule 901@1.0.0 exited because instd_amt is missing, but has no .x02 exit condition configured"
+        );
+    }
+
+    #[test]
+    fn survives_anyhow() {
+        let e: anyhow::Error = Exit::UnsupportedTransaction.into();
+
+        assert_eq!(e.downcast_ref(), Some(&Exit::UnsupportedTransaction));
+    }
+}
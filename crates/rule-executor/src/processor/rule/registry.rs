@@ -0,0 +1,204 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use tracing::trace;
+use warden_core::{
+    configuration::{rule::RuleConfiguration, template::QueryRule},
+    message::{Payload, RuleResult},
+};
+
+use crate::{
+    processor::rule::{
+        common, exit::Exit, rule_004, rule_030, rule_901, rule_902, rule_903, rule_904,
+        template::TemplateRule,
+    },
+    state::AppHandle,
+};
+
+/// A rule the executor is able to evaluate
+#[tonic::async_trait]
+pub(crate) trait Rule: Send + Sync + 'static {
+    /// What the `parameters` of the rule's configuration deserialise to. Use `()` when the rule
+    /// takes none
+    type Parameters: DeserializeOwned + Send;
+
+    /// Checks that `configuration` has what [Rule::evaluate] needs before anything is queried.
+    /// By default, the rule must have bands
+    fn validate(&self, configuration: &RuleConfiguration) -> Result<()> {
+        common::bands(configuration).map(|_| ())
+    }
+
+    /// Evaluates `payload` against the rule
+    async fn evaluate(
+        &self,
+        configuration: &RuleConfiguration,
+        parameters: Self::Parameters,
+        payload: &Payload,
+        state: AppHandle,
+    ) -> Result<RuleResult>;
+}
+
+/// A [Rule] with its parameters erased, so rules with different parameters can be registered
+/// together
+#[tonic::async_trait]
+pub(crate) trait DynRule: Send + Sync {
+    /// Validates `configuration`, reads the parameters from it, then evaluates `payload`. Exits
+    /// are turned into their configured exit condition
+    async fn run(
+        &self,
+        configuration: &RuleConfiguration,
+        payload: &Payload,
+        state: AppHandle,
+    ) -> Result<RuleResult>;
+}
+
+#[tonic::async_trait]
+impl<R: Rule> DynRule for R {
+    async fn run(
+        &self,
+        configuration: &RuleConfiguration,
+        payload: &Payload,
+        state: AppHandle,
+    ) -> Result<RuleResult> {
+        self.validate(configuration)?;
+        let parameters = common::parameters::<R::Parameters>(configuration)?;
+
+        match self
+            .evaluate(configuration, parameters, payload, state)
+            .await
+        {
+            Err(e) => match e.downcast::<Exit>() {
+                Ok(exit) => {
+                    trace!(%exit, "rule exited");
+                    exit.resolve(configuration, common::rule_result(configuration))
+                }
+                Err(e) => Err(e),
+            },
+            result => result,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct RuleKey {
+    id: String,
+    /// [None] when the implementation serves every version of the rule
+    version: Option<String>,
+}
+
+/// Rule implementations, keyed by rule id and version
+#[derive(Clone)]
+pub(crate) struct Registry {
+    rules: HashMap<RuleKey, Arc<dyn DynRule>>,
+    template: Arc<dyn DynRule>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            rules: HashMap::default(),
+            template: Arc::new(TemplateRule),
+        }
+    }
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.rules.keys()).finish()
+    }
+}
+
+impl Registry {
+    /// A registry with every rule this executor ships with
+    pub(crate) fn standard() -> Self {
+        let mut registry = Self::default();
+        registry
+            .register("004", None, rule_004::Rule004)
+            .register("030", None, rule_030::Rule030)
+            .register("901", None, rule_901::Rule901)
+            .register("902", None, rule_902::Rule902)
+            .register("903", None, rule_903::Rule903)
+            .register("904", None, rule_904::Rule904);
+
+        registry
+    }
+
+    /// Registers `rule` for `version` of `id`, or for every version of it when [None]. An
+    /// implementation for a specific version takes precedence
+    pub(crate) fn register(
+        &mut self,
+        id: &str,
+        version: Option<&str>,
+        rule: impl Rule,
+    ) -> &mut Self {
+        let key = RuleKey {
+            id: id.to_owned(),
+            version: version.map(ToOwned::to_owned),
+        };
+        self.rules.insert(key, Arc::new(rule));
+        self
+    }
+
+    /// The implementation of `configuration`. Rules without one registered are evaluated from
+    /// their query template, if they define one
+    pub(crate) fn resolve(&self, configuration: &RuleConfiguration) -> Option<Arc<dyn DynRule>> {
+        self.get(&configuration.id, &configuration.version)
+            .or_else(|| {
+                configuration
+                    .configuration
+                    .as_ref()
+                    .and_then(|value| value.parameters.clone())
+                    .map(serde_json::Value::from)
+                    .filter(QueryRule::is_defined_by)
+                    .map(|_| Arc::clone(&self.template))
+            })
+    }
+
+    /// The implementation of `version` of rule `id`, if there is one
+    pub(crate) fn get(&self, id: &str, version: &str) -> Option<Arc<dyn DynRule>> {
+        let mut key = RuleKey {
+            id: id.to_owned(),
+            version: Some(version.to_owned()),
+        };
+
+        self.rules
+            .get(&key)
+            .or_else(|| {
+                key.version = None;
+                self.rules.get(&key)
+            })
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_standard_rules() {
+        let registry = Registry::standard();
+
+        for id in ["004", "030", "901", "902", "903", "904"] {
+            assert!(registry.get(id, "1.0.0").is_some(), "{id} to be registered");
+        }
+        assert!(registry.get("999", "1.0.0").is_none());
+    }
+
+    #[test]
+    fn prefers_exact_version() {
+        let mut registry = Registry::default();
+        registry.register("901", None, rule_901::Rule901).register(
+            "901",
+            Some("2.0.0"),
+            rule_902::Rule902,
+        );
+
+        let any = registry.get("901", "1.0.0").unwrap();
+        let exact = registry.get("901", "2.0.0").unwrap();
+
+        assert!(!Arc::ptr_eq(&any, &exact));
+        assert!(Arc::ptr_eq(&any, &registry.get("901", "3.0.0").unwrap()));
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use opentelemetry_semantic_conventions::attribute;
+use tracing::{Instrument, info_span, trace};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use warden_core::{
+    configuration::rule::RuleConfiguration,
+    iso20022::TransactionType,
+    message::{Payload, RuleResult},
+};
+
+use crate::{
+    processor::rule::{Rule, common, determine_outcome::determine_outcome, exit::Exit},
+    state::AppHandle,
+};
+
+const SECONDS_PER_DAY: f64 = 86_400.0;
+
+/// Account dormancy - debtor
+///
+/// Days since the debtor last made a successful transfer, so that a dormant account being used
+/// again stands out. Accounts that never made one exit with `.x01`
+pub(super) struct Rule004;
+
+#[tonic::async_trait]
+impl Rule for Rule004 {
+    type Parameters = ();
+
+    async fn evaluate(
+        &self,
+        configuration: &RuleConfiguration,
+        _params: (),
+        payload: &Payload,
+        state: AppHandle,
+    ) -> Result<RuleResult> {
+        let mut rule_result = common::rule_result(configuration);
+        let bands = common::bands(configuration)?;
+
+        let report = common::status_report(payload)?;
+
+        let tx_tp = TransactionType::PACS002.to_string();
+
+        let span = info_span!("rule.logic");
+        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+        span.set_attribute(attribute::DB_OPERATION_NAME, "004");
+        span.set_attribute("otel.kind", "client");
+
+        trace!("executing rule query");
+        let last_transaction = sqlx::query_scalar!(
+            "select max(tr.cre_dt_tm) from transaction_relationship tr
+                 where tr.destination = $1
+                   and tr.tx_tp = $2
+                   and tr.tx_sts = 'ACCC'
+                   and tr.cre_dt_tm < $3::timestamptz",
+            report.data_cache.dbtr_acct_id,
+            tx_tp,
+            report.cre_dt_tm,
+        )
+        .fetch_one(&state.services.postgres)
+        .instrument(span)
+        .await?;
+
+        let Some(last_transaction) = last_transaction else {
+            trace!("debtor has no earlier transactions");
+            return Err(Exit::InsufficientHistory.into());
+        };
+
+        let dormant_days = (report.cre_dt_tm - last_transaction).as_seconds_f64() / SECONDS_PER_DAY;
+
+        determine_outcome(dormant_days, bands, &mut rule_result);
+
+        Ok(rule_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        processor::rule::{
+            common::{CREDITOR, DEBTOR, seed, test_configuration, test_payload},
+            registry::DynRule,
+        },
+        state::test_state,
+    };
+
+    fn configuration() -> RuleConfiguration {
+        test_configuration(
+            "004",
+            &[(None, Some(30.0), ".01"), (Some(30.0), None, ".02")],
+            &[],
+            serde_json::Value::Null,
+        )
+    }
+
+    #[sqlx::test(migrations = "../pseudonyms/migrations")]
+    async fn bands_days_since_last_transfer(pool: PgPool) {
+        let state = test_state(pool.clone()).await;
+        let payload = test_payload("2025-08-16T12:00:00Z", 100.0, "USD");
+
+        // unsuccessful transfers do not end a dormant period
+        seed(
+            &pool,
+            &[
+                (CREDITOR, DEBTOR, "2025-06-01T12:00:00Z", 100, "USD", "ACCC"),
+                (CREDITOR, DEBTOR, "2025-08-10T12:00:00Z", 100, "USD", "RJCT"),
+            ],
+        )
+        .await;
+
+        let rule_result = Rule004
+            .run(&configuration(), &payload, state.clone())
+            .await
+            .unwrap();
+        assert_eq!(rule_result.sub_rule_ref, ".02");
+
+        seed(
+            &pool,
+            &[(CREDITOR, DEBTOR, "2025-08-10T12:00:00Z", 100, "USD", "ACCC")],
+        )
+        .await;
+
+        let rule_result = Rule004
+            .run(&configuration(), &payload, state)
+            .await
+            .unwrap();
+        assert_eq!(rule_result.sub_rule_ref, ".01");
+    }
+
+    #[sqlx::test(migrations = "../pseudonyms/migrations")]
+    async fn exits_without_history(pool: PgPool) {
+        let state = test_state(pool).await;
+        let payload = test_payload("2025-08-16T12:00:00Z", 100.0, "USD");
+
+        let rule_result = Rule004
+            .run(&configuration(), &payload, state)
+            .await
+            .unwrap();
+        assert_eq!(rule_result.sub_rule_ref, ".x01");
+    }
+}
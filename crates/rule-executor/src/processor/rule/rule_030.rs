@@ -0,0 +1,189 @@
+use anyhow::Result;
+use opentelemetry_semantic_conventions::attribute;
+use serde::Deserialize;
+use sqlx::types::BigDecimal;
+use tracing::{Instrument, info_span, trace};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use warden_core::{
+    configuration::rule::RuleConfiguration,
+    iso20022::TransactionType,
+    message::{Payload, RuleResult},
+};
+
+use crate::{
+    processor::rule::{Rule, common, time_frame},
+    state::AppHandle,
+};
+
+#[derive(Deserialize)]
+pub struct Parameters {
+    /// Used when the rule has no time frames
+    max_query_range: Option<f64>,
+}
+
+/// Transfer to unfamiliar creditor account - debtor
+///
+/// Counts the earlier successful transfers from the debtor to the same creditor account. A count
+/// of zero is a new payee, the most suspicious result, so its band must be given the highest
+/// `severity` for the time frame strategy to pick it
+pub(super) struct Rule030;
+
+#[tonic::async_trait]
+impl Rule for Rule030 {
+    type Parameters = Parameters;
+
+    async fn evaluate(
+        &self,
+        configuration: &RuleConfiguration,
+        params: Parameters,
+        payload: &Payload,
+        state: AppHandle,
+    ) -> Result<RuleResult> {
+        let mut rule_result = common::rule_result(configuration);
+        let bands = common::bands(configuration)?;
+
+        let report = common::status_report(payload)?;
+
+        let windows = time_frame::windows(configuration, params.max_query_range)?;
+
+        let tx_tp = TransactionType::PACS002.to_string();
+        let (report, tx_tp, state) = (&report, tx_tp.as_str(), &state);
+
+        time_frame::determine_windowed_outcome(
+            configuration,
+            &windows,
+            bands,
+            &mut rule_result,
+            |window| async move {
+                let range = BigDecimal::try_from(window)?;
+
+                let span = info_span!("rule.logic");
+                span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+                span.set_attribute(attribute::DB_OPERATION_NAME, "030");
+                span.set_attribute("otel.kind", "client");
+
+                trace!("executing rule query");
+                let previous_transactions = sqlx::query_scalar!(
+                    "select count(*) from transaction_relationship tr
+                 where tr.source = $1
+                   and tr.destination = $2
+                   and tr.tx_tp = $3
+                   and tr.tx_sts = 'ACCC'
+                   and extract(epoch from ($4::timestamptz - tr.cre_dt_tm)) * 1000 <= $5
+                   and tr.cre_dt_tm < $4::timestamptz",
+                    report.data_cache.cdtr_acct_id,
+                    report.data_cache.dbtr_acct_id,
+                    tx_tp,
+                    report.cre_dt_tm,
+                    range,
+                )
+                .fetch_one(&state.services.postgres)
+                .instrument(span)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("no data"))?;
+
+                Ok(previous_transactions as f64)
+            },
+        )
+        .await?;
+
+        Ok(rule_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        processor::rule::{
+            common::{CREDITOR, DEBTOR, seed, test_configuration, test_payload},
+            registry::DynRule,
+        },
+        state::test_state,
+    };
+
+    const DAY: f64 = 86_400_000.0;
+
+    fn configuration(time_frame: f64) -> RuleConfiguration {
+        test_configuration(
+            "030",
+            &[(None, Some(1.0), ".01"), (Some(1.0), None, ".02")],
+            &[time_frame],
+            serde_json::json!({}),
+        )
+    }
+
+    #[sqlx::test(migrations = "../pseudonyms/migrations")]
+    async fn bands_earlier_transfers_to_creditor(pool: PgPool) {
+        let state = test_state(pool.clone()).await;
+        let payload = test_payload("2025-08-16T12:00:00Z", 100.0, "USD");
+
+        seed(
+            &pool,
+            &[
+                (CREDITOR, DEBTOR, "2025-08-13T12:00:00Z", 100, "USD", "ACCC"),
+                (CREDITOR, DEBTOR, "2025-08-16T11:00:00Z", 100, "USD", "RJCT"),
+                (
+                    "other-account",
+                    DEBTOR,
+                    "2025-08-16T10:00:00Z",
+                    100,
+                    "USD",
+                    "ACCC",
+                ),
+            ],
+        )
+        .await;
+
+        // a new payee within the last day
+        let rule_result = Rule030
+            .run(&configuration(DAY), &payload, state.clone())
+            .await
+            .unwrap();
+        assert_eq!(rule_result.sub_rule_ref, ".01");
+        assert_eq!(rule_result.time_frame, Some(DAY));
+
+        let rule_result = Rule030
+            .run(&configuration(7.0 * DAY), &payload, state)
+            .await
+            .unwrap();
+        assert_eq!(rule_result.sub_rule_ref, ".02");
+    }
+
+    #[sqlx::test(migrations = "../pseudonyms/migrations")]
+    async fn worst_time_frame_is_new_payee(pool: PgPool) {
+        let state = test_state(pool.clone()).await;
+        let payload = test_payload("2025-08-16T12:00:00Z", 100.0, "USD");
+
+        seed(
+            &pool,
+            &[(CREDITOR, DEBTOR, "2025-08-13T12:00:00Z", 100, "USD", "ACCC")],
+        )
+        .await;
+
+        let mut configuration = test_configuration(
+            "030",
+            &[(None, Some(1.0), ".01"), (Some(1.0), None, ".02")],
+            &[DAY, 7.0 * DAY],
+            serde_json::json!({}),
+        );
+
+        // listed by count, the familiar payee of the longer time frame would be the worst
+        let rule_result = Rule030
+            .run(&configuration, &payload, state.clone())
+            .await
+            .unwrap();
+        assert_eq!(rule_result.sub_rule_ref, ".02");
+
+        let bands = &mut configuration.configuration.as_mut().unwrap().bands;
+        for (band, severity) in bands.iter_mut().zip([1, 0]) {
+            band.severity = Some(severity);
+        }
+
+        let rule_result = Rule030.run(&configuration, &payload, state).await.unwrap();
+        assert_eq!(rule_result.sub_rule_ref, ".01");
+        assert_eq!(rule_result.time_frame, Some(DAY));
+    }
+}
@@ -1,10 +1,7 @@
-use anyhow::{Result, anyhow};
-use determine_outcome::determine_outcome;
+use anyhow::Result;
 use opentelemetry_semantic_conventions::attribute;
 use serde::Deserialize;
-use sqlx::types::BigDecimal;
-use time::OffsetDateTime;
-use tracing::{Instrument, error, info_span, trace};
+use tracing::{Instrument, info_span, trace};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use warden_core::{
     configuration::rule::RuleConfiguration,
@@ -12,115 +9,76 @@ use warden_core::{
     message::{Payload, RuleResult},
 };
 
-use crate::{processor::rule::determine_outcome, state::AppHandle};
+use crate::{
+    processor::rule::{Rule, common, time_frame},
+    state::AppHandle,
+};
 
 #[derive(Deserialize)]
 pub struct Parameters {
-    max_query_range: f64,
+    /// Used when the rule has no time frames
+    max_query_range: Option<f64>,
 }
 
-pub(super) async fn process_901(
-    configuration: &RuleConfiguration,
-    payload: &Payload,
-    state: AppHandle,
-) -> Result<RuleResult> {
-    let mut rule_result = RuleResult {
-        id: configuration.id.to_string(),
-        version: configuration.version.to_string(),
-        ..Default::default()
-    };
-    let c = configuration.configuration.as_ref();
-
-    let bands = c
-        .and_then(|value| {
-            if value.bands.is_empty() {
-                None
-            } else {
-                Some(&value.bands)
-            }
-        })
-        .ok_or_else(|| anyhow!("no bands available"))?;
-
-    let exit_conditions = c
-        .and_then(|value| {
-            if value.exit_conditions.is_empty() {
-                None
-            } else {
-                Some(&value.exit_conditions)
-            }
-        })
-        .ok_or_else(|| anyhow!("no exit conditions available"))?;
-
-    let parameters = c
-        .and_then(|value| value.parameters.as_ref())
-        .ok_or_else(|| anyhow!("no parameters available"))?;
-
-    let params: Parameters = serde_json::from_value(parameters.clone().into())
-        .inspect_err(|e| error!("failed to deserailise params: {e:?}"))?;
-
-    let unsuccessful_transaction = exit_conditions
-        .iter()
-        .find(|value| value.sub_rule_ref.eq(".x00"));
+/// Number of outgoing transactions - debtor
+///
+/// The higher the count, the more suspicious, so bands may be listed in that order
+pub(super) struct Rule901;
 
-    if let Some(warden_core::message::payload::Transaction::Pacs002(pacs002_document)) =
-        payload.transaction.as_ref()
-    {
-        let tx_sts = pacs002_document
-            .f_i_to_f_i_pmt_sts_rpt
-            .tx_inf_and_sts
-            .first()
-            .ok_or_else(|| anyhow::anyhow!("tx sts to be there"))?;
+#[tonic::async_trait]
+impl Rule for Rule901 {
+    type Parameters = Parameters;
 
-        if tx_sts.tx_sts().ne("ACCC") {
-            let unsuccessful_transaction = unsuccessful_transaction
-                .ok_or_else(|| anyhow::anyhow!("no unsuccessful transaction ref"))?;
-            rule_result.reason = unsuccessful_transaction.reason.to_owned();
-            rule_result.sub_rule_ref = unsuccessful_transaction.sub_rule_ref.to_owned();
+    async fn evaluate(
+        &self,
+        configuration: &RuleConfiguration,
+        params: Parameters,
+        payload: &Payload,
+        state: AppHandle,
+    ) -> Result<RuleResult> {
+        let mut rule_result = common::rule_result(configuration);
+        let bands = common::bands(configuration)?;
 
-            return Ok(rule_result);
-        }
+        let report = common::status_report(payload)?;
 
-        let current_pacs002_timeframe: OffsetDateTime = pacs002_document
-            .f_i_to_f_i_pmt_sts_rpt
-            .grp_hdr
-            .cre_dt_tm
-            .try_into()?;
-
-        let data_cache = payload
-            .data_cache
-            .as_ref()
-            .ok_or_else(|| anyhow::anyhow!("data cache is missing"))?;
-
-        let range = BigDecimal::try_from(params.max_query_range)?;
+        let windows = time_frame::windows(configuration, params.max_query_range)?;
 
         let tx_tp = TransactionType::PACS002.to_string();
-
-        let span = info_span!("rule.logic");
-        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
-        span.set_attribute(attribute::DB_OPERATION_NAME, "901");
-        span.set_attribute("otel.kind", "client");
-
-        trace!("executing rule query");
-        let recent_transactions = sqlx::query_scalar!(
-            "select count(*) from transaction_relationship tr
-                 where tr.destination = $1
-                   and tr.tx_tp = $2
-                   and extract(epoch from ($3::timestamptz - tr.cre_dt_tm)) * 1000 <= $4
-                   and tr.cre_dt_tm <= $3::timestamptz",
-            data_cache.dbtr_acct_id,
-            tx_tp,
-            current_pacs002_timeframe,
-            range,
+        let (report, tx_tp, state) = (&report, tx_tp.as_str(), &state);
+
+        time_frame::determine_windowed_outcome(
+            configuration,
+            &windows,
+            bands,
+            &mut rule_result,
+            |window| async move {
+                let span = info_span!("rule.logic");
+                span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+                span.set_attribute(attribute::DB_OPERATION_NAME, "901");
+                span.set_attribute("otel.kind", "client");
+
+                trace!("executing rule query");
+                let recent_transactions = sqlx::query_scalar!(
+                    r#"select coalesce(sum(w.count), 0)::bigint as "count!"
+                    from transaction_window(
+                        $1, 'destination', $2,
+                        $3::timestamptz - $4::float8 * interval '1 millisecond',
+                        $3::timestamptz
+                    ) w"#,
+                    report.data_cache.dbtr_acct_id,
+                    tx_tp,
+                    report.cre_dt_tm,
+                    window,
+                )
+                .fetch_one(&state.services.postgres)
+                .instrument(span)
+                .await?;
+
+                Ok(recent_transactions as f64)
+            },
         )
-        .fetch_one(&state.services.postgres)
-        .instrument(span)
-        .await?
-        .ok_or_else(|| anyhow::anyhow!("no data"))?;
-
-        determine_outcome(recent_transactions, bands.as_ref(), &mut rule_result);
+        .await?;
 
         Ok(rule_result)
-    } else {
-        Err(anyhow::anyhow!("no valid transaction"))
     }
 }
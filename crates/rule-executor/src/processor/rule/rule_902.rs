@@ -0,0 +1,142 @@
+use anyhow::Result;
+use opentelemetry_semantic_conventions::attribute;
+use serde::Deserialize;
+use tracing::{Instrument, info_span, trace};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use warden_core::{
+    configuration::rule::RuleConfiguration,
+    iso20022::TransactionType,
+    message::{Payload, RuleResult},
+};
+
+use crate::{
+    processor::rule::{Rule, common, time_frame},
+    state::AppHandle,
+};
+
+#[derive(Deserialize)]
+pub struct Parameters {
+    /// Used when the rule has no time frames
+    max_query_range: Option<f64>,
+}
+
+/// Number of incoming transactions - creditor
+///
+/// Counts the successful transfers to the creditor within the range, including this one, as rules
+/// 030 and 903 do. Status reports are recorded from the creditor to the debtor, so the creditor is
+/// the source. The higher the count, the more suspicious, so bands may be listed in that order
+pub(super) struct Rule902;
+
+#[tonic::async_trait]
+impl Rule for Rule902 {
+    type Parameters = Parameters;
+
+    async fn evaluate(
+        &self,
+        configuration: &RuleConfiguration,
+        params: Parameters,
+        payload: &Payload,
+        state: AppHandle,
+    ) -> Result<RuleResult> {
+        let mut rule_result = common::rule_result(configuration);
+        let bands = common::bands(configuration)?;
+
+        let report = common::status_report(payload)?;
+
+        let windows = time_frame::windows(configuration, params.max_query_range)?;
+
+        let tx_tp = TransactionType::PACS002.to_string();
+        let (report, tx_tp, state) = (&report, tx_tp.as_str(), &state);
+
+        time_frame::determine_windowed_outcome(
+            configuration,
+            &windows,
+            bands,
+            &mut rule_result,
+            |window| async move {
+                let span = info_span!("rule.logic");
+                span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+                span.set_attribute(attribute::DB_OPERATION_NAME, "902");
+                span.set_attribute("otel.kind", "client");
+
+                trace!("executing rule query");
+                let recent_transactions = sqlx::query_scalar!(
+                    r#"select coalesce(sum(w.count), 0)::bigint as "count!"
+                    from transaction_window(
+                        $1, 'source', $2,
+                        $3::timestamptz - $4::float8 * interval '1 millisecond',
+                        $3::timestamptz
+                    ) w
+                    where w.tx_sts = 'ACCC'"#,
+                    report.data_cache.cdtr_acct_id,
+                    tx_tp,
+                    report.cre_dt_tm,
+                    window,
+                )
+                .fetch_one(&state.services.postgres)
+                .instrument(span)
+                .await?;
+
+                Ok(recent_transactions as f64)
+            },
+        )
+        .await?;
+
+        Ok(rule_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        processor::rule::{
+            common::{CREDITOR, DEBTOR, seed, test_configuration, test_payload},
+            registry::DynRule,
+        },
+        state::test_state,
+    };
+
+    const DAY: f64 = 86_400_000.0;
+
+    fn configuration(time_frame: f64) -> RuleConfiguration {
+        test_configuration(
+            "902",
+            &[(None, Some(3.0), ".01"), (Some(3.0), None, ".02")],
+            &[time_frame],
+            serde_json::json!({}),
+        )
+    }
+
+    #[sqlx::test(migrations = "../pseudonyms/migrations")]
+    async fn bands_successful_transfers_to_creditor(pool: PgPool) {
+        let state = test_state(pool.clone()).await;
+        let payload = test_payload("2025-08-16T12:00:00Z", 100.0, "USD");
+
+        seed(
+            &pool,
+            &[
+                (CREDITOR, DEBTOR, "2025-08-16T11:30:00Z", 100, "USD", "ACCC"),
+                (CREDITOR, DEBTOR, "2025-08-16T10:15:00Z", 100, "USD", "ACCC"),
+                (CREDITOR, DEBTOR, "2025-08-16T11:00:00Z", 100, "USD", "RJCT"),
+                (CREDITOR, DEBTOR, "2025-08-13T12:00:00Z", 100, "USD", "ACCC"),
+            ],
+        )
+        .await;
+
+        let rule_result = Rule902
+            .run(&configuration(DAY), &payload, state.clone())
+            .await
+            .unwrap();
+        assert_eq!(rule_result.sub_rule_ref, ".01");
+
+        let rule_result = Rule902
+            .run(&configuration(7.0 * DAY), &payload, state)
+            .await
+            .unwrap();
+        assert_eq!(rule_result.sub_rule_ref, ".02");
+        assert_eq!(rule_result.time_frame, Some(7.0 * DAY));
+    }
+}
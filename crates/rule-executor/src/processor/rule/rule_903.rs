@@ -0,0 +1,156 @@
+use anyhow::Result;
+use opentelemetry_semantic_conventions::attribute;
+use serde::Deserialize;
+use tracing::{Instrument, info_span, trace};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use warden_core::{
+    configuration::rule::RuleConfiguration,
+    iso20022::TransactionType,
+    message::{Payload, RuleResult},
+};
+
+use crate::{
+    processor::rule::{Rule, common, exit::Exit, time_frame},
+    state::AppHandle,
+};
+
+#[derive(Deserialize)]
+pub struct Parameters {
+    /// Used when the rule has no time frames
+    max_query_range: Option<f64>,
+}
+
+/// Value of outgoing transactions - debtor
+///
+/// Sums the successful transfers from the debtor within the range, including this one. Only
+/// transfers in the currency of this one are counted, as amounts are not converted. The higher the
+/// sum, the more suspicious, so bands may be listed in that order
+pub(super) struct Rule903;
+
+#[tonic::async_trait]
+impl Rule for Rule903 {
+    type Parameters = Parameters;
+
+    async fn evaluate(
+        &self,
+        configuration: &RuleConfiguration,
+        params: Parameters,
+        payload: &Payload,
+        state: AppHandle,
+    ) -> Result<RuleResult> {
+        let mut rule_result = common::rule_result(configuration);
+        let bands = common::bands(configuration)?;
+
+        let report = common::status_report(payload)?;
+
+        let ccy = report
+            .data_cache
+            .instd_amt
+            .as_ref()
+            .map(|value| value.ccy.as_str())
+            .ok_or(Exit::MissingData("instd_amt"))?;
+
+        let windows = time_frame::windows(configuration, params.max_query_range)?;
+
+        let tx_tp = TransactionType::PACS002.to_string();
+        let (report, tx_tp, state) = (&report, tx_tp.as_str(), &state);
+
+        time_frame::determine_windowed_outcome(
+            configuration,
+            &windows,
+            bands,
+            &mut rule_result,
+            |window| async move {
+                let span = info_span!("rule.logic");
+                span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+                span.set_attribute(attribute::DB_OPERATION_NAME, "903");
+                span.set_attribute("otel.kind", "client");
+
+                trace!("executing rule query");
+                let total = sqlx::query_scalar!(
+                    r#"select coalesce(sum(w.amt), 0)::float8 as "total!"
+                    from transaction_window(
+                        $1, 'destination', $2,
+                        $4::timestamptz - $5::float8 * interval '1 millisecond',
+                        $4::timestamptz
+                    ) w
+                    where w.tx_sts = 'ACCC'
+                      and w.amt_ccy = $3"#,
+                    report.data_cache.dbtr_acct_id,
+                    tx_tp,
+                    ccy,
+                    report.cre_dt_tm,
+                    window,
+                )
+                .fetch_one(&state.services.postgres)
+                .instrument(span)
+                .await?;
+
+                Ok(total)
+            },
+        )
+        .await?;
+
+        Ok(rule_result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        processor::rule::{
+            common::{CREDITOR, DEBTOR, seed, test_configuration, test_payload},
+            registry::DynRule,
+        },
+        state::test_state,
+    };
+
+    const DAY: f64 = 86_400_000.0;
+
+    fn configuration() -> RuleConfiguration {
+        test_configuration(
+            "903",
+            &[(None, Some(500.0), ".01"), (Some(500.0), None, ".02")],
+            &[DAY],
+            serde_json::json!({}),
+        )
+    }
+
+    #[sqlx::test(migrations = "../pseudonyms/migrations")]
+    async fn bands_successful_transfers_in_currency(pool: PgPool) {
+        let state = test_state(pool.clone()).await;
+        let payload = test_payload("2025-08-16T12:00:00Z", 100.0, "USD");
+
+        seed(
+            &pool,
+            &[
+                (CREDITOR, DEBTOR, "2025-08-16T11:00:00Z", 400, "USD", "ACCC"),
+                (CREDITOR, DEBTOR, "2025-08-16T10:00:00Z", 300, "EUR", "ACCC"),
+                (CREDITOR, DEBTOR, "2025-08-16T09:00:00Z", 500, "USD", "RJCT"),
+                (CREDITOR, DEBTOR, "2025-08-14T09:00:00Z", 500, "USD", "ACCC"),
+            ],
+        )
+        .await;
+
+        let rule_result = Rule903
+            .run(&configuration(), &payload, state.clone())
+            .await
+            .unwrap();
+        assert_eq!(rule_result.sub_rule_ref, ".01");
+
+        seed(
+            &pool,
+            &[(CREDITOR, DEBTOR, "2025-08-15T18:00:00Z", 200, "USD", "ACCC")],
+        )
+        .await;
+
+        let rule_result = Rule903
+            .run(&configuration(), &payload, state)
+            .await
+            .unwrap();
+        assert_eq!(rule_result.sub_rule_ref, ".02");
+    }
+}
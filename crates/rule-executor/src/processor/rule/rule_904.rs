@@ -0,0 +1,95 @@
+use anyhow::Result;
+use serde::Deserialize;
+use tracing::trace;
+use warden_core::{
+    configuration::rule::RuleConfiguration,
+    message::{Payload, RuleResult},
+};
+
+use crate::{
+    processor::rule::{Rule, common, determine_outcome::determine_case, exit::Exit},
+    state::AppHandle,
+};
+
+#[derive(Deserialize)]
+pub struct Parameters {
+    /// Amounts a transfer is checked to be a multiple of, such as `[1000, 100]`
+    units: Vec<f64>,
+}
+
+/// Round amount - debtor
+///
+/// Matches the largest of the configured units the instructed amount is a multiple of against
+/// the cases, or `0` when it is a multiple of none
+pub(super) struct Rule904;
+
+#[tonic::async_trait]
+impl Rule for Rule904 {
+    type Parameters = Parameters;
+
+    fn validate(&self, configuration: &RuleConfiguration) -> Result<()> {
+        common::cases(configuration).map(|_| ())
+    }
+
+    async fn evaluate(
+        &self,
+        configuration: &RuleConfiguration,
+        params: Parameters,
+        payload: &Payload,
+        _state: AppHandle,
+    ) -> Result<RuleResult> {
+        let mut rule_result = common::rule_result(configuration);
+        let cases = common::cases(configuration)?;
+
+        let report = common::status_report(payload)?;
+
+        let amount = report
+            .data_cache
+            .instd_amt
+            .as_ref()
+            .map(|value| value.value)
+            .ok_or(Exit::MissingData("instd_amt"))?;
+
+        let unit = round_unit(amount, &params.units);
+        trace!(amount, unit, "checked for a round amount");
+
+        determine_case(unit, cases, &mut rule_result);
+
+        Ok(rule_result)
+    }
+}
+
+/// The largest of `units` that `amount` is a multiple of, or `0`
+fn round_unit(amount: f64, units: &[f64]) -> f64 {
+    // compared in minor units so that binary fractions do not get in the way
+    let minor = |value: f64| (value * 100.0).round() as i64;
+    let amount = minor(amount);
+
+    units
+        .iter()
+        .copied()
+        .filter(|unit| minor(*unit) > 0 && amount != 0 && amount % minor(*unit) == 0)
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_largest_unit() {
+        let units = [100.0, 1000.0, 10.0];
+
+        assert_eq!(round_unit(5000.0, &units), 1000.0);
+        assert_eq!(round_unit(4500.0, &units), 100.0);
+        assert_eq!(round_unit(4510.0, &units), 10.0);
+        assert_eq!(round_unit(4512.5, &units), 0.0);
+        assert_eq!(round_unit(0.0, &units), 0.0);
+    }
+
+    #[test]
+    fn handles_fractional_amounts() {
+        assert_eq!(round_unit(0.3 + 0.7, &[1.0]), 1.0);
+        assert_eq!(round_unit(12.5, &[0.5]), 0.5);
+    }
+}
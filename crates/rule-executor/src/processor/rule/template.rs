@@ -0,0 +1,175 @@
+use anyhow::Result;
+use opentelemetry_semantic_conventions::attribute;
+use sqlx::Postgres;
+use tracing::{Instrument, info_span, trace};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use warden_core::{
+    configuration::{
+        rule::RuleConfiguration,
+        template::{Bind, QueryRule, QueryTemplate},
+    },
+    iso20022::TransactionType,
+    message::{Payload, RuleResult},
+};
+
+use crate::{
+    processor::rule::{
+        Rule,
+        common::{self, StatusReport},
+        determine_outcome::determine_outcome,
+        exit::Exit,
+        time_frame,
+    },
+    state::AppHandle,
+};
+
+/// A rule defined by the query template in its parameters
+///
+/// The query runs in a read only transaction, and is cancelled once it has run for the configured
+/// `query-timeout`. A query that returns no row or a null exits with `.x01`. A template binding
+/// `{max_query_range}` runs once for each of the rule's time frames. Whether a higher value is more
+/// suspicious depends on the query, so set the `severity` of its bands when it is not
+pub(super) struct TemplateRule;
+
+#[tonic::async_trait]
+impl Rule for TemplateRule {
+    type Parameters = QueryRule;
+
+    async fn evaluate(
+        &self,
+        configuration: &RuleConfiguration,
+        params: QueryRule,
+        payload: &Payload,
+        state: AppHandle,
+    ) -> Result<RuleResult> {
+        let mut rule_result = common::rule_result(configuration);
+        let bands = common::bands(configuration)?;
+        let time_frames = configuration
+            .configuration
+            .as_ref()
+            .map(|value| value.time_frames.as_slice())
+            .unwrap_or_default();
+        let template = params.template(time_frames)?;
+
+        let report = common::status_report(payload)?;
+
+        if !template.binds().contains(&Bind::MaxQueryRange) {
+            let value = query(configuration, &template, &report, None, &state).await?;
+            determine_outcome(value, bands, &mut rule_result);
+
+            return Ok(rule_result);
+        }
+
+        let windows = time_frame::windows(configuration, params.max_query_range)?;
+        let (template, report, state) = (&template, &report, &state);
+
+        time_frame::determine_windowed_outcome(
+            configuration,
+            &windows,
+            bands,
+            &mut rule_result,
+            |window| async move {
+                query(configuration, template, report, Some(window), state).await
+            },
+        )
+        .await?;
+
+        Ok(rule_result)
+    }
+}
+
+/// Runs `template`, binding `window` as `{max_query_range}`
+async fn query(
+    configuration: &RuleConfiguration,
+    template: &QueryTemplate,
+    report: &StatusReport<'_>,
+    window: Option<f64>,
+    state: &AppHandle,
+) -> Result<f64> {
+    let tx_tp = TransactionType::PACS002.to_string();
+
+    // the first column of the first row is banded, whichever numeric type it is
+    let sql = format!(
+        "select (t.value)::float8 from ({}) as t(value)",
+        template.sql()
+    );
+    let mut query = sqlx::query_scalar::<Postgres, Option<f64>>(&sql);
+    for bind in template.binds() {
+        query = match bind {
+            Bind::DebtorAccount => query.bind(&report.data_cache.dbtr_acct_id),
+            Bind::CreditorAccount => query.bind(&report.data_cache.cdtr_acct_id),
+            Bind::Debtor => query.bind(&report.data_cache.dbtr_id),
+            Bind::Creditor => query.bind(&report.data_cache.cdtr_id),
+            Bind::CreDtTm => query.bind(report.cre_dt_tm),
+            Bind::TxTp => query.bind(&tx_tp),
+            Bind::MaxQueryRange => query.bind(window),
+        };
+    }
+
+    let span = info_span!("rule.logic");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, configuration.id.to_owned());
+    span.set_attribute("otel.kind", "client");
+
+    trace!("executing rule query");
+    let value = async {
+        let mut transaction = state.services.postgres.begin().await?;
+        sqlx::query("set transaction read only")
+            .execute(&mut *transaction)
+            .await?;
+        // set does not take parameters, the timeout is a number read from the configuration
+        sqlx::query(&format!(
+            "set local statement_timeout = {}",
+            state.config.query_timeout
+        ))
+        .execute(&mut *transaction)
+        .await?;
+        let value = query.fetch_optional(&mut *transaction).await?;
+        transaction.rollback().await?;
+
+        Ok::<_, sqlx::Error>(value.flatten())
+    }
+    .instrument(span)
+    .await?;
+
+    value.ok_or_else(|| {
+        trace!("rule query returned no value");
+        Exit::InsufficientHistory.into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{
+        processor::rule::{
+            common::{test_configuration, test_payload},
+            registry::DynRule,
+        },
+        state::test_state,
+    };
+
+    #[sqlx::test(migrations = "../pseudonyms/migrations")]
+    async fn cancels_slow_queries(pool: PgPool) {
+        let mut state = test_state(pool).await;
+        Arc::get_mut(&mut state).unwrap().config.query_timeout = 50;
+
+        let configuration = test_configuration(
+            "template",
+            &[(None, None, ".01")],
+            &[],
+            serde_json::json!({ "query": "select count(*) from generate_series(1, 1000000000)" }),
+        );
+        let payload = test_payload("2025-08-16T12:00:00Z", 100.0, "USD");
+
+        let error = TemplateRule
+            .run(&configuration, &payload, state)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("statement timeout"), "{error}");
+    }
+}
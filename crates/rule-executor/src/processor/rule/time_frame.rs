@@ -0,0 +1,223 @@
+use std::future::Future;
+
+use anyhow::{Result, anyhow};
+use tracing::trace;
+use warden_core::{
+    configuration::rule::{Band, RuleConfiguration, TimeframeStrategy},
+    message::RuleResult,
+};
+
+use crate::processor::rule::determine_outcome::band_index;
+
+/// The windows, in milliseconds, a rule is evaluated over: its time frames, or `max_query_range`
+/// when it has none
+pub(super) fn windows(
+    configuration: &RuleConfiguration,
+    max_query_range: Option<f64>,
+) -> Result<Vec<f64>> {
+    let time_frames: Vec<_> = configuration
+        .configuration
+        .iter()
+        .flat_map(|value| value.time_frames.iter())
+        .map(|value| value.threshold)
+        .collect();
+
+    if !time_frames.is_empty() {
+        return Ok(time_frames);
+    }
+
+    max_query_range
+        .map(|value| vec![value])
+        .ok_or_else(|| anyhow!("neither time frames nor max_query_range are configured"))
+}
+
+/// Bands the value `value_of` computes for each window, and reports one of those bands as the
+/// rule's strategy asks, along with its window
+pub(super) async fn determine_windowed_outcome<F, Fut>(
+    configuration: &RuleConfiguration,
+    windows: &[f64],
+    bands: &[Band],
+    rule_result: &mut RuleResult,
+    mut value_of: F,
+) -> Result<()>
+where
+    F: FnMut(f64) -> Fut,
+    Fut: Future<Output = Result<f64>>,
+{
+    let strategy = configuration
+        .configuration
+        .as_ref()
+        .and_then(|value| TimeframeStrategy::try_from(value.time_frame_strategy).ok())
+        .unwrap_or_default();
+
+    let mut values = Vec::with_capacity(windows.len());
+    for window in windows {
+        let value = value_of(*window).await?;
+        trace!(window, value, "evaluated time frame");
+        values.push((*window, value));
+    }
+
+    if let Some((band, window)) = choose(strategy, bands, &values) {
+        rule_result.sub_rule_ref = band.sub_rule_ref.to_owned();
+        rule_result.reason = band.reason.to_owned();
+        rule_result.time_frame = Some(window);
+    }
+
+    Ok(())
+}
+
+/// The band, and its window, `strategy` picks from `values` by [severity]. On a tie, the
+/// earliest window is kept
+///
+/// A window whose value falls in no band is passed over by [TimeframeStrategy::Worst], but
+/// leaves [TimeframeStrategy::Best] without a band, as not every window reaches one
+fn choose<'a>(
+    strategy: TimeframeStrategy,
+    bands: &'a [Band],
+    values: &[(f64, f64)],
+) -> Option<(&'a Band, f64)> {
+    let banded = values
+        .iter()
+        .map(|(window, value)| band_index(*value, bands).map(|index| (index, *window)));
+
+    let (index, window) = match strategy {
+        TimeframeStrategy::Worst => banded
+            .flatten()
+            .rev()
+            .max_by_key(|(index, _)| severity(bands, *index)),
+        TimeframeStrategy::Best => banded
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .min_by_key(|(index, _)| severity(bands, *index)),
+    }?;
+
+    Some((&bands[index], window))
+}
+
+/// How suspicious the band at `index` is: its configured severity, or its position when bands
+/// set none
+fn severity(bands: &[Band], index: usize) -> i64 {
+    bands[index].severity.map_or(index as i64, i64::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use warden_core::configuration::rule::{Config, Timeframe};
+
+    use super::*;
+
+    const HOUR: f64 = 3_600_000.0;
+    const DAY: f64 = 24.0 * HOUR;
+
+    fn bands() -> Vec<Band> {
+        [
+            (None, Some(2.0), ".01"),
+            (Some(2.0), Some(5.0), ".02"),
+            (Some(5.0), None, ".03"),
+        ]
+        .into_iter()
+        .map(|(lower_limit, upper_limit, sub_rule_ref)| Band {
+            sub_rule_ref: sub_rule_ref.to_owned(),
+            reason: String::default(),
+            lower_limit,
+            upper_limit,
+            severity: None,
+        })
+        .collect()
+    }
+
+    #[test]
+    fn prefers_time_frames() {
+        let mut configuration = RuleConfiguration {
+            configuration: Some(Config {
+                time_frames: vec![Timeframe { threshold: HOUR }, Timeframe { threshold: DAY }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(windows(&configuration, Some(DAY)).unwrap(), [HOUR, DAY]);
+
+        configuration.configuration = None;
+        assert_eq!(windows(&configuration, Some(DAY)).unwrap(), [DAY]);
+        assert!(windows(&configuration, None).is_err());
+    }
+
+    #[test]
+    fn chooses_worst_band() {
+        let bands = bands();
+        let values = [(HOUR, 1.0), (DAY, 6.0), (30.0 * DAY, 6.0)];
+
+        let (band, window) = choose(TimeframeStrategy::Worst, &bands, &values).unwrap();
+
+        assert_eq!(band.sub_rule_ref, ".03");
+        assert_eq!(window, DAY);
+    }
+
+    #[test]
+    fn chooses_best_band() {
+        let bands = bands();
+        let values = [(HOUR, 3.0), (DAY, 6.0), (30.0 * DAY, 3.0)];
+
+        let (band, window) = choose(TimeframeStrategy::Best, &bands, &values).unwrap();
+
+        assert_eq!(band.sub_rule_ref, ".02");
+        assert_eq!(window, HOUR);
+    }
+
+    #[test]
+    fn compares_configured_severity() {
+        let mut bands = bands();
+        for (band, severity) in bands.iter_mut().zip([2, 1, 0]) {
+            band.severity = Some(severity);
+        }
+        let values = [(HOUR, 1.0), (DAY, 6.0)];
+
+        let (band, window) = choose(TimeframeStrategy::Worst, &bands, &values).unwrap();
+        assert_eq!(band.sub_rule_ref, ".01");
+        assert_eq!(window, HOUR);
+
+        let (band, window) = choose(TimeframeStrategy::Best, &bands, &values).unwrap();
+        assert_eq!(band.sub_rule_ref, ".03");
+        assert_eq!(window, DAY);
+    }
+
+    #[test]
+    fn worst_ignores_unbanded_windows() {
+        let bands = &bands()[1..];
+
+        let (band, window) =
+            choose(TimeframeStrategy::Worst, bands, &[(HOUR, 1.0), (DAY, 3.0)]).unwrap();
+        assert_eq!(band.sub_rule_ref, ".02");
+        assert_eq!(window, DAY);
+
+        assert!(choose(TimeframeStrategy::Worst, bands, &[(HOUR, 1.0)]).is_none());
+    }
+
+    #[test]
+    fn best_requires_every_window_banded() {
+        let bands = &bands()[1..];
+
+        assert!(choose(TimeframeStrategy::Best, bands, &[(HOUR, 1.0), (DAY, 3.0)]).is_none());
+        assert!(choose(TimeframeStrategy::Best, bands, &[(HOUR, 3.0), (DAY, 1.0)]).is_none());
+    }
+
+    #[tokio::test]
+    async fn records_window() {
+        let configuration = RuleConfiguration::default();
+        let mut rule_result = RuleResult::default();
+
+        determine_windowed_outcome(
+            &configuration,
+            &[HOUR, DAY],
+            &bands(),
+            &mut rule_result,
+            |window| async move { Ok(if window == HOUR { 1.0 } else { 3.0 }) },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(rule_result.sub_rule_ref, ".02");
+        assert_eq!(rule_result.time_frame, Some(DAY));
+    }
+}
@@ -3,7 +3,6 @@ use std::sync::Arc;
 use async_nats::jetstream::Context;
 use moka::future::Cache;
 use tokio::sync::RwLock;
-use tonic::transport::Endpoint;
 use tracing::error;
 use warden_core::configuration::rule::{
     RuleConfiguration, RuleConfigurationRequest,
@@ -11,7 +10,7 @@ use warden_core::configuration::rule::{
 };
 use warden_stack::{Configuration, sqlx::PgPool};
 
-use crate::cnfg::LocalConfig;
+use crate::{cnfg::LocalConfig, processor::rule::Registry};
 use warden_middleware::grpc::interceptor::{Intercepted, MyInterceptor};
 
 #[derive(Clone)]
@@ -28,13 +27,15 @@ pub struct AppState {
     pub local_cache: Arc<RwLock<Cache<RuleConfigurationRequest, RuleConfiguration>>>,
     pub config: LocalConfig,
     pub query_rule_client: QueryRuleConfigurationClient<Intercepted>,
+    pub registry: Registry,
 }
 
 impl AppState {
     pub async fn new(services: Services, configuration: Configuration) -> anyhow::Result<Self> {
         let config: LocalConfig = serde_json::from_value(configuration.misc.clone())?;
-        let channel = Endpoint::new(config.config_endpoint.to_string())?
-            .connect()
+        let channel = configuration
+            .grpc
+            .connect(config.config_endpoint.to_string())
             .await
             .inspect_err(|e| {
                 error!(
@@ -43,14 +44,56 @@ impl AppState {
                 )
             })?;
 
-        let query_rule_client =
-            QueryRuleConfigurationClient::with_interceptor(channel, MyInterceptor);
+        let query_rule_client = QueryRuleConfigurationClient::with_interceptor(
+            channel,
+            MyInterceptor::new(&configuration.grpc)?,
+        );
 
         Ok(Self {
             services,
             config,
             local_cache: Arc::new(RwLock::new(Cache::builder().build())),
             query_rule_client,
+            registry: Registry::standard(),
         })
     }
 }
+
+/// State for tests that query `postgres`. Neither NATS nor the config service are connected to
+/// until they are used
+#[cfg(test)]
+pub(crate) async fn test_state(postgres: PgPool) -> AppHandle {
+    let configuration: Configuration = config::Config::builder()
+        .add_source(config::File::from_str(
+            include_str!("../rule-executor.toml"),
+            config::FileFormat::Toml,
+        ))
+        .build()
+        .unwrap()
+        .try_deserialize()
+        .unwrap();
+    let config: LocalConfig = serde_json::from_value(configuration.misc).unwrap();
+
+    let client = async_nats::ConnectOptions::new()
+        .retry_on_initial_connect()
+        .connect(configuration.nats.hosts[0].as_str())
+        .await
+        .unwrap();
+    let channel = tonic::transport::Endpoint::from_shared(config.config_endpoint.to_string())
+        .unwrap()
+        .connect_lazy();
+
+    Arc::new(AppState {
+        services: Services {
+            jetstream: async_nats::jetstream::new(client),
+            postgres,
+        },
+        local_cache: Arc::new(RwLock::new(Cache::builder().build())),
+        query_rule_client: QueryRuleConfigurationClient::with_interceptor(
+            channel,
+            MyInterceptor::default(),
+        ),
+        config,
+        registry: Registry::standard(),
+    })
+}
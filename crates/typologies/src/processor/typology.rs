@@ -46,35 +46,32 @@ pub async fn process_typology(
 
     let transaction = payload.transaction.as_ref().expect("to have returned");
 
-    match transaction {
-        warden_core::message::payload::Transaction::Pacs008(_) => {
-            warn!("Pacs008 is unsupported on this version: this should be unreachable");
-        }
-        warden_core::message::payload::Transaction::Pacs002(pacs002_document) => {
-            let key = format!(
-                "tp_{}",
-                pacs002_document.f_i_to_f_i_pmt_sts_rpt.grp_hdr.msg_id
-            );
-
-            let rule_result = &payload
-                .rule_result
-                .as_ref()
-                .expect("rule result should be here");
-            let rule_results = cache_and_get_all(&key, rule_result, Arc::clone(&state)).await?;
-
-            let routing = payload
-                .routing
-                .as_ref()
-                .expect("routing missing from payload");
-
-            let (mut typology_result, _rule_count) =
-                aggregate_rules::aggregate_rules(&rule_results, routing, rule_result)?;
-
-            let _ = evaluate_typology(&mut typology_result, routing, payload.clone(), &key, state)
-                .await
-                .inspect_err(|e| error!("{e}"));
-        }
-    };
+    if let warden_core::message::payload::Transaction::Pacs008(_) = transaction {
+        warn!("Pacs008 is unsupported on this version: this should be unreachable");
+    } else {
+        // a report may carry several statuses, and an initiation several transfers, each
+        // routed separately
+        let (msg_id, end_to_end_id) = transaction.evaluation_ids();
+        let key = format!("tp_{msg_id}_{end_to_end_id}");
+
+        let rule_result = &payload
+            .rule_result
+            .as_ref()
+            .expect("rule result should be here");
+        let rule_results = cache_and_get_all(&key, rule_result, Arc::clone(&state)).await?;
+
+        let routing = payload
+            .routing
+            .as_ref()
+            .expect("routing missing from payload");
+
+        let (mut typology_result, _rule_count) =
+            aggregate_rules::aggregate_rules(&rule_results, routing, rule_result)?;
+
+        let _ = evaluate_typology(&mut typology_result, routing, payload.clone(), &key, state)
+            .await
+            .inspect_err(|e| error!("{e}"));
+    }
 
     let span = info_span!("nats.ack");
     message
@@ -3,7 +3,6 @@ use std::sync::Arc;
 use async_nats::jetstream::Context;
 use moka::future::Cache;
 use tokio::sync::RwLock;
-use tonic::transport::Endpoint;
 use tracing::error;
 use warden_core::configuration::typology::{
     TypologyConfiguration, TypologyConfigurationRequest,
@@ -33,8 +32,9 @@ pub struct AppState {
 impl AppState {
     pub async fn new(services: Services, configuration: Configuration) -> anyhow::Result<Self> {
         let config: LocalConfig = serde_json::from_value(configuration.misc.clone())?;
-        let channel = Endpoint::new(config.config_endpoint.to_string())?
-            .connect()
+        let channel = configuration
+            .grpc
+            .connect(config.config_endpoint.to_string())
             .await
             .inspect_err(|e| {
                 error!(
@@ -43,7 +43,10 @@ impl AppState {
                 )
             })?;
 
-        let query_typology_client = QueryTypologiesClient::with_interceptor(channel, MyInterceptor);
+        let query_typology_client = QueryTypologiesClient::with_interceptor(
+            channel,
+            MyInterceptor::new(&configuration.grpc)?,
+        );
 
         Ok(Self {
             services,
@@ -0,0 +1,7 @@
+create table idempotency_key (
+    key text primary key,
+    request jsonb not null,
+    status smallint,
+    response jsonb,
+    created_at timestamptz default now()
+);
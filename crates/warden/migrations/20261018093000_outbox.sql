@@ -0,0 +1,14 @@
+create table outbox (
+    id uuid primary key,
+    subject text not null,
+    payload bytea not null,
+    headers jsonb not null default '{}',
+    created_at timestamptz not null default now(),
+    sent_at timestamptz
+);
+
+create index idx_outbox_pending on outbox (created_at) where sent_at is null;
+
+-- delivery is tracked by the outbox
+alter table pacs008 drop column processed;
+alter table pacs002 drop column processed;
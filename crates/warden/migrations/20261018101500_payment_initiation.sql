@@ -0,0 +1,35 @@
+create table pain001 (
+    id uuid primary key,
+    document jsonb not null,
+    created_at timestamptz default now(),
+
+    message_id text generated always as (
+        document->'cstmr_cdt_trf_initn'->'grp_hdr'->>'msg_id'
+    ) stored,
+
+    end_to_end_id text generated always as (
+        document->'cstmr_cdt_trf_initn'->'pmt_inf'->0->'cdt_trf_tx_inf'->0->'pmt_id'->>'end_to_end_id'
+    ) stored,
+
+    constraint unique_msgid_e2eid_pain001 unique (message_id, end_to_end_id),
+    constraint pain001_message_id_not_null check (message_id is not null),
+    constraint pain001_end_to_end_id_not_null check (end_to_end_id is not null)
+);
+
+create table pain013 (
+    id uuid primary key,
+    document jsonb not null,
+    created_at timestamptz default now(),
+
+    message_id text generated always as (
+        document->'cdtr_pmt_actvtn_req'->'grp_hdr'->>'msg_id'
+    ) stored,
+
+    end_to_end_id text generated always as (
+        document->'cdtr_pmt_actvtn_req'->'pmt_inf'->0->'cdt_trf_tx'->0->'pmt_id'->>'end_to_end_id'
+    ) stored,
+
+    constraint unique_msgid_e2eid_pain013 unique (message_id, end_to_end_id),
+    constraint pain013_message_id_not_null check (message_id is not null),
+    constraint pain013_end_to_end_id_not_null check (end_to_end_id is not null)
+);
@@ -0,0 +1,15 @@
+alter table pacs008
+    add column debtor_account_id text generated always as (
+        document->'f_i_to_f_i_cstmr_cdt_trf'->'cdt_trf_tx_inf'->0->'dbtr_acct'->'id'->'othr'->>'id'
+    ) stored,
+
+    add column creditor_account_id text generated always as (
+        document->'f_i_to_f_i_cstmr_cdt_trf'->'cdt_trf_tx_inf'->0->'cdtr_acct'->'id'->'othr'->>'id'
+    ) stored;
+
+-- ids are v7 uuids, so they follow insertion order and serve as the page cursor
+create index idx_pacs008_end_to_end_id on pacs008 (end_to_end_id);
+create index idx_pacs008_created_at on pacs008 (created_at, id);
+create index idx_pacs008_debtor_account_id on pacs008 (debtor_account_id, id);
+create index idx_pacs008_creditor_account_id on pacs008 (creditor_account_id, id);
+create index idx_pacs002_end_to_end_id on pacs002 (end_to_end_id);
@@ -0,0 +1,7 @@
+-- a claim that is never finished, because its instance stopped, can be taken over once its
+-- lease has lapsed
+alter table idempotency_key add column claimed_at timestamptz not null default now();
+update idempotency_key set claimed_at = created_at where created_at is not null;
+
+-- finished keys are removed once they are past the retention period
+create index idx_idempotency_key_created_at on idempotency_key (created_at);
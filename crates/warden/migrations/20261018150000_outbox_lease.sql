@@ -0,0 +1,3 @@
+-- rows are leased to a relay while they are published, outside of any transaction. A lease that
+-- lapses, because its relay stopped, makes the rows available again
+alter table outbox add column leased_until timestamptz;
@@ -0,0 +1,2 @@
+-- sent rows are removed once they are past the retention period
+create index idx_outbox_sent_at on outbox (sent_at) where sent_at is not null;
@@ -0,0 +1,12 @@
+-- account ids are the account's other id followed by the member id of its agent, as in the data
+-- cache, the pseudonyms and the evaluations
+alter table pacs008
+    alter column debtor_account_id set expression as (
+        (document->'f_i_to_f_i_cstmr_cdt_trf'->'cdt_trf_tx_inf'->0->'dbtr_acct'->'id'->'othr'->>'id')
+        || (document->'f_i_to_f_i_cstmr_cdt_trf'->'cdt_trf_tx_inf'->0->'dbtr_agt'->'fin_instn_id'->'clr_sys_mmb_id'->>'mmb_id')
+    ),
+
+    alter column creditor_account_id set expression as (
+        (document->'f_i_to_f_i_cstmr_cdt_trf'->'cdt_trf_tx_inf'->0->'cdtr_acct'->'id'->'othr'->>'id')
+        || (document->'f_i_to_f_i_cstmr_cdt_trf'->'cdt_trf_tx_inf'->0->'cdtr_agt'->'fin_instn_id'->'clr_sys_mmb_id'->>'mmb_id')
+    );
@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use warden_middleware::{auth::AuthConfig, rate_limit::RateLimitConfig};
 
 #[derive(Deserialize, Clone)]
 #[serde(rename_all = "kebab-case")]
@@ -7,9 +8,61 @@ pub struct LocalConfig {
     #[serde(rename = "pseudonyms-endpoint")]
     pub pseudonyms_endpoint: std::sync::Arc<str>,
     pub nats: NatsConfig,
+    pub outbox: OutboxConfig,
+    pub idempotency: IdempotencyConfig,
+    pub batch: BatchConfig,
+    pub evaluation: EvaluationConfig,
+    pub auth: AuthConfig,
+    pub rate_limit: RateLimitConfig,
 }
 
 #[derive(Deserialize, Clone)]
 pub struct NatsConfig {
     pub subject: std::sync::Arc<str>,
 }
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct OutboxConfig {
+    /// Maximum number of messages published per relay run
+    pub batch_size: usize,
+    /// Milliseconds between relay runs when no new messages are signalled
+    pub poll_interval: u64,
+    /// Milliseconds a relay holds the messages it is publishing before another may take them
+    pub lease: u64,
+    /// Seconds a message is kept after it was sent
+    pub retention: u64,
+    /// Seconds between removals of messages past their retention
+    pub cleanup_interval: u64,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct IdempotencyConfig {
+    /// Milliseconds a claim is held before another request with the same key may take it over
+    pub lease: u64,
+    /// Seconds a key is kept, and its response replayed, after it was claimed
+    pub retention: u64,
+    /// Seconds between removals of keys past their retention
+    pub cleanup_interval: u64,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct BatchConfig {
+    /// Maximum number of documents processed at the same time within a batch
+    pub concurrency: usize,
+    /// Maximum number of documents accepted in a single batch
+    pub max_documents: usize,
+    /// Maximum size of a batch request body, in bytes
+    pub body_limit: usize,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct EvaluationConfig {
+    /// Prefix of the subject the aggregator publishes results on, followed by the message id
+    pub subject: std::sync::Arc<str>,
+    /// Milliseconds a synchronous submission waits for its results
+    pub timeout: u64,
+}
@@ -1,26 +1,179 @@
 use axum::{
-    http::StatusCode,
+    Json,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
+use serde::Serialize;
+use tracing::error;
+use utoipa::ToSchema;
+use warden_core::iso20022::validate::Violation;
+use warden_middleware::rate_limit::RateLimited;
 
-#[derive(Debug)]
-pub struct AppError(anyhow::Error);
+/// Errors returned by the HTTP API
+///
+/// Every variant is rendered as an [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807) problem
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    /// A required element is absent from the request
+    #[error("{0}")]
+    BadRequest(String),
+    /// The caller may not perform the request
+    #[error("{0}")]
+    Forbidden(String),
+    /// The requested resource does not exist
+    #[error("{0}")]
+    NotFound(String),
+    /// The request clashes with a transaction that was already submitted
+    #[error("{0}")]
+    Conflict(String),
+    /// The request is well formed but its content cannot be processed
+    #[error("{0}")]
+    Unprocessable(String),
+    /// The document does not satisfy the ISO 20022 facets
+    #[error("document failed validation with {} violation(s)", .0.len())]
+    Invalid(Vec<Violation>),
+    /// The caller has made too many requests
+    #[error(transparent)]
+    RateLimited(#[from] RateLimited),
+    /// A downstream dependency could not be reached
+    #[error("{0}")]
+    Unavailable(String),
+    /// Anything else
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
+
+/// Problem details as described in [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807)
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProblemDetails {
+    /// URI reference identifying the problem type
+    #[serde(rename = "type")]
+    #[schema(example = "about:blank")]
+    pub r#type: String,
+    /// Short summary of the problem type
+    #[schema(example = "Unprocessable Entity")]
+    pub title: String,
+    /// HTTP status code
+    #[schema(example = 422)]
+    pub status: u16,
+    /// Explanation specific to this occurrence of the problem
+    #[schema(example = "invalid currency")]
+    pub detail: String,
+    /// Every element that failed validation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<Violation>,
+}
+
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+impl AppError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::Unprocessable(_) | AppError::Invalid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn bad_request(detail: impl Into<String>) -> Self {
+        Self::BadRequest(detail.into())
+    }
+
+    pub fn forbidden(detail: impl Into<String>) -> Self {
+        Self::Forbidden(detail.into())
+    }
+
+    pub fn not_found(detail: impl Into<String>) -> Self {
+        Self::NotFound(detail.into())
+    }
+
+    pub fn unprocessable(detail: impl Into<String>) -> Self {
+        Self::Unprocessable(detail.into())
+    }
+
+    pub fn unavailable(detail: impl Into<String>) -> Self {
+        Self::Unavailable(detail.into())
+    }
+
+    pub fn cache_unavailable(err: impl std::fmt::Display) -> Self {
+        error!("cache: {err}");
+        Self::unavailable("cache is unavailable")
+    }
+
+    pub fn problem(&self) -> ProblemDetails {
+        let status = self.status();
+        let detail = match self {
+            // do not leak internals
+            AppError::Internal(_) => "an unexpected error occurred".to_string(),
+            _ => self.to_string(),
+        };
+        let violations = match self {
+            AppError::Invalid(violations) => violations.clone(),
+            _ => Vec::new(),
+        };
+
+        ProblemDetails {
+            r#type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail,
+            violations,
+        }
+    }
+}
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("Something went wrong: {}", self.0),
-        )
-            .into_response()
+        if let AppError::Internal(ref e) = self {
+            error!("{e:?}");
+        }
+
+        let mut response = (self.status(), Json(self.problem())).into_response();
+        let headers = response.headers_mut();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+        if let AppError::RateLimited(ref e) = self {
+            headers.insert(header::RETRY_AFTER, HeaderValue::from(e.retry_after_secs()));
+        }
+        response
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::Database(ref e) if e.is_unique_violation() => {
+                Self::Conflict("transaction has already been submitted".to_string())
+            }
+            sqlx::Error::PoolTimedOut | sqlx::Error::PoolClosed | sqlx::Error::Io(_) => {
+                error!("database: {err}");
+                Self::unavailable("transaction history is unavailable")
+            }
+            e => Self::Internal(e.into()),
+        }
+    }
+}
+
+impl From<warden_stack::redis::RedisError> for AppError {
+    fn from(err: warden_stack::redis::RedisError) -> Self {
+        Self::cache_unavailable(err)
     }
 }
 
-impl<E> From<E> for AppError
-where
-    E: Into<anyhow::Error>,
-{
-    fn from(err: E) -> Self {
-        Self(err.into())
+impl From<tonic::Status> for AppError {
+    fn from(status: tonic::Status) -> Self {
+        error!(code = ?status.code(), "pseudonyms: {}", status.message());
+        match status.code() {
+            tonic::Code::InvalidArgument => Self::unprocessable(status.message()),
+            tonic::Code::AlreadyExists => Self::Conflict(status.message().to_string()),
+            tonic::Code::Unavailable | tonic::Code::DeadlineExceeded => {
+                Self::unavailable("pseudonyms service is unavailable")
+            }
+            _ => Self::Internal(anyhow::anyhow!("could not create pseudonyms")),
+        }
     }
 }
@@ -1,5 +1,6 @@
 mod cnfg;
 mod error;
+mod outbox;
 mod server;
 mod state;
 mod version;
@@ -7,7 +8,7 @@ mod version;
 use std::net::{Ipv6Addr, SocketAddr};
 use tokio::signal;
 
-use clap::{Parser, command};
+use clap::{Parser, Subcommand, command};
 use tracing::{error, info, trace};
 use warden_stack::{
     Configuration, Services,
@@ -23,10 +24,26 @@ struct Args {
     /// Path to config file
     #[arg(short, long)]
     config_file: Option<std::path::PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Resend the parties of every stored transaction to the pseudonyms service, then exit
+    ///
+    /// Links each party of transactions recorded before this release with their own account.
+    /// Safe to run more than once. Links still marked legacy in the pseudonyms database
+    /// afterwards were wrong, and can be removed
+    ReplayPseudonyms {
+        /// Transactions read from the history at a time
+        #[arg(long, default_value_t = 500)]
+        batch_size: usize,
+    },
 }
 
 #[tokio::main]
-async fn main() -> Result<(), error::AppError> {
+async fn main() -> anyhow::Result<()> {
     let args = Args::parse();
     let config = include_str!("../warden.toml");
 
@@ -91,6 +108,15 @@ async fn main() -> Result<(), error::AppError> {
         .await?;
     trace!("migrations updated");
 
+    if let Some(Command::ReplayPseudonyms { batch_size }) = args.command {
+        info!("replaying stored transactions to the pseudonyms service");
+        return server::replay::pseudonyms(&state, batch_size).await;
+    }
+
+    tokio::spawn(outbox::relay(state.clone()));
+    tokio::spawn(outbox::expire(state.clone()));
+    tokio::spawn(server::idempotency::expire(state.clone()));
+
     let addr = SocketAddr::from((Ipv6Addr::UNSPECIFIED, config.application.port));
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
@@ -0,0 +1,348 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_nats::header::NATS_MESSAGE_ID;
+use opentelemetry::global;
+use opentelemetry_semantic_conventions::attribute;
+use sqlx::PgConnection;
+use tracing::{Instrument, Span, debug, error, info_span, instrument, trace, warn};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+use warden_core::message::Payload;
+
+use crate::{error::AppError, state::AppHandle};
+
+/// Stages `payload` for publishing on the connection's current transaction
+///
+/// The trace context of the current span is stored alongside so that the relay can continue it
+#[instrument(skip(conn, state, payload), err(Debug))]
+pub async fn enqueue(
+    conn: &mut PgConnection,
+    state: &AppHandle,
+    payload: &Payload,
+    msg_id: &str,
+) -> Result<(), AppError> {
+    let subject = format!("{}.{}", state.app_config.nats.subject, msg_id);
+    let bytes = prost::Message::encode_to_vec(payload);
+
+    let mut headers = HashMap::<String, String>::new();
+    let cx = Span::current().context();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&cx, &mut headers));
+
+    let span = info_span!("create.outbox");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "insert");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "outbox");
+    span.set_attribute("otel.kind", "client");
+
+    let id = Uuid::now_v7();
+    sqlx::query!(
+        "insert into outbox (id, subject, payload, headers) values ($1, $2, $3, $4)",
+        id,
+        subject,
+        bytes,
+        sqlx::types::Json(&headers) as _
+    )
+    .execute(conn)
+    .instrument(span)
+    .await?;
+
+    trace!(%id, %subject, "message staged in outbox");
+
+    Ok(())
+}
+
+/// Publishes pending outbox rows until the process exits
+///
+/// Runs whenever a handler commits a row, and on a fixed interval to pick up anything left
+/// behind by a failed attempt or another instance
+pub async fn relay(state: AppHandle) {
+    let interval = Duration::from_millis(state.app_config.outbox.poll_interval);
+    let batch_size = state.app_config.outbox.batch_size;
+
+    loop {
+        match relay_pending(&state, batch_size).await {
+            // there may be more waiting
+            Ok(sent) if sent == batch_size => continue,
+            Ok(_) => {}
+            Err(e) => error!("outbox relay: {e:?}"),
+        }
+
+        tokio::select! {
+            _ = state.outbox.notified() => {}
+            _ = tokio::time::sleep(interval) => {}
+        }
+    }
+}
+
+/// Removes sent messages past their retention, until the process exits
+pub async fn expire(state: AppHandle) {
+    let mut interval = tokio::time::interval(Duration::from_secs(
+        state.app_config.outbox.cleanup_interval,
+    ));
+
+    loop {
+        interval.tick().await;
+
+        match remove_sent(&state).await {
+            Ok(count) => debug!(count, "expired outbox messages"),
+            Err(e) => error!("could not expire outbox messages: {e}"),
+        }
+    }
+}
+
+/// Deletes the messages sent longer than the retention ago. Returns how many were deleted
+async fn remove_sent(state: &AppHandle) -> Result<u64, sqlx::Error> {
+    let span = info_span!("delete.outbox");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "delete");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "outbox");
+    span.set_attribute("otel.kind", "client");
+
+    let result = sqlx::query!(
+        "delete from outbox where sent_at < now() - $1::bigint * interval '1 second'",
+        state.app_config.outbox.retention as i64
+    )
+    .execute(&state.services.postgres)
+    .instrument(span)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[instrument(skip(state), err(Debug))]
+async fn relay_pending(state: &AppHandle, batch_size: usize) -> anyhow::Result<usize> {
+    let span = info_span!("update.outbox");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "update");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "outbox");
+    span.set_attribute("otel.kind", "client");
+
+    // the batch is leased and committed before publishing, so no transaction is held open while
+    // waiting on the stream. skip locked lets several instances lease at the same time
+    let mut rows = sqlx::query!(
+        r#"update outbox set leased_until = now() + $2::bigint * interval '1 millisecond'
+        where id in (
+            select id from outbox
+            where sent_at is null and (leased_until is null or leased_until < now())
+            order by created_at
+            limit $1
+            for update skip locked
+        )
+        returning id, subject, payload, created_at, headers as "headers: sqlx::types::Json<HashMap<String, String>>""#,
+        batch_size as i64,
+        state.app_config.outbox.lease as i64
+    )
+    .fetch_all(&state.services.postgres)
+    .instrument(span)
+    .await?;
+
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    rows.sort_by_key(|row| row.created_at);
+
+    debug!(count = rows.len(), "relaying outbox");
+    let mut sent = Vec::with_capacity(rows.len());
+    let mut unsent = Vec::new();
+
+    let mut rows = rows.into_iter();
+    for row in rows.by_ref() {
+        let context =
+            global::get_text_map_propagator(|propagator| propagator.extract(&row.headers.0));
+
+        let span = info_span!("nats.publish");
+        if let Err(e) = span.set_parent(context) {
+            warn!("{e:?}");
+        }
+        span.set_attribute("otel.kind", "producer");
+        span.set_attribute(
+            attribute::MESSAGING_DESTINATION_SUBSCRIPTION_NAME,
+            row.subject.to_string(),
+        );
+        span.set_attribute(attribute::MESSAGING_SYSTEM, "nats");
+
+        let mut headers = async_nats::HeaderMap::new();
+        for (key, value) in row.headers.0.iter() {
+            headers.insert(key.as_str(), value.as_str());
+        }
+        // the stream drops a message it has already stored, should a lease lapse after publishing
+        headers.insert(NATS_MESSAGE_ID, row.id.to_string());
+
+        let published = async {
+            state
+                .services
+                .jetstream
+                .publish_with_headers(row.subject, headers, row.payload.into())
+                .await?
+                .await
+        }
+        .instrument(span)
+        .await;
+
+        match published {
+            Ok(_) => sent.push(row.id),
+            Err(e) => {
+                // keep ordering, the rest are retried on the next run
+                error!(id = %row.id, "could not publish outbox message: {e}");
+                unsent.push(row.id);
+                break;
+            }
+        }
+    }
+    unsent.extend(rows.map(|row| row.id));
+
+    let span = info_span!("update.outbox");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "update");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "outbox");
+    span.set_attribute("otel.kind", "client");
+
+    sqlx::query!(
+        "update outbox
+        set sent_at = case when id = any($1) then now() end, leased_until = null
+        where id = any($1) or id = any($2)",
+        &sent,
+        &unsent
+    )
+    .execute(&state.services.postgres)
+    .instrument(span)
+    .await?;
+
+    debug!(count = sent.len(), "outbox messages sent");
+
+    Ok(sent.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+    use warden_stack::cache::RedisManager;
+
+    use crate::{
+        server::{self, test_config},
+        state::{AppState, Services},
+    };
+
+    #[sqlx::test]
+    async fn relay(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool.clone(),
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state.clone());
+
+        let pacs = server::test_pacs008();
+        let subject = format!(
+            "{}.{}",
+            state.app_config.nats.subject, pacs.f_i_to_f_i_cstmr_cdt_trf.grp_hdr.msg_id
+        );
+        let body = serde_json::to_vec(&pacs).unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/pacs008")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let pending = || async {
+            sqlx::query_scalar!(
+                "select count(*) from outbox where subject = $1 and sent_at is null",
+                subject
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap()
+        };
+
+        assert_eq!(pending().await, Some(1));
+
+        // held by another relay
+        sqlx::query("update outbox set leased_until = now() + interval '1 hour'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(super::relay_pending(&state, 100).await.unwrap(), 0);
+        assert_eq!(pending().await, Some(1));
+
+        // which stopped before publishing
+        sqlx::query("update outbox set leased_until = now() - interval '1 second'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let sent = super::relay_pending(&state, 100).await.unwrap();
+
+        assert!(sent >= 1);
+        assert_eq!(pending().await, Some(0));
+    }
+
+    #[sqlx::test]
+    async fn expires_sent_messages(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool.clone(),
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+
+        for sent_at in [
+            Some("now() - interval '30 days'"),
+            Some("now() - interval '1 minute'"),
+            None,
+        ] {
+            sqlx::query(&format!(
+                "insert into outbox (id, subject, payload, created_at, sent_at)
+                values ($1, 'subject', '', now() - interval '30 days', {})",
+                sent_at.unwrap_or("null")
+            ))
+            .bind(uuid::Uuid::now_v7())
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        // only the message sent before the retention, unsent ones are kept however old
+        assert_eq!(super::remove_sent(&state).await.unwrap(), 1);
+
+        let remaining: Vec<bool> =
+            sqlx::query_scalar("select sent_at is null from outbox order by sent_at nulls last")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(remaining, [false, true]);
+    }
+}
@@ -1,42 +1,46 @@
-mod publish;
+mod document;
+mod evaluation;
+pub(crate) mod idempotency;
 mod routes;
 pub use routes::metrics::metrics_app;
+pub(crate) use routes::processor::replay;
+
+use std::sync::Arc;
 
 use axum::Router;
+use tokio::sync::Semaphore;
 use utoipa::OpenApi;
 use utoipa_axum::{router::OpenApiRouter, routes};
 
-#[cfg(feature = "redoc")]
-use utoipa_redoc::Servable;
-#[cfg(feature = "scalar")]
-use utoipa_scalar::Servable as _;
-
-use crate::{server::routes::ApiDoc, state::AppHandle};
+use crate::{server::routes::ApiDoc, state::AppHandle, version::Version};
 
 pub fn router(state: AppHandle) -> Router {
-    let (router, _api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
-        .routes(routes!(health_check))
-        .nest("/api", routes::processor::router(state.clone()))
-        .split_for_parts();
-
-    #[cfg(feature = "swagger")]
-    let router = router.merge(
-        utoipa_swagger_ui::SwaggerUi::new("/swagger-ui")
-            .url("/api-docs/swaggerdoc.json", _api.clone()),
-    );
+    // shared by every version, as they compete for the same downstream services
+    let in_flight = state
+        .app_config
+        .rate_limit
+        .max_in_flight
+        .map(|permits| Arc::new(Semaphore::new(permits)));
 
-    #[cfg(feature = "redoc")]
-    let router = router.merge(utoipa_redoc::Redoc::with_url("/redoc", _api.clone()));
+    let router = warden_middleware::version::router::<Version>(
+        ApiDoc::openapi(),
+        OpenApiRouter::new().routes(routes!(health_check)),
+        |version| routes::router(state.clone(), version),
+        |versioned| {
+            let versioned = warden_middleware::auth::require_authentication(
+                versioned,
+                state.authenticator.clone(),
+            );
 
-    #[cfg(feature = "rapidoc")]
-    let router = router.merge(
-        utoipa_rapidoc::RapiDoc::with_openapi("/api-docs/rapidoc.json", _api.clone())
-            .path("/rapidoc"),
+            match &in_flight {
+                Some(in_flight) => {
+                    warden_middleware::rate_limit::shed_load(versioned, Arc::clone(in_flight))
+                }
+                None => versioned,
+            }
+        },
     );
 
-    #[cfg(feature = "scalar")]
-    let router = router.merge(utoipa_scalar::Scalar::with_url("/scalar", _api));
-
     warden_middleware::apply(router)
 }
 
@@ -46,7 +50,8 @@ pub fn router(state: AppHandle) -> Router {
     path = "/",
     responses(
         (status = OK, description = "Success", body = str, content_type = "text/plain")
-    )
+    ),
+    security(())
 )]
 pub async fn health_check() -> impl axum::response::IntoResponse {
     let name = env!("CARGO_PKG_NAME");
@@ -93,7 +98,7 @@ pub(crate) fn test_pacs008() -> warden_core::iso20022::pacs008::Pacs008Document
             "grp_hdr": {
               "msg_id": msg_id,
               "cre_dt_tm": cre_dt_tm,
-              "nb_of_txs": "CLRG",
+              "nb_of_txs": "1",
               "sttlm_inf": {
                 "sttlm_mtd": 1
               }
@@ -298,3 +303,163 @@ pub(crate) fn test_pacs008() -> warden_core::iso20022::pacs008::Pacs008Document
 
     serde_json::from_value(v).unwrap()
 }
+
+#[cfg(test)]
+fn test_party(id: &str, prtry: &str) -> serde_json::Value {
+    serde_json::json!({
+      "nm": "April Blake Grant",
+      "id": {
+        "org_id": {
+          "othr": []
+        },
+        "prvt_id": {
+          "othr": [
+            {
+              "id": id,
+              "schme_nm": {
+                "prtry": prtry,
+                "cd": "cd-value"
+              }
+            }
+          ]
+        }
+      }
+    })
+}
+
+#[cfg(test)]
+fn test_account(id: &str) -> serde_json::Value {
+    serde_json::json!({
+      "id": {
+        "i_b_a_n": "value",
+        "othr": {
+          "id": id,
+          "schme_nm": {
+            "prtry": "MSISDN",
+            "cd": "value"
+          }
+        }
+      }
+    })
+}
+
+#[cfg(test)]
+fn test_agent(mmb_id: &str) -> serde_json::Value {
+    serde_json::json!({
+      "fin_instn_id": {
+        "clr_sys_mmb_id": {
+          "mmb_id": mmb_id
+        }
+      }
+    })
+}
+
+#[cfg(test)]
+pub(crate) fn test_pain001() -> warden_core::iso20022::pain001::Pain001Document {
+    let cre_dt_tm = time::OffsetDateTime::now_utc()
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap();
+
+    let v = serde_json::json!({
+      "cstmr_cdt_trf_initn": {
+        "grp_hdr": {
+          "msg_id": generate_id(),
+          "cre_dt_tm": cre_dt_tm,
+          "nb_of_txs": "1",
+          "authstn": [],
+          "initg_pty": test_party(&generate_id(), "EID")
+        },
+        "pmt_inf": [
+          {
+            "pmt_inf_id": generate_id(),
+            "pmt_mtd": 2,
+            "reqd_exctn_dt": {
+              "dt_tm": cre_dt_tm
+            },
+            "dbtr": test_party(&generate_id(), "EID"),
+            "dbtr_acct": test_account(&generate_id()),
+            "dbtr_agt": test_agent("fsp001"),
+            "cdt_trf_tx_inf": [
+              {
+                "pmt_id": {
+                  "instr_id": generate_id(),
+                  "end_to_end_id": generate_id()
+                },
+                "amt": {
+                  "instd_amt": {
+                    "value": 294.3,
+                    "ccy": "XTS"
+                  }
+                },
+                "cdtr_agt": test_agent("fsp002"),
+                "cdtr": test_party(&generate_id(), "EID"),
+                "cdtr_acct": test_account(&generate_id()),
+                "instr_for_cdtr_agt": [],
+                "rgltry_rptg": [],
+                "rltd_rmt_inf": [],
+                "splmtry_data": []
+              }
+            ]
+          }
+        ],
+        "splmtry_data": []
+      }
+    });
+
+    serde_json::from_value(v).unwrap()
+}
+
+#[cfg(test)]
+pub(crate) fn test_pain013() -> warden_core::iso20022::pain013::Pain013Document {
+    let cre_dt_tm = time::OffsetDateTime::now_utc()
+        .format(&time::format_description::well_known::Rfc3339)
+        .unwrap();
+
+    let v = serde_json::json!({
+      "cdtr_pmt_actvtn_req": {
+        "grp_hdr": {
+          "msg_id": generate_id(),
+          "cre_dt_tm": cre_dt_tm,
+          "nb_of_txs": "1",
+          "initg_pty": test_party(&generate_id(), "EID")
+        },
+        "pmt_inf": [
+          {
+            "pmt_inf_id": generate_id(),
+            "pmt_mtd": 2,
+            "reqd_exctn_dt": {
+              "dt_tm": cre_dt_tm
+            },
+            "dbtr": test_party(&generate_id(), "EID"),
+            "dbtr_acct": test_account(&generate_id()),
+            "dbtr_agt": test_agent("fsp001"),
+            "cdt_trf_tx": [
+              {
+                "pmt_id": {
+                  "instr_id": generate_id(),
+                  "end_to_end_id": generate_id()
+                },
+                "amt": {
+                  "instd_amt": {
+                    "value": 294.3,
+                    "ccy": "XTS"
+                  }
+                },
+                "chrg_br": 1,
+                "cdtr_agt": test_agent("fsp002"),
+                "cdtr": test_party(&generate_id(), "EID"),
+                "cdtr_acct": test_account(&generate_id()),
+                "instr_for_cdtr_agt": [],
+                "rgltry_rptg": [],
+                "rltd_rmt_inf": [],
+                "splmtry_data": []
+              }
+            ]
+          }
+        ],
+        "splmtry_data": []
+      }
+    });
+
+    serde_json::from_value(v).unwrap()
+}
@@ -0,0 +1,201 @@
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{HeaderMap, header},
+    response::{IntoResponse, Response},
+};
+use serde::de::DeserializeOwned;
+use tracing::debug;
+use warden_core::iso20022::xml;
+
+use crate::error::AppError;
+
+/// An ISO 20022 document read from either its JSON or canonical XML representation
+///
+/// The representation is chosen by the `Content-Type` header. Anything that is not XML is
+/// handled as JSON
+#[derive(Debug)]
+pub struct IsoDocument<T>(pub T);
+
+impl<S, T> FromRequest<S> for IsoDocument<T>
+where
+    T: DeserializeOwned + xml::Document,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_xml(req.headers()) {
+            let Json(document) = Json::<T>::from_request(req, state)
+                .await
+                .map_err(IntoResponse::into_response)?;
+            return Ok(Self(document));
+        }
+
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        let document = xml::from_slice::<T>(&bytes).map_err(|e| {
+            debug!(
+                path = e.path(),
+                line = e.line(),
+                "invalid xml document: {}",
+                e.reason()
+            );
+            AppError::bad_request(e.to_string()).into_response()
+        })?;
+
+        Ok(Self(document))
+    }
+}
+
+fn is_xml(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    mime == "application/xml" || mime == "text/xml" || mime.ends_with("+xml")
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode, header},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+    use warden_stack::cache::RedisManager;
+
+    use crate::{
+        error::PROBLEM_JSON,
+        server::{self, generate_id, test_config},
+        state::{AppState, Services},
+    };
+
+    fn pacs008_xml(msg_id: &str, instd_amt: &str) -> String {
+        let cre_dt_tm = time::OffsetDateTime::now_utc()
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap();
+
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pacs.008.001.12">
+  <FIToFICstmrCdtTrf>
+    <GrpHdr>
+      <MsgId>{msg_id}</MsgId>
+      <CreDtTm>{cre_dt_tm}</CreDtTm>
+      <NbOfTxs>1</NbOfTxs>
+      <SttlmInf><SttlmMtd>CLRG</SttlmMtd></SttlmInf>
+    </GrpHdr>
+    <CdtTrfTxInf>
+      <PmtId>
+        <InstrId>{instr_id}</InstrId>
+        <EndToEndId>{end_to_end_id}</EndToEndId>
+      </PmtId>
+      <IntrBkSttlmAmt Ccy="XTS">294.3</IntrBkSttlmAmt>
+      <InstdAmt Ccy="XTS">{instd_amt}</InstdAmt>
+      <XchgRate>1</XchgRate>
+      <ChrgBr>DEBT</ChrgBr>
+      <Dbtr>
+        <Id><PrvtId><Othr><Id>{dbtr_id}</Id><SchmeNm><Prtry>EID</Prtry></SchmeNm></Othr></PrvtId></Id>
+      </Dbtr>
+      <DbtrAcct>
+        <Id><Othr><Id>{dbtr_acct_id}</Id><SchmeNm><Prtry>MSISDN</Prtry></SchmeNm></Othr></Id>
+      </DbtrAcct>
+      <DbtrAgt><FinInstnId><ClrSysMmbId><MmbId>fsp001</MmbId></ClrSysMmbId></FinInstnId></DbtrAgt>
+      <CdtrAgt><FinInstnId><ClrSysMmbId><MmbId>fsp002</MmbId></ClrSysMmbId></FinInstnId></CdtrAgt>
+      <Cdtr>
+        <Id><PrvtId><Othr><Id>{cdtr_id}</Id><SchmeNm><Prtry>EID</Prtry></SchmeNm></Othr></PrvtId></Id>
+      </Cdtr>
+      <CdtrAcct>
+        <Id><Othr><Id>{cdtr_acct_id}</Id><SchmeNm><Prtry>MSISDN</Prtry></SchmeNm></Othr></Id>
+      </CdtrAcct>
+    </CdtTrfTxInf>
+  </FIToFICstmrCdtTrf>
+</Document>"#,
+            instr_id = generate_id(),
+            end_to_end_id = generate_id(),
+            dbtr_id = generate_id(),
+            dbtr_acct_id = generate_id(),
+            cdtr_id = generate_id(),
+            cdtr_acct_id = generate_id(),
+        )
+    }
+
+    #[sqlx::test]
+    async fn post_pacs008_xml(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let msg_id = generate_id();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header(header::CONTENT_TYPE, "application/xml")
+                    .uri("/api/v0/pacs008")
+                    .body(Body::from(pacs008_xml(&msg_id, "294.3")))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header(header::CONTENT_TYPE, "application/xml; charset=utf-8")
+                    .uri("/api/v0/pacs008")
+                    .body(Body::from(pacs008_xml(&generate_id(), "lots")))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.headers().get(header::CONTENT_TYPE).unwrap(),
+            PROBLEM_JSON
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let problem: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let detail = problem["detail"].as_str().unwrap();
+
+        assert!(detail.contains("/Document/FIToFICstmrCdtTrf/CdtTrfTxInf[1]/InstdAmt"));
+    }
+}
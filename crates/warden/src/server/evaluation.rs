@@ -0,0 +1,96 @@
+use std::{collections::HashMap, time::Duration};
+
+use async_nats::Subscriber;
+use futures_util::StreamExt;
+use tracing::{debug, trace, warn};
+use warden_core::message::{AggregationResult, Payload, payload::Transaction};
+
+use crate::{error::AppError, state::AppHandle};
+
+/// Aggregation results announced for a single message
+///
+/// The subscription is made before the message is submitted so that a fast evaluation is not
+/// missed
+pub struct Evaluation {
+    subscriber: Subscriber,
+    timeout: Duration,
+}
+
+impl Evaluation {
+    pub async fn subscribe(state: &AppHandle, msg_id: &str) -> Result<Self, AppError> {
+        let config = &state.app_config.evaluation;
+        let subject = format!("{}.{msg_id}", config.subject);
+        trace!(%subject, "subscribing to evaluation results");
+
+        let subscriber = state
+            .services
+            .jetstream
+            .client()
+            .subscribe(subject)
+            .await
+            .map_err(|e| {
+                warn!("nats: {e}");
+                AppError::unavailable("evaluation results are unavailable")
+            })?;
+
+        Ok(Self {
+            subscriber,
+            timeout: Duration::from_millis(config.timeout),
+        })
+    }
+
+    /// Collects the result of every transaction in `end_to_end_ids`, keyed by end to end id
+    ///
+    /// Gives up once the timeout elapses, so transactions that are still being evaluated are
+    /// absent from the result
+    pub async fn wait(
+        self,
+        end_to_end_ids: impl IntoIterator<Item = String>,
+    ) -> HashMap<String, AggregationResult> {
+        let Self {
+            mut subscriber,
+            timeout,
+        } = self;
+        let mut pending: Vec<_> = end_to_end_ids.into_iter().collect();
+        let mut results = HashMap::with_capacity(pending.len());
+
+        let collect = async {
+            while !pending.is_empty()
+                && let Some(message) = subscriber.next().await
+            {
+                let Ok(payload) = <Payload as prost::Message>::decode(message.payload.as_ref())
+                else {
+                    warn!(subject = %message.subject, "could not decode evaluation result");
+                    continue;
+                };
+
+                let end_to_end_id = match &payload.transaction {
+                    Some(Transaction::Pacs002(document)) => document
+                        .f_i_to_f_i_pmt_sts_rpt
+                        .tx_inf_and_sts
+                        .first()
+                        .and_then(|value| value.orgnl_end_to_end_id.clone()),
+                    _ => None,
+                };
+
+                if let (Some(end_to_end_id), Some(result)) =
+                    (end_to_end_id, payload.aggregation_result)
+                    && let Some(position) = pending.iter().position(|id| *id == end_to_end_id)
+                {
+                    debug!(%end_to_end_id, review = result.review, "received evaluation result");
+                    pending.swap_remove(position);
+                    results.insert(end_to_end_id, result);
+                }
+            }
+        };
+
+        if tokio::time::timeout(timeout, collect).await.is_err() {
+            warn!(
+                pending = pending.len(),
+                "timed out waiting for evaluation results"
+            );
+        }
+
+        results
+    }
+}
@@ -0,0 +1,390 @@
+use std::time::Duration;
+
+use axum::{
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use opentelemetry_semantic_conventions::attribute;
+use tracing::{Instrument, debug, error, info_span, instrument, warn};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use serde::Serialize;
+use warden_middleware::auth::Principal;
+
+use crate::{
+    error::{AppError, PROBLEM_JSON},
+    state::AppHandle,
+};
+
+pub const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
+/// Resolves the key a submission is deduplicated on
+///
+/// The `Idempotency-Key` header takes precedence. Without it, the message id and the end to end
+/// ids it carries are used. Keys are scoped to the caller, so that one may neither collide with
+/// nor replay the submissions of another. They are shared between API versions: a submission
+/// retried on another version replays the response in the shape it was first given
+pub fn key<'a>(
+    headers: &HeaderMap,
+    principal: &Principal,
+    tx_tp: &str,
+    msg_id: &str,
+    end_to_end_ids: impl Iterator<Item = &'a str>,
+) -> Result<String, AppError> {
+    match headers.get(IDEMPOTENCY_KEY) {
+        Some(value) => {
+            let value = value
+                .to_str()
+                .map_err(|_| AppError::bad_request("Idempotency-Key must be visible ASCII"))?;
+            if value.is_empty() {
+                return Err(AppError::bad_request("Idempotency-Key must not be empty"));
+            }
+            Ok(format!("{}:{tx_tp}:{value}", principal.subject))
+        }
+        None => Ok(format!(
+            "{}:{tx_tp}:{msg_id}:{}",
+            principal.subject,
+            end_to_end_ids.collect::<Vec<_>>().join(",")
+        )),
+    }
+}
+
+/// Claims `key` for `request` before any side effects take place
+///
+/// Returns the original response if the request was already processed. A claim that was not
+/// finished within the configured lease, because the instance holding it stopped, is taken over
+/// by a request with the same payload. Keys past their retention are claimed afresh
+#[instrument(skip(state, request), err(Debug))]
+pub async fn claim(
+    state: &AppHandle,
+    key: &str,
+    request: &serde_json::Value,
+) -> Result<Option<Response>, AppError> {
+    let config = &state.app_config.idempotency;
+
+    let span = info_span!("create.idempotency_key");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "insert");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "idempotency_key");
+    span.set_attribute("otel.kind", "client");
+
+    let claimed = sqlx::query!(
+        "insert into idempotency_key (key, request) values ($1, $2)
+        on conflict (key) do update set
+            request = excluded.request,
+            status = null,
+            response = null,
+            created_at = now(),
+            claimed_at = now()
+        where (
+            idempotency_key.status is null
+            and idempotency_key.request = excluded.request
+            and idempotency_key.claimed_at < now() - $3::bigint * interval '1 millisecond'
+        ) or idempotency_key.created_at < now() - $4::bigint * interval '1 second'",
+        key,
+        request,
+        config.lease as i64,
+        config.retention as i64
+    )
+    .execute(&state.services.postgres)
+    .instrument(span)
+    .await?
+    .rows_affected()
+        == 1;
+
+    if claimed {
+        debug!("idempotency key claimed");
+        return Ok(None);
+    }
+
+    let span = info_span!("get.idempotency_key");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "idempotency_key");
+    span.set_attribute("otel.kind", "client");
+
+    let existing = sqlx::query!(
+        r#"select request = $2 as "matches!", status, response as "response: sqlx::types::Json<serde_json::Value>" from idempotency_key where key = $1"#,
+        key,
+        request
+    )
+    .fetch_optional(&state.services.postgres)
+    .instrument(span)
+    .await?;
+
+    let Some(existing) = existing else {
+        // released between the insert and the select
+        return Err(AppError::Conflict(
+            "request is already being processed".to_string(),
+        ));
+    };
+
+    if !existing.matches {
+        warn!("idempotency key reused with a different payload");
+        return Err(AppError::Conflict(
+            "idempotency key was already used with a different payload".to_string(),
+        ));
+    }
+
+    match (existing.status, existing.response) {
+        (Some(status), Some(response)) => {
+            debug!(status, "replaying stored response");
+            let status = StatusCode::from_u16(status as u16).map_err(anyhow::Error::new)?;
+            let mut response = (status, axum::Json(response.0)).into_response();
+            if status.is_client_error() {
+                response
+                    .headers_mut()
+                    .insert(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+            }
+            Ok(Some(response))
+        }
+        _ => Err(AppError::Conflict(
+            "request is already being processed".to_string(),
+        )),
+    }
+}
+
+/// Stores the outcome of a claimed request so it can be replayed
+///
+/// Client errors are stored as well, as a retry with the same payload fails the same way. On a
+/// server error the key is released instead so that the request may be retried: each document
+/// is stored together with its outbox message, so one that failed left no history behind, and
+/// the pseudonyms and cache writes that may have preceded it are safe to repeat
+#[instrument(skip(state, result), err(Debug))]
+pub async fn finish(
+    state: &AppHandle,
+    key: &str,
+    result: Result<(StatusCode, impl Serialize), AppError>,
+) -> Result<Response, AppError> {
+    match result {
+        Ok((status, response)) => {
+            store(state, key, status, &response).await?;
+            Ok((status, axum::Json(response)).into_response())
+        }
+        Err(e) if e.status().is_client_error() => {
+            store(state, key, e.status(), &e.problem()).await?;
+            Err(e)
+        }
+        Err(e) => {
+            release(state, key).await?;
+            Err(e)
+        }
+    }
+}
+
+/// Removes keys past their retention, until the process exits
+pub async fn expire(state: AppHandle) {
+    let config = &state.app_config.idempotency;
+    let mut interval = tokio::time::interval(Duration::from_secs(config.cleanup_interval));
+
+    loop {
+        interval.tick().await;
+
+        let span = info_span!("delete.idempotency_key");
+        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+        span.set_attribute(attribute::DB_OPERATION_NAME, "delete");
+        span.set_attribute(attribute::DB_COLLECTION_NAME, "idempotency_key");
+        span.set_attribute("otel.kind", "client");
+
+        match sqlx::query!(
+            "delete from idempotency_key where created_at < now() - $1::bigint * interval '1 second'",
+            config.retention as i64
+        )
+        .execute(&state.services.postgres)
+        .instrument(span)
+        .await
+        {
+            Ok(result) => debug!(count = result.rows_affected(), "expired idempotency keys"),
+            Err(e) => error!("could not expire idempotency keys: {e}"),
+        }
+    }
+}
+
+async fn store(
+    state: &AppHandle,
+    key: &str,
+    status: StatusCode,
+    response: &impl Serialize,
+) -> Result<(), AppError> {
+    let span = info_span!("update.idempotency_key");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "update");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "idempotency_key");
+    span.set_attribute("otel.kind", "client");
+
+    sqlx::query!(
+        "update idempotency_key set status = $2, response = $3 where key = $1",
+        key,
+        status.as_u16() as i16,
+        sqlx::types::Json(response) as _
+    )
+    .execute(&state.services.postgres)
+    .instrument(span)
+    .await?;
+
+    debug!(%status, "idempotency key finished");
+    Ok(())
+}
+
+async fn release(state: &AppHandle, key: &str) -> Result<(), AppError> {
+    let span = info_span!("delete.idempotency_key");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "delete");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "idempotency_key");
+    span.set_attribute("otel.kind", "client");
+
+    sqlx::query!("delete from idempotency_key where key = $1", key)
+        .execute(&state.services.postgres)
+        .instrument(span)
+        .await?;
+
+    debug!("idempotency key released");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue, StatusCode, header};
+    use sqlx::PgPool;
+    use warden_middleware::auth::{Credential, Principal, Role};
+    use warden_stack::cache::RedisManager;
+
+    use crate::{
+        error::{AppError, PROBLEM_JSON},
+        server::{generate_id, test_config},
+        state::{AppHandle, AppState, Services},
+    };
+
+    async fn claim(
+        state: &AppHandle,
+        key: &str,
+        request: &serde_json::Value,
+    ) -> Option<axum::response::Response> {
+        super::claim(state, key, request).await.unwrap()
+    }
+
+    async fn state(pool: PgPool) -> AppHandle {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &config,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[test]
+    fn scopes_keys_to_the_caller() {
+        let key = |subject: &str, headers: &HeaderMap| {
+            let principal = Principal {
+                subject: subject.to_string(),
+                credential: Credential::ApiKey,
+                roles: vec![Role::Participant],
+                participant: None,
+            };
+            super::key(
+                headers,
+                &principal,
+                "pacs.008.001.12",
+                "msg",
+                ["e2e"].into_iter(),
+            )
+            .unwrap()
+        };
+
+        let derived = HeaderMap::new();
+        assert_eq!(key("fsp001", &derived), "fsp001:pacs.008.001.12:msg:e2e");
+        assert_ne!(key("fsp001", &derived), key("fsp002", &derived));
+
+        let mut supplied = HeaderMap::new();
+        supplied.insert(super::IDEMPOTENCY_KEY, HeaderValue::from_static("retry-1"));
+        assert_eq!(key("fsp001", &supplied), "fsp001:pacs.008.001.12:retry-1");
+        assert_ne!(key("fsp001", &supplied), key("fsp002", &supplied));
+    }
+
+    #[sqlx::test]
+    async fn takes_over_stale_claims(pool: PgPool) {
+        let state = state(pool.clone()).await;
+        let key = generate_id();
+        let request = serde_json::json!({ "msg_id": key });
+
+        assert!(claim(&state, &key, &request).await.is_none());
+        assert!(matches!(
+            super::claim(&state, &key, &request).await,
+            Err(AppError::Conflict(_))
+        ));
+
+        // the instance holding the claim stopped before finishing it
+        sqlx::query("update idempotency_key set claimed_at = now() - interval '1 hour'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // but only the same payload may take it over
+        let other = serde_json::json!({ "msg_id": generate_id() });
+        assert!(matches!(
+            super::claim(&state, &key, &other).await,
+            Err(AppError::Conflict(_))
+        ));
+        assert!(claim(&state, &key, &request).await.is_none());
+    }
+
+    #[sqlx::test]
+    async fn replays_client_errors(pool: PgPool) {
+        let state = state(pool).await;
+        let key = generate_id();
+        let request = serde_json::json!({ "msg_id": key });
+
+        assert!(claim(&state, &key, &request).await.is_none());
+        let result: Result<(StatusCode, ()), _> = Err(AppError::unprocessable("invalid currency"));
+        assert!(super::finish(&state, &key, result).await.is_err());
+
+        let response = claim(&state, &key, &request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+    }
+
+    #[sqlx::test]
+    async fn releases_on_server_errors(pool: PgPool) {
+        let state = state(pool).await;
+        let key = generate_id();
+        let request = serde_json::json!({ "msg_id": key });
+
+        assert!(claim(&state, &key, &request).await.is_none());
+        let result: Result<(StatusCode, ()), _> =
+            Err(AppError::unavailable("cache is unavailable"));
+        assert!(super::finish(&state, &key, result).await.is_err());
+
+        assert!(claim(&state, &key, &request).await.is_none());
+    }
+
+    #[sqlx::test]
+    async fn expires_finished_keys(pool: PgPool) {
+        let state = state(pool.clone()).await;
+        let key = generate_id();
+        let request = serde_json::json!({ "msg_id": key });
+
+        assert!(claim(&state, &key, &request).await.is_none());
+        let result = Ok((StatusCode::CREATED, ()));
+        super::finish(&state, &key, result).await.unwrap();
+        assert!(claim(&state, &key, &request).await.is_some());
+
+        sqlx::query("update idempotency_key set created_at = now() - interval '2 days'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // past the retention, even a different payload claims the key afresh
+        let other = serde_json::json!({ "msg_id": generate_id() });
+        assert!(claim(&state, &key, &other).await.is_none());
+    }
+}
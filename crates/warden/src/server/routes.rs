@@ -1,20 +1,78 @@
+pub mod history;
 pub mod metrics;
 pub mod processor;
 
-use utoipa::OpenApi;
+use utoipa::{
+    Modify, OpenApi,
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+};
+use utoipa_axum::router::OpenApiRouter;
+
+use warden_middleware::auth::Principal;
+
+use crate::{error::AppError, state::AppHandle, version::Version};
 
 const PACS008_001_12: &str = "pacs.008.001.12";
 const PACS002_001_12: &str = "pacs.002.001.12";
+const PAIN001_001_12: &str = "pain.001.001.12";
+const PAIN013_001_11: &str = "pain.013.001.11";
+const BATCH: &str = "batch";
+const HISTORY: &str = "history";
 
 #[derive(OpenApi)]
 #[openapi(
     tags(
         (name = PACS008_001_12, description = "Submit a pacs.008.001.12 payload"),
         (name = PACS002_001_12, description = "Submit a pacs.002.001.12 payload"),
-    )
+        (name = PAIN001_001_12, description = "Submit a pain.001.001.12 payload"),
+        (name = PAIN013_001_11, description = "Submit a pain.013.001.11 payload"),
+        (name = BATCH, description = "Load many pacs.008.001.12 and pacs.002.001.12 payloads at once"),
+        (name = HISTORY, description = "Look up transactions that were accepted"),
+    ),
+    components(schemas(crate::error::ProblemDetails)),
+    modifiers(&SecurityAddon),
+    security(("bearer" = []), ("api_key" = []))
 )]
 pub struct ApiDoc;
 
+/// Restricts a route to admins
+fn require_admin(principal: &Principal) -> Result<(), AppError> {
+    if principal.is_admin() {
+        Ok(())
+    } else {
+        tracing::warn!(subject = %principal.subject, "admin route denied");
+        Err(AppError::forbidden("only admins may use this route"))
+    }
+}
+
+/// Documents the credentials accepted by
+/// [require_authentication](warden_middleware::auth::require_authentication)
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+        );
+    }
+}
+
+/// Every route served under the prefix of `version`
+pub fn router(state: AppHandle, version: Version) -> OpenApiRouter {
+    processor::router(state.clone(), version).merge(history::router(state))
+}
+
 #[cfg(test)]
 mod tests {
     use axum::{
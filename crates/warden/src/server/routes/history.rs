@@ -0,0 +1,539 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::{Instrument, debug, info_span};
+use utoipa::{IntoParams, ToSchema};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use uuid::Uuid;
+use warden_core::iso20022::{pacs002::Pacs002Document, pacs008::Pacs008Document};
+use warden_middleware::{auth::Principal, version::Versioned};
+use warden_stack::{
+    opentelemetry_semantic_conventions::attribute, tracing_opentelemetry::OpenTelemetrySpanExt,
+};
+
+use crate::{
+    error::{AppError, ProblemDetails},
+    server::routes::{HISTORY, require_admin},
+    state::AppHandle,
+    version::Version,
+};
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 500;
+
+pub fn router(store: AppHandle) -> OpenApiRouter {
+    OpenApiRouter::new()
+        .routes(routes!(get_pacs008))
+        .routes(routes!(get_transaction))
+        .routes(routes!(list_transactions))
+        .with_state(store)
+}
+
+/// A credit transfer as it was stored when accepted
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StoredPacs008 {
+    /// Identifier of the stored document
+    pub id: Uuid,
+    /// When the credit transfer was accepted
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub created_at: Option<OffsetDateTime>,
+    /// The document, holding a single credit transfer
+    pub document: Pacs008Document,
+}
+
+/// A status report as it was stored when accepted
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StoredPacs002 {
+    /// Identifier of the stored document
+    pub id: Uuid,
+    /// When the status report was accepted
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub created_at: Option<OffsetDateTime>,
+    /// The document, holding a single status entry
+    pub document: Pacs002Document,
+}
+
+/// Every credit transfer of a pacs.008 message
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MessageHistory {
+    /// Message identifier from the group header
+    pub msg_id: String,
+    /// Credit transfers in the order they were accepted
+    pub transactions: Vec<StoredPacs008>,
+}
+
+/// A credit transfer together with the status reports that refer to it
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TransactionHistory {
+    /// End to end identifier of the transaction
+    pub end_to_end_id: String,
+    /// The most recent credit transfer with this end to end id
+    pub pacs008: StoredPacs008,
+    /// Status reports in the order they were accepted
+    pub pacs002: Vec<StoredPacs002>,
+}
+
+/// A page of credit transfers, newest first
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TransactionPage {
+    /// Credit transfers on this page
+    pub transactions: Vec<StoredPacs008>,
+    /// Passed as `page_token` to fetch the next page. Absent on the last page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct MessagePath {
+    msg_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct TransactionPath {
+    end_to_end_id: String,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub(super) struct ListParams {
+    /// Only credit transfers accepted at or after this time
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    from: Option<OffsetDateTime>,
+    /// Only credit transfers accepted before this time
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    to: Option<OffsetDateTime>,
+    /// Only credit transfers from this debtor account, its id followed by the `mmb_id` of its agent
+    dbtr_acct_id: Option<String>,
+    /// Only credit transfers to this creditor account, its id followed by the `mmb_id` of its agent
+    cdtr_acct_id: Option<String>,
+    /// Number of credit transfers per page. Defaults to 50, at most 500
+    page_size: Option<u32>,
+    /// `next_page_token` of the previous page
+    page_token: Option<String>,
+}
+
+struct Pacs008Row {
+    id: Uuid,
+    created_at: Option<OffsetDateTime>,
+    document: sqlx::types::Json<Pacs008Document>,
+}
+
+impl From<Pacs008Row> for StoredPacs008 {
+    fn from(value: Pacs008Row) -> Self {
+        Self {
+            id: value.id,
+            created_at: value.created_at,
+            document: value.document.0,
+        }
+    }
+}
+
+struct Pacs002Row {
+    id: Uuid,
+    created_at: Option<OffsetDateTime>,
+    document: sqlx::types::Json<Pacs002Document>,
+}
+
+impl From<Pacs002Row> for StoredPacs002 {
+    fn from(value: Pacs002Row) -> Self {
+        Self {
+            id: value.id,
+            created_at: value.created_at,
+            document: value.document.0,
+        }
+    }
+}
+
+/// Get the credit transfers of a pacs.008 message
+#[utoipa::path(
+    get,
+    responses(
+        (status = OK, body = MessageHistory, description = "the message was found"),
+        (status = NOT_FOUND, body = ProblemDetails, content_type = "application/problem+json", description = "no credit transfer was accepted for this message"),
+        (status = FORBIDDEN, body = ProblemDetails, content_type = "application/problem+json", description = "the caller is not an admin"),
+        (status = SERVICE_UNAVAILABLE, body = ProblemDetails, content_type = "application/problem+json", description = "transaction history is unavailable"),
+    ),
+    operation_id = "get_pacs_008", // https://github.com/juhaku/utoipa/issues/1170
+    path = "/pacs008/{msg_id}",
+    params(
+        ("msg_id" = String, Path, description = "Message identifier from the group header"),
+    ),
+    tag = HISTORY,
+)]
+#[tracing::instrument(skip(state), err(Debug), fields(method = "GET"))]
+pub(super) async fn get_pacs008(
+    Versioned(version): Versioned<Version>,
+    State(state): State<AppHandle>,
+    principal: Principal,
+    Path(MessagePath { msg_id }): Path<MessagePath>,
+) -> Result<Json<MessageHistory>, AppError> {
+    require_admin(&principal)?;
+
+    let span = info_span!("get.transaction_history.pacs008");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "pacs008");
+    span.set_attribute(attribute::DB_OPERATION_PARAMETER, msg_id.to_string());
+
+    let transactions = sqlx::query_as!(
+        Pacs008Row,
+        r#"select id, created_at, document as "document: sqlx::types::Json<Pacs008Document>"
+        from pacs008 where message_id = $1 order by id"#,
+        msg_id
+    )
+    .fetch_all(&state.services.postgres)
+    .instrument(span)
+    .await?;
+
+    if transactions.is_empty() {
+        return Err(AppError::not_found(format!(
+            "no pacs.008 found for msg_id {msg_id}"
+        )));
+    }
+
+    debug!(count = transactions.len(), "found credit transfers");
+
+    Ok(Json(MessageHistory {
+        msg_id,
+        transactions: transactions.into_iter().map(StoredPacs008::from).collect(),
+    }))
+}
+
+/// Get a credit transfer and its status reports
+#[utoipa::path(
+    get,
+    responses(
+        (status = OK, body = TransactionHistory, description = "the transaction was found"),
+        (status = NOT_FOUND, body = ProblemDetails, content_type = "application/problem+json", description = "no credit transfer was accepted with this end to end id"),
+        (status = FORBIDDEN, body = ProblemDetails, content_type = "application/problem+json", description = "the caller is not an admin"),
+        (status = SERVICE_UNAVAILABLE, body = ProblemDetails, content_type = "application/problem+json", description = "transaction history is unavailable"),
+    ),
+    operation_id = "get_transaction", // https://github.com/juhaku/utoipa/issues/1170
+    path = "/transactions/{end_to_end_id}",
+    params(
+        ("end_to_end_id" = String, Path, description = "End to end identifier of the transaction"),
+    ),
+    tag = HISTORY,
+)]
+#[tracing::instrument(skip(state), err(Debug), fields(method = "GET"))]
+pub(super) async fn get_transaction(
+    Versioned(version): Versioned<Version>,
+    State(state): State<AppHandle>,
+    principal: Principal,
+    Path(TransactionPath { end_to_end_id }): Path<TransactionPath>,
+) -> Result<Json<TransactionHistory>, AppError> {
+    require_admin(&principal)?;
+
+    let span = info_span!("get.transaction_history.pacs008");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "pacs008");
+    span.set_attribute(attribute::DB_OPERATION_PARAMETER, end_to_end_id.to_string());
+
+    let pacs008 = sqlx::query_as!(
+        Pacs008Row,
+        r#"select id, created_at, document as "document: sqlx::types::Json<Pacs008Document>"
+        from pacs008 where end_to_end_id = $1 order by id desc limit 1"#,
+        end_to_end_id
+    )
+    .fetch_optional(&state.services.postgres)
+    .instrument(span)
+    .await?
+    .ok_or_else(|| {
+        AppError::not_found(format!(
+            "no pacs.008 found for end_to_end_id {end_to_end_id}"
+        ))
+    })?;
+
+    let span = info_span!("get.transaction_history.pacs002");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "pacs002");
+    span.set_attribute(attribute::DB_OPERATION_PARAMETER, end_to_end_id.to_string());
+
+    let pacs002 = sqlx::query_as!(
+        Pacs002Row,
+        r#"select id, created_at, document as "document: sqlx::types::Json<Pacs002Document>"
+        from pacs002 where end_to_end_id = $1 order by id"#,
+        end_to_end_id
+    )
+    .fetch_all(&state.services.postgres)
+    .instrument(span)
+    .await?;
+
+    debug!(id = %pacs008.id, reports = pacs002.len(), "found transaction");
+
+    Ok(Json(TransactionHistory {
+        end_to_end_id,
+        pacs008: pacs008.into(),
+        pacs002: pacs002.into_iter().map(StoredPacs002::from).collect(),
+    }))
+}
+
+/// List credit transfers, newest first
+#[utoipa::path(
+    get,
+    responses(
+        (status = OK, body = TransactionPage, description = "a page of credit transfers"),
+        (status = BAD_REQUEST, body = ProblemDetails, content_type = "application/problem+json", description = "a filter or the page token is malformed"),
+        (status = FORBIDDEN, body = ProblemDetails, content_type = "application/problem+json", description = "the caller is not an admin"),
+        (status = SERVICE_UNAVAILABLE, body = ProblemDetails, content_type = "application/problem+json", description = "transaction history is unavailable"),
+    ),
+    operation_id = "list_transactions", // https://github.com/juhaku/utoipa/issues/1170
+    path = "/transactions",
+    params(ListParams),
+    tag = HISTORY,
+)]
+#[tracing::instrument(skip(state), err(Debug), fields(method = "GET"))]
+pub(super) async fn list_transactions(
+    Versioned(version): Versioned<Version>,
+    State(state): State<AppHandle>,
+    principal: Principal,
+    Query(params): Query<ListParams>,
+) -> Result<Json<TransactionPage>, AppError> {
+    require_admin(&principal)?;
+
+    let page_size = match params.page_size {
+        None | Some(0) => DEFAULT_PAGE_SIZE,
+        Some(size) => size.min(MAX_PAGE_SIZE),
+    };
+
+    // the cursor is the id of the last credit transfer on the previous page
+    let after = params
+        .page_token
+        .as_deref()
+        .map(Uuid::parse_str)
+        .transpose()
+        .map_err(|_| AppError::bad_request("invalid page_token"))?;
+
+    let span = info_span!("get.transaction_history.pacs008");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "pacs008");
+
+    let mut transactions = sqlx::query_as!(
+        Pacs008Row,
+        r#"select id, created_at, document as "document: sqlx::types::Json<Pacs008Document>"
+        from pacs008
+        where ($1::timestamptz is null or created_at >= $1)
+          and ($2::timestamptz is null or created_at < $2)
+          and ($3::text is null or debtor_account_id = $3)
+          and ($4::text is null or creditor_account_id = $4)
+          and ($5::uuid is null or id < $5)
+        order by id desc
+        limit $6"#,
+        params.from,
+        params.to,
+        params.dbtr_acct_id,
+        params.cdtr_acct_id,
+        after,
+        i64::from(page_size) + 1,
+    )
+    .fetch_all(&state.services.postgres)
+    .instrument(span)
+    .await?;
+
+    // one more row than requested tells whether there is another page
+    let next_page_token = if transactions.len() > page_size as usize {
+        transactions.truncate(page_size as usize);
+        transactions.last().map(|value| value.id.to_string())
+    } else {
+        None
+    };
+
+    debug!(count = transactions.len(), "listed credit transfers");
+
+    Ok(Json(TransactionPage {
+        transactions: transactions.into_iter().map(StoredPacs008::from).collect(),
+        next_page_token,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+    use warden_core::message::DataCache;
+    use warden_stack::{cache::RedisManager, redis::AsyncCommands};
+
+    use crate::{
+        server::{self, generate_id, test_config, test_pacs008},
+        state::{AppState, Services},
+    };
+
+    async fn get(app: &Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[sqlx::test]
+    async fn lookup(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let transactions = [test_pacs008(), test_pacs008()];
+        for transaction in &transactions {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .header("Content-Type", "application/json")
+                        .uri("/api/v0/pacs008")
+                        .body(Body::from(serde_json::to_vec(transaction).unwrap()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        let msg_id = &transactions[0].f_i_to_f_i_cstmr_cdt_trf.grp_hdr.msg_id;
+        let (status, body) = get(&app, &format!("/api/v0/pacs008/{msg_id}")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["transactions"].as_array().unwrap().len(), 1);
+
+        let end_to_end_id = &transactions[1].f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0]
+            .pmt_id
+            .end_to_end_id;
+        let (status, body) = get(&app, &format!("/api/v0/transactions/{end_to_end_id}")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["end_to_end_id"], end_to_end_id.as_str());
+        assert!(body["pacs002"].as_array().unwrap().is_empty());
+
+        let (status, first) = get(&app, "/api/v0/transactions?page_size=1").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(first["transactions"].as_array().unwrap().len(), 1);
+
+        let token = first["next_page_token"].as_str().unwrap();
+        let (_, second) = get(
+            &app,
+            &format!("/api/v0/transactions?page_size=1&page_token={token}"),
+        )
+        .await;
+        assert_eq!(second["transactions"].as_array().unwrap().len(), 1);
+        assert!(second.get("next_page_token").is_none());
+        assert_ne!(
+            first["transactions"][0]["id"],
+            second["transactions"][0]["id"]
+        );
+
+        let (status, _) = get(&app, &format!("/api/v0/pacs008/{}", generate_id())).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = get(&app, "/api/v0/transactions?page_token=nope").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test]
+    async fn list_by_data_cache_account(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state.clone());
+
+        let transaction = test_pacs008();
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/pacs008")
+                    .body(Body::from(serde_json::to_vec(&transaction).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let cdt_trf_tx_inf = &transaction.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0];
+        let bytes: Vec<u8> = state
+            .services
+            .cache
+            .get()
+            .await
+            .unwrap()
+            .get(&cdt_trf_tx_inf.pmt_id.end_to_end_id)
+            .await
+            .unwrap();
+        let data_cache = <DataCache as prost::Message>::decode(bytes.as_slice()).unwrap();
+
+        for query in [
+            format!("dbtr_acct_id={}", data_cache.dbtr_acct_id),
+            format!("cdtr_acct_id={}", data_cache.cdtr_acct_id),
+        ] {
+            let (status, body) = get(&app, &format!("/api/v0/transactions?{query}")).await;
+            assert_eq!(status, StatusCode::OK);
+            let transactions = body["transactions"].as_array().unwrap();
+            assert_eq!(transactions.len(), 1);
+            assert_eq!(
+                transactions[0]["document"]["f_i_to_f_i_cstmr_cdt_trf"]["cdt_trf_tx_inf"][0]["pmt_id"]
+                    ["end_to_end_id"],
+                cdt_trf_tx_inf.pmt_id.end_to_end_id.as_str()
+            );
+        }
+
+        // the account's own id alone names no account
+        let othr = &cdt_trf_tx_inf
+            .dbtr_acct
+            .as_ref()
+            .unwrap()
+            .id
+            .as_ref()
+            .unwrap()
+            .othr;
+        let (_, body) = get(
+            &app,
+            &format!("/api/v0/transactions?dbtr_acct_id={}", othr.id),
+        )
+        .await;
+        assert!(body["transactions"].as_array().unwrap().is_empty());
+    }
+}
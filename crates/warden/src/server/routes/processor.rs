@@ -1,13 +1,355 @@
+/// Declares the v0 and v1 handlers of a submission, which differ only in the shape of their
+/// response
+///
+/// `responses` are answered with the submission response, and `problems` with a problem. The
+/// bad request, rate limit and unavailable problems every submission may answer are added to
+/// them. `accept` is called with the version of the handler
+macro_rules! submission_handlers {
+    (
+        $(#[doc = $doc:tt])*
+        handlers = ($v0:ident, $v1:ident),
+        responses($(($status:ident, $description:tt)),* $(,)?),
+        problems($(($problem:ident, $problem_description:tt)),* $(,)?),
+        operation_id = $operation_id:tt,
+        path = $path:tt,
+        params($($params:tt)*),
+        tag = $tag:ident,
+        document = $document:ident,
+        skip($($skip:ident),*),
+        async fn($($args:tt)*) { |$version:ident| $accept:expr }
+    ) => {
+        submission_handlers!(
+            @handler [$(#[doc = $doc])*] $v0, V0,
+            [$((status = $status, body = SubmissionResponse, description = $description),)*],
+            [$(($problem, $problem_description)),*],
+            $operation_id, $path, [$($params)*], $tag, $document, [$($skip),*], [$($args)*],
+            |$version| $accept
+        );
+        submission_handlers!(
+            @handler [
+                $(#[doc = $doc])*
+                #[doc = ""]
+                #[doc = " Every transaction reports its `status`, and the full problem when it was not accepted"]
+            ] $v1, V1,
+            [$((status = $status, body = v1::SubmissionResponse, description = $description),)*],
+            [$(($problem, $problem_description)),*],
+            $operation_id, $path, [$($params)*], $tag, $document, [$($skip),*], [$($args)*],
+            |$version| $accept
+        );
+    };
+    (
+        @handler [$($doc:tt)*] $name:ident, $variant:ident,
+        [$($responses:tt)*],
+        [$(($problem:ident, $problem_description:tt)),*],
+        $operation_id:tt, $path:tt, [$($params:tt)*], $tag:ident, $document:ident, [$($skip:ident),*], [$($args:tt)*],
+        |$version:ident| $accept:expr
+    ) => {
+        $($doc)*
+        #[utoipa::path(
+            post,
+            responses(
+                $($responses)*
+                (status = BAD_REQUEST, body = ProblemDetails, content_type = "application/problem+json", description = "a required element is missing"),
+                $((status = $problem, body = ProblemDetails, content_type = "application/problem+json", description = $problem_description),)*
+                (status = TOO_MANY_REQUESTS, body = ProblemDetails, content_type = "application/problem+json", description = "the caller has exceeded its rate limit", headers(("Retry-After" = u64, description = "seconds until the caller may retry"))),
+                (status = SERVICE_UNAVAILABLE, body = ProblemDetails, content_type = "application/problem+json", description = "a downstream service is unavailable, or too many requests are in flight"),
+            ),
+            operation_id = $operation_id, // https://github.com/juhaku/utoipa/issues/1170
+            path = $path,
+            params($($params)*),
+            tag = $tag,
+            request_body(
+                description = "The document as JSON or as canonical ISO 20022 XML",
+                content(
+                    ($document = "application/json"),
+                    ($document = "application/xml"),
+                )
+            ))
+        ]
+        #[tracing::instrument(
+            skip($($skip),*),
+            err(Debug),
+            fields(method = "POST", msg_id, tx_tp, tx_count)
+        )]
+        pub(super) async fn $name($($args)*) -> Result<Response, AppError> {
+            let $version = Version::$variant;
+            $accept
+        }
+    };
+}
+
+mod batch;
+mod history;
 mod pacs002;
 mod pacs008;
+mod pain001;
+mod pain013;
+pub(crate) mod replay;
+pub mod v1;
+
+use std::collections::HashMap;
 
+use axum::{extract::DefaultBodyLimit, http::StatusCode, response::Response};
+use serde::Serialize;
+use tracing::warn;
+use utoipa::ToSchema;
 use utoipa_axum::{router::OpenApiRouter, routes};
+use warden_core::message::AggregationResult;
+use warden_middleware::auth::{Credential, Principal};
+
+use crate::{
+    error::{AppError, ProblemDetails},
+    server::idempotency,
+    state::AppHandle,
+    version::Version,
+};
+
+pub fn router(store: AppHandle, version: Version) -> OpenApiRouter {
+    // batches are expected to be far larger than a single document
+    let batch = OpenApiRouter::new()
+        .routes(routes!(batch::post_batch))
+        .layer(DefaultBodyLimit::max(store.app_config.batch.body_limit));
+
+    let submissions = match version {
+        Version::V0 => OpenApiRouter::new()
+            .routes(routes!(pacs008::post_pacs008))
+            .routes(routes!(pacs002::post_pacs002))
+            .routes(routes!(pain001::post_pain001))
+            .routes(routes!(pain013::post_pain013)),
+        Version::V1 => OpenApiRouter::new()
+            .routes(routes!(pacs008::post_pacs008_v1))
+            .routes(routes!(pacs002::post_pacs002_v1))
+            .routes(routes!(pain001::post_pain001_v1))
+            .routes(routes!(pain013::post_pain013_v1)),
+    };
+
+    submissions.merge(batch).with_state(store)
+}
+
+/// Outcome of a single transaction within a submitted message
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TransactionOutcome {
+    /// End to end identifier of the transaction
+    pub end_to_end_id: String,
+    /// Whether the transaction was accepted for evaluation
+    pub accepted: bool,
+    /// Reason the transaction was not accepted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Verdict of the evaluation, when the submission waited for it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evaluation: Option<AggregationResult>,
+    /// The full problem behind `error`, reported from v1 onwards
+    #[serde(skip)]
+    pub problem: Option<ProblemDetails>,
+}
+
+/// Result of submitting a message
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubmissionResponse {
+    /// Message identifier from the group header
+    pub msg_id: String,
+    /// Outcome for each transaction, in the order they were received
+    pub transactions: Vec<TransactionOutcome>,
+}
+
+/// Collects the outcome of every transaction in a message
+#[derive(Debug)]
+struct Submission {
+    response: SubmissionResponse,
+    first_error: Option<AppError>,
+    incomplete: bool,
+}
+
+impl Submission {
+    fn new(msg_id: impl Into<String>, capacity: usize) -> Self {
+        Self {
+            response: SubmissionResponse {
+                msg_id: msg_id.into(),
+                transactions: Vec::with_capacity(capacity),
+            },
+            first_error: None,
+            incomplete: false,
+        }
+    }
+
+    fn record(&mut self, end_to_end_id: String, result: Result<(), AppError>) {
+        let problem = match result {
+            Ok(()) => None,
+            Err(e) => {
+                warn!(msg_id = %self.response.msg_id, %end_to_end_id, "transaction not accepted: {e}");
+                let problem = e.problem();
+                self.first_error.get_or_insert(e);
+                Some(problem)
+            }
+        };
+
+        self.response.transactions.push(TransactionOutcome {
+            end_to_end_id,
+            accepted: problem.is_none(),
+            error: problem.as_ref().map(|value| value.detail.clone()),
+            evaluation: None,
+            problem,
+        });
+    }
+
+    /// End to end ids of the accepted transactions
+    fn accepted_ids(&self) -> Vec<String> {
+        self.response
+            .transactions
+            .iter()
+            .filter(|outcome| outcome.accepted)
+            .map(|outcome| outcome.end_to_end_id.clone())
+            .collect()
+    }
+
+    /// Attaches the verdict of each accepted transaction. Any transaction left without one marks
+    /// the submission as incomplete
+    fn evaluated(&mut self, mut results: HashMap<String, AggregationResult>) {
+        for outcome in self
+            .response
+            .transactions
+            .iter_mut()
+            .filter(|outcome| outcome.accepted)
+        {
+            outcome.evaluation = results.remove(&outcome.end_to_end_id);
+            self.incomplete |= outcome.evaluation.is_none();
+        }
+    }
+
+    fn accepted(&self) -> usize {
+        self.response
+            .transactions
+            .iter()
+            .filter(|outcome| outcome.accepted)
+            .count()
+    }
+
+    /// [StatusCode::CREATED] when every transaction was accepted,
+    /// [StatusCode::MULTI_STATUS] when only some were. If none were, the first error is returned.
+    /// [StatusCode::ACCEPTED] when every transaction was accepted but some verdicts did not arrive
+    /// in time
+    fn finish(self) -> Result<(StatusCode, SubmissionResponse), AppError> {
+        let status = match (self.accepted(), self.first_error) {
+            (0, Some(e)) => return Err(e),
+            (_, Some(_)) => StatusCode::MULTI_STATUS,
+            (_, None) if self.incomplete => StatusCode::ACCEPTED,
+            (_, None) => StatusCode::CREATED,
+        };
+
+        Ok((status, self.response))
+    }
+}
+
+/// Rejects a submission unless the caller is an agent, by `mmb_id`, in every one of its
+/// transactions
+fn authorize<'a, A>(
+    principal: &Principal,
+    transactions: impl IntoIterator<Item = A>,
+) -> Result<(), AppError>
+where
+    A: IntoIterator<Item = Option<&'a str>>,
+{
+    if principal.is_admin() {
+        return Ok(());
+    }
+
+    for (index, agents) in transactions.into_iter().enumerate() {
+        if !principal.may_act_for(agents) {
+            warn!(subject = %principal.subject, index, "caller is not an agent in the transaction");
+            return Err(AppError::forbidden(format!(
+                "transaction {index} does not involve participant {}",
+                principal.participant.as_deref().unwrap_or_default()
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Takes a token from the rate limit of the caller
+///
+/// Callers holding an API key are limited per key, and those holding a token per participant.
+/// `sender`, the `mmb_id` of the agent the message comes from, is only used when authentication
+/// is disabled: it is chosen by the caller, who could otherwise escape their own limit or spend
+/// another participant's
+async fn throttle(
+    state: &AppHandle,
+    principal: &Principal,
+    sender: Option<&str>,
+) -> Result<(), AppError> {
+    let key = bucket(principal, sender);
+
+    state.rate_limiter.acquire(&key).await.inspect_err(|e| {
+        warn!(subject = %principal.subject, %key, "{e}");
+    })?;
+
+    Ok(())
+}
+
+/// The rate limit bucket a request of `principal` takes its token from
+fn bucket(principal: &Principal, sender: Option<&str>) -> String {
+    let participant = match principal.credential {
+        Credential::Anonymous => sender,
+        Credential::Token => principal.participant.as_deref(),
+        Credential::ApiKey => None,
+    };
+
+    match participant {
+        Some(mmb_id) => format!("participant:{mmb_id}"),
+        None => format!("caller:{}", principal.subject),
+    }
+}
+
+/// Stores the outcome of a submission under its idempotency key and renders it in the shape of
+/// `version`
+async fn respond(
+    state: &AppHandle,
+    key: &str,
+    version: Version,
+    submission: Submission,
+) -> Result<Response, AppError> {
+    let result = submission.finish();
+
+    match version {
+        Version::V0 => idempotency::finish(state, key, result).await,
+        Version::V1 => {
+            let result =
+                result.map(|(status, response)| (status, v1::SubmissionResponse::from(response)));
+            idempotency::finish(state, key, result).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use warden_middleware::auth::{Credential, Principal, Role};
+
+    fn principal(credential: Credential, participant: Option<&str>) -> Principal {
+        Principal {
+            subject: "caller".to_string(),
+            credential,
+            roles: vec![Role::Participant],
+            participant: participant.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn bucket_ignores_sender_when_authenticated() {
+        let token = principal(Credential::Token, Some("fsp001"));
+        assert_eq!(super::bucket(&token, Some("fsp002")), "participant:fsp001");
+
+        let token = principal(Credential::Token, None);
+        assert_eq!(super::bucket(&token, Some("fsp002")), "caller:caller");
 
-use crate::state::AppHandle;
+        let key = principal(Credential::ApiKey, Some("fsp001"));
+        assert_eq!(super::bucket(&key, Some("fsp002")), "caller:caller");
 
-pub fn router(store: AppHandle) -> OpenApiRouter {
-    OpenApiRouter::new()
-        .routes(routes!(pacs008::post_pacs008))
-        .routes(routes!(pacs002::post_pacs002))
-        .with_state(store)
+        let anonymous = principal(Credential::Anonymous, None);
+        assert_eq!(
+            super::bucket(&anonymous, Some("fsp002")),
+            "participant:fsp002"
+        );
+        assert_eq!(super::bucket(&anonymous, None), "caller:caller");
+    }
 }
@@ -0,0 +1,413 @@
+use axum::{
+    Json,
+    body::Bytes,
+    extract::{Query, State},
+    http::{HeaderMap, header},
+};
+use futures_util::{StreamExt, stream};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+use warden_core::iso20022::{
+    TransactionType,
+    pacs002::Pacs002Document,
+    pacs008::Pacs008Document,
+    validate::{Validate, Violation},
+};
+use warden_middleware::{auth::Principal, version::Versioned};
+
+use crate::{
+    error::{AppError, ProblemDetails},
+    server::routes::{
+        BATCH,
+        processor::{Submission, TransactionOutcome, pacs002, pacs008},
+        require_admin,
+    },
+    state::AppHandle,
+    version::Version,
+};
+
+const NDJSON: &str = "application/x-ndjson";
+
+#[derive(Debug, Deserialize)]
+pub(super) struct BatchParams {
+    publish: Option<bool>,
+}
+
+/// Outcome of a single document within a batch
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DocumentOutcome {
+    /// Position of the document in the batch, starting from zero
+    pub index: usize,
+    /// Transaction type of the document, when it could be determined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tx_tp: Option<String>,
+    /// Message identifier from the group header, when it could be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub msg_id: Option<String>,
+    /// Whether every transaction in the document was accepted
+    pub accepted: bool,
+    /// Outcome for each transaction, in the order they appear in the document
+    pub transactions: Vec<TransactionOutcome>,
+    /// Reason the document was rejected before any of its transactions were processed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Every element that failed validation
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<Violation>,
+}
+
+/// Result of submitting a batch
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BatchResponse {
+    /// Whether accepted transactions were queued for evaluation
+    pub published: bool,
+    /// Number of documents whose transactions were all accepted
+    pub accepted: usize,
+    /// Number of documents with at least one transaction that was not accepted
+    pub rejected: usize,
+    /// Outcome for each document, in the order they were received
+    pub documents: Vec<DocumentOutcome>,
+}
+
+enum Document {
+    Pacs008(Pacs008Document),
+    Pacs002(Pacs002Document),
+}
+
+/// Submit a batch of pacs.008.001.12 and pacs.002.001.12 documents
+///
+/// The body is either a JSON array or newline delimited JSON with one document per line. Every
+/// pacs.008 in the batch is stored before any pacs.002, so status reports may refer to transfers
+/// in the same batch. Documents are not deduplicated by idempotency key: resubmitting a
+/// transaction is reported as a conflict in its outcome
+#[utoipa::path(
+    post,
+    responses(
+        (status = OK, body = BatchResponse, description = "the batch was processed, see each document for its outcome"),
+        (status = BAD_REQUEST, body = ProblemDetails, content_type = "application/problem+json", description = "the body is not a JSON array or newline delimited JSON, or holds too many documents"),
+        (status = FORBIDDEN, body = ProblemDetails, content_type = "application/problem+json", description = "the caller is not an admin"),
+    ),
+    operation_id = "post_batch", // https://github.com/juhaku/utoipa/issues/1170
+    path = "/batch",
+    params(
+        ("publish" = Option<bool>, Query, description = "Queue accepted transactions for evaluation. Set to false to only load them into history. Defaults to true")
+    ),
+    tag = BATCH,
+    request_body(
+        description = "The documents as a JSON array or as newline delimited JSON",
+        content(
+            (Vec<Value> = "application/json"),
+            (Value = "application/x-ndjson"),
+        )
+    ))
+]
+#[tracing::instrument(
+    skip(state, headers, body),
+    err(Debug),
+    fields(method = "POST", doc_count)
+)]
+pub(super) async fn post_batch(
+    Versioned(version): Versioned<Version>,
+    State(state): State<AppHandle>,
+    principal: Principal,
+    Query(params): Query<BatchParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<BatchResponse>, AppError> {
+    require_admin(&principal)?;
+
+    let publish = params.publish.unwrap_or(true);
+    let entries = parse(&headers, &body)?;
+    let doc_count = entries.len();
+    tracing::Span::current().record("doc_count", doc_count);
+
+    let config = &state.app_config.batch;
+    if doc_count > config.max_documents {
+        warn!(doc_count, max = config.max_documents, "batch is too large");
+        return Err(AppError::bad_request(format!(
+            "batch holds {doc_count} documents, at most {} are accepted",
+            config.max_documents
+        )));
+    }
+
+    let mut outcomes = Vec::with_capacity(doc_count);
+    let mut transfers = Vec::new();
+    let mut statuses = Vec::new();
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        let mut outcome = DocumentOutcome {
+            index,
+            tx_tp: None,
+            msg_id: None,
+            accepted: false,
+            transactions: Vec::new(),
+            error: None,
+            violations: Vec::new(),
+        };
+
+        match entry.and_then(read_document) {
+            Ok(Document::Pacs008(document)) => {
+                outcome.tx_tp = Some(TransactionType::PACS008.to_string());
+                outcome.msg_id = Some(document.f_i_to_f_i_cstmr_cdt_trf.grp_hdr.msg_id.clone());
+                transfers.push((index, document));
+            }
+            Ok(Document::Pacs002(document)) => {
+                outcome.tx_tp = Some(TransactionType::PACS002.to_string());
+                outcome.msg_id = Some(document.f_i_to_f_i_pmt_sts_rpt.grp_hdr.msg_id.clone());
+                statuses.push((index, document));
+            }
+            Err(e) => {
+                debug!(index, "document rejected: {e}");
+                let problem = e.problem();
+                outcome.error = Some(problem.detail);
+                outcome.violations = problem.violations;
+            }
+        }
+
+        outcomes.push(outcome);
+    }
+
+    debug!(
+        transfers = transfers.len(),
+        statuses = statuses.len(),
+        publish,
+        "processing batch"
+    );
+
+    // status reports look up their transfer, so every transfer has to be stored first
+    let concurrency = config.concurrency.max(1);
+    let tx_tp = TransactionType::PACS008.to_string();
+    let submissions: Vec<_> = stream::iter(transfers)
+        .map(|(index, document)| {
+            let (state, tx_tp) = (&state, &tx_tp);
+            async move {
+                (
+                    index,
+                    pacs008::submit(state, document, tx_tp, publish).await,
+                )
+            }
+        })
+        .buffered(concurrency)
+        .collect()
+        .await;
+    record(&mut outcomes, submissions);
+
+    let tx_tp = TransactionType::PACS002.to_string();
+    let submissions: Vec<_> = stream::iter(statuses)
+        .map(|(index, document)| {
+            let (state, tx_tp) = (&state, &tx_tp);
+            async move {
+                (
+                    index,
+                    pacs002::submit(state, document, tx_tp, publish).await,
+                )
+            }
+        })
+        .buffered(concurrency)
+        .collect()
+        .await;
+    record(&mut outcomes, submissions);
+
+    let accepted = outcomes.iter().filter(|outcome| outcome.accepted).count();
+    info!(doc_count, accepted, publish, "processed batch");
+
+    Ok(Json(BatchResponse {
+        published: publish,
+        accepted,
+        rejected: doc_count - accepted,
+        documents: outcomes,
+    }))
+}
+
+/// Splits the body into documents. A line of NDJSON that is not valid JSON only rejects that
+/// document, whereas a malformed JSON array rejects the whole batch
+fn parse(headers: &HeaderMap, body: &[u8]) -> Result<Vec<Result<Value, AppError>>, AppError> {
+    let is_ndjson = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case(NDJSON));
+
+    if is_ndjson {
+        return Ok(body
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.trim_ascii().is_empty())
+            .map(|line| {
+                serde_json::from_slice(line)
+                    .map_err(|e| AppError::bad_request(format!("invalid json: {e}")))
+            })
+            .collect());
+    }
+
+    let documents: Vec<Value> = serde_json::from_slice(body)
+        .map_err(|e| AppError::bad_request(format!("expected a json array of documents: {e}")))?;
+
+    Ok(documents.into_iter().map(Ok).collect())
+}
+
+/// Recognises the document by its root element and checks it the same way the single document
+/// endpoints do
+fn read_document(value: Value) -> Result<Document, AppError> {
+    if value.get("f_i_to_f_i_cstmr_cdt_trf").is_some() {
+        let document: Pacs008Document = serde_json::from_value(value)
+            .map_err(|e| AppError::bad_request(format!("invalid pacs.008 document: {e}")))?;
+
+        if document.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf.is_empty() {
+            return Err(AppError::bad_request(
+                "cdt_trf_tx_inf must contain at least one entry",
+            ));
+        }
+        document.validate().map_err(AppError::Invalid)?;
+
+        return Ok(Document::Pacs008(document));
+    }
+
+    if value.get("f_i_to_f_i_pmt_sts_rpt").is_some() {
+        let document: Pacs002Document = serde_json::from_value(value)
+            .map_err(|e| AppError::bad_request(format!("invalid pacs.002 document: {e}")))?;
+
+        if document.f_i_to_f_i_pmt_sts_rpt.tx_inf_and_sts.is_empty() {
+            return Err(AppError::bad_request(
+                "tx_inf_and_sts must contain at least one entry",
+            ));
+        }
+        document.validate().map_err(AppError::Invalid)?;
+
+        return Ok(Document::Pacs002(document));
+    }
+
+    Err(AppError::bad_request(
+        "unsupported document, expected a pacs.008 or a pacs.002",
+    ))
+}
+
+fn record(outcomes: &mut [DocumentOutcome], submissions: Vec<(usize, Submission)>) {
+    for (index, submission) in submissions {
+        let outcome = &mut outcomes[index];
+        outcome.accepted = submission.first_error.is_none();
+        outcome.transactions = submission.response.transactions;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode, header},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+    use warden_stack::cache::RedisManager;
+
+    use crate::{
+        server::{self, test_config, test_pacs008},
+        state::{AppState, Services},
+    };
+
+    #[sqlx::test]
+    async fn post_batch(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool.clone(),
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let first = serde_json::to_string(&test_pacs008()).unwrap();
+        let second = serde_json::to_string(&test_pacs008()).unwrap();
+        let body = format!("{first}\n\n{{\"unknown\": {{}}}}\n{second}\nnot json\n");
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header(header::CONTENT_TYPE, "application/x-ndjson")
+                    .uri("/api/v0/batch?publish=false")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["published"], false);
+        assert_eq!(body["accepted"], 2);
+        assert_eq!(body["rejected"], 2);
+
+        let documents = body["documents"].as_array().unwrap();
+        assert_eq!(documents.len(), 4);
+        assert_eq!(documents[0]["accepted"], true);
+        assert_eq!(documents[0]["tx_tp"], "pacs.008.001.12");
+        assert_eq!(documents[1]["accepted"], false);
+        assert!(documents[1]["error"].is_string());
+        assert_eq!(documents[2]["accepted"], true);
+        assert_eq!(documents[3]["index"], 3);
+        assert_eq!(documents[3]["accepted"], false);
+
+        let stored = sqlx::query_scalar!("select count(*) from pacs008")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, Some(2));
+
+        let queued = sqlx::query_scalar!("select count(*) from outbox")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(queued, Some(0));
+    }
+
+    #[sqlx::test]
+    async fn post_batch_not_array(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .uri("/api/v0/batch")
+                    .body(Body::from(serde_json::to_string(&test_pacs008()).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,184 @@
+use std::future::Future;
+
+use serde::Serialize;
+use sqlx::PgConnection;
+use tracing::{Instrument, Span, debug, info, instrument, trace, trace_span};
+use uuid::Uuid;
+use warden_core::{
+    google::r#type::Money,
+    message::{DataCache, Payload, payload::Transaction},
+    pseudonyms::transaction_relationship::{CreatePseudonymRequest, TransactionRelationship},
+};
+use warden_stack::{
+    opentelemetry_semantic_conventions::attribute, redis::AsyncCommands,
+    tracing_opentelemetry::OpenTelemetrySpanExt,
+};
+
+use crate::{error::AppError, outbox, state::AppHandle};
+
+/// A message stored in its own history table, holding a single transaction
+pub(super) trait HistoryDocument: Serialize + Send + Sync + Sized {
+    /// Table the documents are stored in
+    const TABLE: &'static str;
+
+    /// Builds the request recording the parties of the transaction, and the [DataCache] it is
+    /// evaluated with
+    fn pseudonym_request(
+        &self,
+        tx_tp: &str,
+    ) -> Result<(CreatePseudonymRequest, DataCache), AppError>;
+
+    /// Inserts the document into [Self::TABLE] under `id`
+    fn insert(
+        &self,
+        conn: &mut PgConnection,
+        id: Uuid,
+    ) -> impl Future<Output = Result<(), sqlx::Error>> + Send;
+
+    fn into_transaction(self) -> Transaction;
+}
+
+/// Builds the request recording the parties of the transaction described by `data_cache`
+pub(super) fn pseudonym_request(
+    data_cache: &DataCache,
+    msg_id: &str,
+    end_to_end_id: &str,
+    pmt_inf_id: String,
+    tx_tp: &str,
+) -> Result<CreatePseudonymRequest, AppError> {
+    debug!(%msg_id, %end_to_end_id, "extracted transaction identifiers");
+
+    let money = match data_cache.instd_amt.as_ref() {
+        Some(amt) => Some(
+            Money::try_from((amt.value, amt.ccy.as_str()))
+                .map_err(|_e| AppError::unprocessable(format!("invalid currency: {}", amt.ccy)))?,
+        ),
+        None => {
+            trace!(msg_id, "transaction has no instructed amount");
+            None
+        }
+    };
+
+    let transaction_relationship = TransactionRelationship {
+        from: data_cache.dbtr_acct_id.to_string(),
+        to: data_cache.cdtr_acct_id.to_string(),
+        amt: money,
+        cre_dt_tm: data_cache.cre_dt_tm,
+        end_to_end_id: end_to_end_id.to_string(),
+        msg_id: msg_id.to_string(),
+        pmt_inf_id,
+        tx_tp: tx_tp.to_owned(),
+        ..Default::default()
+    };
+
+    Ok(CreatePseudonymRequest {
+        transaction_relationship: Some(transaction_relationship),
+        debtor_id: data_cache.dbtr_id.to_string(),
+        debtor_account_id: data_cache.dbtr_acct_id.to_string(),
+        creditor_id: data_cache.cdtr_id.to_string(),
+        creditor_account_id: data_cache.cdtr_acct_id.to_string(),
+    })
+}
+
+/// Evaluates a document holding a single transaction
+///
+/// The parties are recorded and the [DataCache] cached before the document is added to history.
+/// When `publish` is false, it is not queued for evaluation
+#[instrument(skip(state, document), err(Debug), fields(end_to_end_id))]
+pub(super) async fn process_transaction<D: HistoryDocument>(
+    state: &AppHandle,
+    document: D,
+    tx_tp: &str,
+    publish: bool,
+) -> Result<(), AppError> {
+    let (request, data_cache) = document.pseudonym_request(tx_tp)?;
+    let (msg_id, end_to_end_id) = request
+        .transaction_relationship
+        .as_ref()
+        .map(|value| (value.msg_id.to_string(), value.end_to_end_id.to_string()))
+        .unwrap_or_default();
+    Span::current().record("end_to_end_id", &end_to_end_id);
+
+    debug!(%msg_id, %end_to_end_id, "constructed transaction relationship");
+
+    let mut pseudonyms_client = state.mutate_pseudonym_client.clone();
+
+    trace!("updating pseudonyms");
+
+    let pseudonyms_fut = async {
+        let span = trace_span!(
+            "create.pseudonyms.account",
+            "otel.kind" = "client",
+            "rpc.service" = "pseudonyms"
+        );
+        pseudonyms_client
+            .create_pseudonym(request)
+            .instrument(span)
+            .await
+            .map_err(AppError::from)
+    };
+
+    let (_, _) = tokio::try_join!(
+        pseudonyms_fut,
+        set_cache(&end_to_end_id, state, &data_cache)
+    )?;
+    trace!("pseudonyms saved");
+
+    let id = Uuid::now_v7();
+    debug!(%id, "inserting transaction into history");
+
+    let span = trace_span!("create.transaction_history");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute("otel.kind", "client");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "insert");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, D::TABLE);
+
+    let mut tx = state.services.postgres.begin().await?;
+
+    trace!(id = ?id, "saving transaction history");
+    document.insert(&mut *tx, id).instrument(span).await?;
+
+    if publish {
+        let payload = Payload {
+            tx_tp: tx_tp.to_string(),
+            transaction: Some(document.into_transaction()),
+            data_cache: Some(data_cache),
+            ..Default::default()
+        };
+
+        // published by the relay once committed
+        outbox::enqueue(&mut tx, state, &payload, &msg_id).await?;
+    }
+    tx.commit().await?;
+    if publish {
+        state.outbox.notify_one();
+    }
+    info!(%id, %msg_id, %end_to_end_id, publish, "transaction added to history");
+
+    Ok(())
+}
+
+#[instrument(skip(state), fields(end_to_end_id = end_to_end_id))]
+pub(super) async fn set_cache(
+    end_to_end_id: &str,
+    state: &AppHandle,
+    data_cache: &DataCache,
+) -> Result<(), AppError> {
+    trace!("updating cache");
+    let span = Span::current();
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "valkey");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "set");
+    span.set_attribute(attribute::DB_OPERATION_PARAMETER, end_to_end_id.to_string());
+    let mut cache_update = state
+        .services
+        .cache
+        .get()
+        .await
+        .map_err(AppError::cache_unavailable)?;
+    let bytes = prost::Message::encode_to_vec(data_cache);
+    cache_update
+        .set_ex::<_, _, ()>(&end_to_end_id, bytes, state.app_config.cache_ttl)
+        .await?;
+
+    Ok(())
+}
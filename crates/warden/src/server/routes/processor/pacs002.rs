@@ -1,80 +1,255 @@
-use axum::{extract::State, response::IntoResponse};
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::Response,
+};
 use opentelemetry_semantic_conventions::attribute;
 use prost::Message as _;
-use serde::Serialize;
-use tracing::{Instrument, debug, error, info, info_span, trace};
+use serde::{Deserialize, Serialize};
+use tracing::{Instrument, debug, error, info, info_span, trace, warn};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 use uuid::Uuid;
 use warden_core::{
     google::r#type::Money,
-    iso20022::{TransactionType, pacs002::Pacs002Document},
+    iso20022::{
+        TransactionType, pacs002::Pacs002Document, pacs008::Pacs008Document, validate::Validate,
+    },
     message::{DataCache, Payload},
     pseudonyms::transaction_relationship::{CreatePseudonymRequest, TransactionRelationship},
 };
+use warden_middleware::auth::Principal;
 use warden_stack::redis::AsyncCommands;
 
 use crate::{
-    error::AppError,
+    error::{AppError, ProblemDetails},
+    outbox,
     server::{
-        publish::publish_message,
+        document::IsoDocument,
+        evaluation::Evaluation,
+        idempotency,
         routes::{
             PACS002_001_12,
-            processor::pacs008::{build_data_cache, set_cache},
+            processor::{
+                Submission, SubmissionResponse, authorize, history::set_cache,
+                pacs008::build_data_cache, respond, throttle, v1,
+            },
         },
     },
     state::AppHandle,
     version::Version,
 };
 
+#[derive(Debug, Deserialize)]
+pub struct StatusParams {
+    wait: Option<bool>,
+}
+
 #[derive(Serialize)]
 struct Row {
     id: Uuid,
     document: sqlx::types::Json<serde_json::Value>,
 }
 
-/// Submit a pacs.002.001.12 transaction
-#[utoipa::path(
-    post,
-    responses((
-        status = CREATED,
-        body = Pacs002Document
-    )),
-    operation_id = "post_pacs_002", // https://github.com/juhaku/utoipa/issues/1170
-    path = "/{version}/pacs002",
+submission_handlers! {
+    /// Submit a pacs.002.001.12 transaction
+    ///
+    /// Each status entry in `tx_inf_and_sts` is evaluated independently. With `wait`, the response
+    /// is held until every accepted entry has been evaluated or the configured timeout elapses
+    handlers = (post_pacs002, post_pacs002_v1),
+    responses(
+        (CREATED, "all status entries were accepted, and evaluated if `wait` was set"),
+        (ACCEPTED, "all status entries were accepted, but some were not evaluated before the timeout"),
+        (MULTI_STATUS, "some status entries were not accepted"),
+    ),
+    problems(
+        (FORBIDDEN, "the caller is not an agent in every original transfer"),
+        (CONFLICT, "the idempotency key was used with a different payload, or the status report was already submitted"),
+        (UNPROCESSABLE_ENTITY, "the document failed validation, or the original pacs.008 is unknown"),
+    ),
+    operation_id = "post_pacs_002",
+    path = "/pacs002",
     params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3")
+        ("Idempotency-Key" = Option<String>, Header, description = "Deduplicates retries of the same submission. Defaults to the message and end to end ids"),
+        ("wait" = Option<bool>, Query, description = "Wait for the evaluation of each status entry and return the verdicts. Defaults to false")
     ),
     tag = PACS002_001_12,
-    request_body(
-        content = Pacs002Document
-    ))
-]
-#[tracing::instrument(
+    document = Pacs002Document,
     skip(state, request),
-    err(Debug),
-    fields(method = "POST", end_to_end_id, msg_id, tx_tp)
-)]
-pub async fn post_pacs002(
-    State(state): State<AppHandle>,
-    axum::Json(request): axum::Json<Pacs002Document>,
-) -> Result<impl IntoResponse, AppError> {
+    async fn(
+        State(state): State<AppHandle>,
+        principal: Principal,
+        Query(params): Query<StatusParams>,
+        headers: HeaderMap,
+        IsoDocument(request): IsoDocument<Pacs002Document>,
+    ) {
+        |version| accept(&state, version, &principal, &headers, params, request).await
+    }
+}
+
+/// Validates, deduplicates and submits every status entry of the document, then renders the
+/// outcome in the shape of `version`
+async fn accept(
+    state: &AppHandle,
+    version: Version,
+    principal: &Principal,
+    headers: &HeaderMap,
+    params: StatusParams,
+    request: Pacs002Document,
+) -> Result<Response, AppError> {
     let tx_tp = TransactionType::PACS002.to_string();
     tracing::Span::current().record("tx_tp", &tx_tp);
 
+    let msg_id = request.f_i_to_f_i_pmt_sts_rpt.grp_hdr.msg_id.to_string();
+    tracing::Span::current().record("msg_id", &msg_id);
+
+    if request.f_i_to_f_i_pmt_sts_rpt.tx_inf_and_sts.is_empty() {
+        warn!(%msg_id, "payment status report has no transaction statuses");
+        return Err(AppError::bad_request(
+            "tx_inf_and_sts must contain at least one entry",
+        ));
+    }
+
+    request.validate().map_err(|violations| {
+        warn!(%msg_id, count = violations.len(), "document failed validation");
+        AppError::Invalid(violations)
+    })?;
+
+    // the agents named in the report are chosen by the sender, so the caller must be an agent of
+    // each original transfer instead
+    if !principal.is_admin() {
+        let mut transfers = Vec::with_capacity(request.f_i_to_f_i_pmt_sts_rpt.tx_inf_and_sts.len());
+        for value in &request.f_i_to_f_i_pmt_sts_rpt.tx_inf_and_sts {
+            let end_to_end_id = value.orgnl_end_to_end_id.as_deref().ok_or_else(|| {
+                AppError::bad_request("orgnl_end_to_end_id is required for every status entry")
+            })?;
+            transfers.push(original_agents(state, end_to_end_id).await?);
+        }
+        authorize(
+            principal,
+            transfers
+                .iter()
+                .map(|agents| agents.each_ref().map(Option::as_deref)),
+        )?;
+    }
+
+    let grp_hdr = &request.f_i_to_f_i_pmt_sts_rpt.grp_hdr;
+    let group_agents = [grp_hdr.instg_agt.as_ref(), grp_hdr.instd_agt.as_ref()].map(|agt| {
+        agt.and_then(|agt| agt.fin_instn_id.clr_sys_mmb_id.as_ref())
+            .map(|value| value.mmb_id.as_str())
+    });
+
+    // status reports are sent by the instructing agent
+    let [instg_agt, _] = group_agents;
+    throttle(state, principal, instg_agt).await?;
+
+    let key = idempotency::key(
+        headers,
+        principal,
+        &tx_tp,
+        &msg_id,
+        request
+            .f_i_to_f_i_pmt_sts_rpt
+            .tx_inf_and_sts
+            .iter()
+            .map(|value| value.orgnl_end_to_end_id.as_deref().unwrap_or_default()),
+    )?;
+    // a retry must wait for the verdicts if the original did, as the response carries them
+    let body = serde_json::json!({
+        "document": serde_json::to_value(&request).map_err(anyhow::Error::new)?,
+        "wait": params.wait.unwrap_or_default(),
+    });
+
+    // subscribed before anything is published so that no verdict is missed
+    let evaluation = match params.wait {
+        Some(true) => Some(Evaluation::subscribe(state, &msg_id).await?),
+        _ => None,
+    };
+
+    if let Some(response) = idempotency::claim(state, &key, &body).await? {
+        info!(%msg_id, "replaying previous submission");
+        return Ok(response);
+    }
+
+    let tx_count = request.f_i_to_f_i_pmt_sts_rpt.tx_inf_and_sts.len();
+    tracing::Span::current().record("tx_count", tx_count);
+
+    let mut submission = submit(state, request, &tx_tp, true).await;
+
+    if let Some(evaluation) = evaluation {
+        debug!(%msg_id, "waiting for evaluation results");
+        let results = evaluation.wait(submission.accepted_ids()).await;
+        submission.evaluated(results);
+    }
+
+    let accepted = submission.accepted();
+    info!(%msg_id, tx_count, accepted, "processed transaction statuses");
+
+    respond(state, &key, version, submission).await
+}
+
+/// Stores every status entry of a pacs.002 message as its own document
+///
+/// When `publish` is false, the statuses are added to history without being queued for
+/// evaluation
+pub(super) async fn submit(
+    state: &AppHandle,
+    mut request: Pacs002Document,
+    tx_tp: &str,
+    publish: bool,
+) -> Submission {
+    let entries = std::mem::take(&mut request.f_i_to_f_i_pmt_sts_rpt.tx_inf_and_sts);
+    let mut submission = Submission::new(
+        request.f_i_to_f_i_pmt_sts_rpt.grp_hdr.msg_id.as_str(),
+        entries.len(),
+    );
+
+    for entry in entries {
+        let end_to_end_id = entry.orgnl_end_to_end_id.clone().unwrap_or_default();
+
+        // every status entry is stored and routed as its own document
+        let mut document = request.clone();
+        document.f_i_to_f_i_pmt_sts_rpt.tx_inf_and_sts = vec![entry];
+
+        let result = process_status(state, document, tx_tp, publish).await;
+        submission.record(end_to_end_id, result);
+    }
+
+    submission
+}
+
+/// Evaluates a pacs.002 document holding a single status entry
+#[tracing::instrument(skip(state, request), err(Debug), fields(end_to_end_id))]
+async fn process_status(
+    state: &AppHandle,
+    request: Pacs002Document,
+    tx_tp: &str,
+    publish: bool,
+) -> Result<(), AppError> {
     let cre_dt_tm = request.f_i_to_f_i_pmt_sts_rpt.grp_hdr.cre_dt_tm;
-    let end_to_end_id = request.f_i_to_f_i_pmt_sts_rpt.tx_inf_and_sts[0]
+    let tx_inf_and_sts = request
+        .f_i_to_f_i_pmt_sts_rpt
+        .tx_inf_and_sts
+        .first()
+        .ok_or_else(|| AppError::bad_request("missing tx_inf_and_sts"))?;
+
+    let end_to_end_id = tx_inf_and_sts
         .orgnl_end_to_end_id
-        .as_ref()
-        .ok_or_else(|| anyhow::anyhow!("end_to_end_id is expected"))?;
-    tracing::Span::current().record("end_to_end_id", end_to_end_id);
+        .clone()
+        .ok_or_else(|| AppError::bad_request("missing orgnl_end_to_end_id"))?;
+    tracing::Span::current().record("end_to_end_id", &end_to_end_id);
+    let end_to_end_id = end_to_end_id.as_str();
 
     let msg_id = &request.f_i_to_f_i_pmt_sts_rpt.grp_hdr.msg_id;
-    tracing::Span::current().record("msg_id", msg_id);
 
-    let pmt_inf_id = &request.f_i_to_f_i_pmt_sts_rpt.tx_inf_and_sts[0].orgnl_instr_id;
-    let tx_sts = &request.f_i_to_f_i_pmt_sts_rpt.tx_inf_and_sts[0].tx_sts;
+    let pmt_inf_id = &tx_inf_and_sts.orgnl_instr_id;
+    let tx_sts = &tx_inf_and_sts.tx_sts;
 
-    let mut cache = state.services.cache.get().await?;
+    let mut cache = state
+        .services
+        .cache
+        .get()
+        .await
+        .map_err(AppError::cache_unavailable)?;
     trace!(end_to_end_id = end_to_end_id, "getting data cache");
     let cache = cache
         .get::<_, Vec<u8>>(&end_to_end_id)
@@ -84,11 +259,11 @@ pub async fn post_pacs002(
     let data_cache = match cache {
         Ok(Ok(data_cache)) => {
             debug!(end_to_end_id = end_to_end_id, "cache hit");
-            rebuild_entities(end_to_end_id, &state, Some(data_cache)).await?
+            rebuild_entities(end_to_end_id, state, Some(data_cache)).await?
         }
         _ => {
             debug!(end_to_end_id = end_to_end_id, "cache miss");
-            rebuild_entities(end_to_end_id, &state, None).await?
+            rebuild_entities(end_to_end_id, state, None).await?
         }
     };
 
@@ -102,7 +277,10 @@ pub async fn post_pacs002(
     debug!(%msg_id, %end_to_end_id, "parsed transaction identifiers");
 
     let money = if let (Some(amt), Some(ccy)) = (amount, ccy) {
-        Some(Money::try_from((amt, ccy)).map_err(|_e| anyhow::anyhow!("invalid currency"))?)
+        Some(
+            Money::try_from((amt, ccy))
+                .map_err(|_e| AppError::unprocessable(format!("invalid currency: {ccy}")))?,
+        )
     } else {
         trace!(msg_id, "transaction has no amount or currency");
         None
@@ -119,7 +297,7 @@ pub async fn post_pacs002(
             .as_ref()
             .ok_or_else(|| {
                 error!("missing pmt_inf_id");
-                anyhow::anyhow!("missing pmt_inf_id")
+                AppError::bad_request("missing orgnl_instr_id")
             })?
             .to_string(),
         tx_tp: tx_tp.to_string(),
@@ -142,59 +320,90 @@ pub async fn post_pacs002(
 
     let mut pseudonyms_client = state.mutate_pseudonym_client.clone();
 
-    let pseudonyms_fut = async {
-        debug!("creating pseudonyms");
-        let span = info_span!("create.pseudonyms.account");
-        span.set_attribute("otel.kind", "client");
-        span.set_attribute(attribute::RPC_SERVICE, "pseudonyms");
-        pseudonyms_client
-            .create_pseudonym(pseudonyms_request)
-            .instrument(span)
-            .await
-            .map_err(|e| {
-                error!(error = %e, "failed to create pseudonyms");
-                anyhow::anyhow!("could not create pseudonyms")
-            })
-    };
+    debug!("creating pseudonyms");
+    let span = info_span!("create.pseudonyms.account");
+    span.set_attribute("otel.kind", "client");
+    span.set_attribute(attribute::RPC_SERVICE, "pseudonyms");
+    pseudonyms_client
+        .create_pseudonym(pseudonyms_request)
+        .instrument(span)
+        .await?;
 
     let id = Uuid::now_v7();
 
-    let tr_fut = async {
-        let span = info_span!("create.transaction_history.pacs002");
-        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
-        span.set_attribute(attribute::DB_OPERATION_NAME, "insert");
-        span.set_attribute(attribute::DB_COLLECTION_NAME, "pacs002");
+    let span = info_span!("create.transaction_history.pacs002");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "insert");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "pacs002");
 
-        sqlx::query!(
-            "insert into pacs002 (id, document) values ($1, $2)",
-            id,
-            sqlx::types::Json(&request) as _
-        )
-        .execute(&state.services.postgres)
-        .instrument(span)
-        .await
-        .map_err(|e| {
-            error!("{e}");
-            anyhow::anyhow!("could not insert transaction_history")
-        })
-    };
-    let (_result, _resp) = tokio::try_join!(tr_fut, pseudonyms_fut)?;
-    debug!(%id, %msg_id, %tx_tp, "transaction added to history");
+    let mut tx = state.services.postgres.begin().await?;
 
-    trace!(%msg_id, "publishing payload to ");
+    sqlx::query!(
+        "insert into pacs002 (id, document) values ($1, $2)",
+        id,
+        sqlx::types::Json(&request) as _
+    )
+    .execute(&mut *tx)
+    .instrument(span)
+    .await?;
 
-    let payload = Payload {
-        tx_tp: tx_tp.to_string(),
-        data_cache: Some(data_cache),
-        transaction: Some(warden_core::message::payload::Transaction::Pacs002(
-            request.clone(),
-        )),
-        ..Default::default()
-    };
+    let msg_id = msg_id.to_string();
+    if publish {
+        let payload = Payload {
+            tx_tp: tx_tp.to_string(),
+            data_cache: Some(data_cache),
+            transaction: Some(warden_core::message::payload::Transaction::Pacs002(request)),
+            ..Default::default()
+        };
+
+        // published by the relay once committed
+        outbox::enqueue(&mut tx, state, &payload, &msg_id).await?;
+    }
+    tx.commit().await?;
+    if publish {
+        state.outbox.notify_one();
+    }
+    info!(%id, %msg_id, %end_to_end_id, publish, "transaction added to history");
+
+    Ok(())
+}
 
-    publish_message(&state, payload, msg_id).await?;
-    info!(%msg_id, "published transaction to router");
-    Ok((axum::http::StatusCode::CREATED, axum::Json(request)))
+/// The debtor and creditor agents of the stored pacs.008 with `end_to_end_id`
+///
+/// Both are `None` when no pacs.008 is stored, so that an unknown transfer is refused in the same
+/// way as one the caller is not an agent in
+#[tracing::instrument(skip(state))]
+async fn original_agents(
+    state: &AppHandle,
+    end_to_end_id: &str,
+) -> Result<[Option<String>; 2], AppError> {
+    let span = info_span!("get.transaction_history.pacs008");
+    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+    span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+    span.set_attribute(attribute::DB_COLLECTION_NAME, "pacs008");
+    span.set_attribute(attribute::DB_OPERATION_PARAMETER, end_to_end_id.to_string());
+
+    let agents = sqlx::query!(
+        r#"select
+            elem->'dbtr_agt'->'fin_instn_id'->'clr_sys_mmb_id'->>'mmb_id' as "dbtr_agt?",
+            elem->'cdtr_agt'->'fin_instn_id'->'clr_sys_mmb_id'->>'mmb_id' as "cdtr_agt?"
+        from pacs008,
+            jsonb_array_elements(document->'f_i_to_f_i_cstmr_cdt_trf'->'cdt_trf_tx_inf') as elem
+        where elem->'pmt_id'->>'end_to_end_id' = $1
+        limit 1"#,
+        end_to_end_id
+    )
+    .fetch_optional(&state.services.postgres)
+    .instrument(span)
+    .await?;
+
+    Ok(match agents {
+        Some(agents) => [agents.dbtr_agt, agents.cdtr_agt],
+        None => {
+            debug!(end_to_end_id, "no pacs.008 stored for status entry");
+            [None, None]
+        }
+    })
 }
 
 #[tracing::instrument(skip(state, data_cache))]
@@ -202,7 +411,7 @@ async fn rebuild_entities(
     end_to_end_id: &str,
     state: &AppHandle,
     mut data_cache: Option<DataCache>,
-) -> anyhow::Result<DataCache> {
+) -> Result<DataCache, AppError> {
     let span = info_span!("get.transaction_history.pacs008");
     span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
     span.set_attribute(attribute::DB_OPERATION_NAME, "select");
@@ -219,22 +428,428 @@ async fn rebuild_entities(
         ) limit 1"#,
         end_to_end_id
     )
-    .fetch_one(&state.services.postgres)
+    .fetch_optional(&state.services.postgres)
     .instrument(span)
-    .await?;
+    .await?
+    .ok_or_else(|| {
+        AppError::unprocessable(format!("no pacs.008 found for end_to_end_id {end_to_end_id}"))
+    })?;
 
     debug!(id = ?transaction.id, "found transaction");
 
-    let document = serde_json::from_value(transaction.document.0)?;
+    let document: Pacs008Document =
+        serde_json::from_value(transaction.document.0).map_err(anyhow::Error::new)?;
 
     if data_cache.is_none() {
         debug!(e2e_id = end_to_end_id, "attempting to rebuild data cache");
-        let data_cache_value = build_data_cache(&document)?;
+        let cdt_trf_tx_inf = document
+            .f_i_to_f_i_cstmr_cdt_trf
+            .cdt_trf_tx_inf
+            .iter()
+            .find(|value| value.pmt_id.end_to_end_id == end_to_end_id)
+            .ok_or_else(|| {
+                AppError::unprocessable(format!(
+                    "no pacs.008 found for end_to_end_id {end_to_end_id}"
+                ))
+            })?;
+        let data_cache_value = build_data_cache(&document, cdt_trf_tx_inf)?;
 
         set_cache(end_to_end_id, state, &data_cache_value).await?;
 
         let _old_value = data_cache.replace(data_cache_value);
     };
 
-    data_cache.ok_or_else(|| anyhow::anyhow!("no pacs008 found"))
+    data_cache.ok_or_else(|| {
+        AppError::unprocessable(format!(
+            "no pacs.008 found for end_to_end_id {end_to_end_id}"
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use sqlx::PgPool;
+    use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+    use tower::ServiceExt;
+    use warden_core::{
+        iso20022::pacs002::Pacs002Document,
+        message::{AggregationResult, Payload, payload::Transaction},
+    };
+    use warden_stack::cache::RedisManager;
+
+    use crate::{
+        server::{self, generate_id, test_config, test_pacs008},
+        state::{AppState, Services},
+    };
+
+    #[sqlx::test]
+    async fn post_empty(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let v = serde_json::json!({
+          "f_i_to_f_i_pmt_sts_rpt": {
+            "grp_hdr": {
+              "msg_id": generate_id(),
+              "cre_dt_tm": OffsetDateTime::now_utc().format(&Rfc3339).unwrap()
+            },
+            "orgnl_grp_inf_and_sts": [],
+            "splmtry_data": [],
+            "tx_inf_and_sts": []
+          }
+        });
+        let body = serde_json::to_vec(&v).unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/pacs002")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test]
+    async fn post_wait(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client.clone());
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let transaction = test_pacs008();
+        let cdt_trf_tx_inf = &transaction.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0];
+        let end_to_end_id = cdt_trf_tx_inf.pmt_id.end_to_end_id.clone();
+        let instr_id = cdt_trf_tx_inf.pmt_id.instr_id.clone();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/pacs008")
+                    .body(Body::from(serde_json::to_vec(&transaction).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let msg_id = generate_id();
+        let v = serde_json::json!({
+          "f_i_to_f_i_pmt_sts_rpt": {
+            "grp_hdr": {
+              "msg_id": msg_id,
+              "cre_dt_tm": OffsetDateTime::now_utc().format(&Rfc3339).unwrap()
+            },
+            "orgnl_grp_inf_and_sts": [],
+            "splmtry_data": [],
+            "tx_inf_and_sts": [
+              {
+                "orgnl_instr_id": instr_id,
+                "orgnl_end_to_end_id": end_to_end_id,
+                "tx_sts": "ACCC",
+                "sts_rsn_inf": [],
+                "splmtry_data": [],
+                "chrgs_inf": []
+              }
+            ]
+          }
+        });
+        let document: Pacs002Document = serde_json::from_value(v.clone()).unwrap();
+
+        // stands in for the aggregator until the handler has heard it
+        let payload = Payload {
+            transaction: Some(Transaction::Pacs002(document)),
+            aggregation_result: Some(AggregationResult {
+                id: "901@1.0.0".to_string(),
+                version: "1.0.0".to_string(),
+                review: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let subject = format!(
+            "{}.{msg_id}",
+            config.misc["evaluation"]["subject"].as_str().unwrap()
+        );
+        let publisher = tokio::spawn(async move {
+            loop {
+                client
+                    .publish(
+                        subject.clone(),
+                        prost::Message::encode_to_vec(&payload).into(),
+                    )
+                    .await
+                    .unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        });
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/pacs002?wait=true")
+                    .body(Body::from(serde_json::to_vec(&v).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        publisher.abort();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["transactions"][0]["end_to_end_id"], end_to_end_id);
+        assert_eq!(body["transactions"][0]["evaluation"]["review"], true);
+    }
+
+    #[sqlx::test]
+    async fn post_wait_is_part_of_the_request(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &config,
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let transaction = test_pacs008();
+        let cdt_trf_tx_inf = &transaction.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0];
+        let end_to_end_id = cdt_trf_tx_inf.pmt_id.end_to_end_id.clone();
+        let instr_id = cdt_trf_tx_inf.pmt_id.instr_id.clone();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/pacs008")
+                    .body(Body::from(serde_json::to_vec(&transaction).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let v = serde_json::json!({
+          "f_i_to_f_i_pmt_sts_rpt": {
+            "grp_hdr": {
+              "msg_id": generate_id(),
+              "cre_dt_tm": OffsetDateTime::now_utc().format(&Rfc3339).unwrap()
+            },
+            "orgnl_grp_inf_and_sts": [],
+            "splmtry_data": [],
+            "tx_inf_and_sts": [
+              {
+                "orgnl_instr_id": instr_id,
+                "orgnl_end_to_end_id": end_to_end_id,
+                "tx_sts": "ACCC",
+                "sts_rsn_inf": [],
+                "splmtry_data": [],
+                "chrgs_inf": []
+              }
+            ]
+          }
+        });
+
+        let mut statuses = Vec::new();
+        for uri in ["/api/v0/pacs002", "/api/v0/pacs002?wait=true"] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .header("Content-Type", "application/json")
+                        .header("Idempotency-Key", "wait")
+                        .uri(uri)
+                        .body(Body::from(serde_json::to_vec(&v).unwrap()))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            statuses.push(response.status());
+        }
+
+        // the retry would otherwise be answered without the verdicts it waits for
+        assert_eq!(statuses, [StatusCode::CREATED, StatusCode::CONFLICT]);
+    }
+
+    #[sqlx::test]
+    async fn post_for_other_participants(pool: PgPool) {
+        let mut config = test_config();
+        config.misc["auth"] = serde_json::json!({
+            "enabled": true,
+            "api-keys": [
+                {
+                    "name": "fsp001",
+                    // sha-256 of "fsp001-key"
+                    "sha256": "e7c43e83f38cabfcdff025b4b132b1122f82cbfc285f577de0fa507fabfc240a",
+                    "role": "participant",
+                    "participant": "fsp001",
+                },
+                {
+                    "name": "fsp999",
+                    // sha-256 of "fsp999-key"
+                    "sha256": "0680b98713cec15b273dfcd89ea4a8f0044be5ec1d788f7751c9dec12d5007eb",
+                    "role": "participant",
+                    "participant": "fsp999",
+                },
+            ],
+        });
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &config,
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        // a transfer from fsp001 to fsp002
+        let transaction = test_pacs008();
+        let cdt_trf_tx_inf = &transaction.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0];
+        let end_to_end_id = cdt_trf_tx_inf.pmt_id.end_to_end_id.clone();
+        let instr_id = cdt_trf_tx_inf.pmt_id.instr_id.clone();
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .header("X-API-Key", "fsp001-key")
+                    .uri("/api/v1/pacs008")
+                    .body(Body::from(serde_json::to_vec(&transaction).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let agent = |mmb_id: &str| {
+            serde_json::json!({
+                "fin_instn_id": { "clr_sys_mmb_id": { "mmb_id": mmb_id } }
+            })
+        };
+        let post = |key: &str, end_to_end_id: &str| {
+            // the report names the caller as both agents
+            let participant = key.trim_end_matches("-key");
+            let v = serde_json::json!({
+              "f_i_to_f_i_pmt_sts_rpt": {
+                "grp_hdr": {
+                  "msg_id": generate_id(),
+                  "cre_dt_tm": OffsetDateTime::now_utc().format(&Rfc3339).unwrap(),
+                  "instg_agt": agent(participant),
+                  "instd_agt": agent(participant)
+                },
+                "orgnl_grp_inf_and_sts": [],
+                "splmtry_data": [],
+                "tx_inf_and_sts": [
+                  {
+                    "orgnl_instr_id": instr_id,
+                    "orgnl_end_to_end_id": end_to_end_id,
+                    "tx_sts": "ACCC",
+                    "instg_agt": agent(participant),
+                    "instd_agt": agent(participant),
+                    "sts_rsn_inf": [],
+                    "splmtry_data": [],
+                    "chrgs_inf": []
+                  }
+                ]
+              }
+            });
+            Request::builder()
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .header("X-API-Key", key)
+                .uri("/api/v1/pacs002")
+                .body(Body::from(serde_json::to_vec(&v).unwrap()))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(post("fsp999-key", &end_to_end_id))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // unknown transfers are refused like those of other participants
+        let response = app
+            .clone()
+            .oneshot(post("fsp001-key", &generate_id()))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app
+            .oneshot(post("fsp001-key", &end_to_end_id))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
 }
@@ -1,205 +1,250 @@
-use axum::{extract::State, response::IntoResponse};
-use tracing::{Instrument, Span, debug, error, info, instrument, trace, trace_span, warn};
+use axum::{extract::State, http::HeaderMap, response::Response};
+use sqlx::PgConnection;
+use tracing::{debug, error, info, trace, warn};
 use uuid::Uuid;
 use warden_core::{
-    google::r#type::Money,
-    iso20022::{TransactionType, pacs008::Pacs008Document},
-    message::DataCache,
-    pseudonyms::transaction_relationship::{CreatePseudonymRequest, TransactionRelationship},
-};
-use warden_stack::{
-    opentelemetry_semantic_conventions::attribute, redis::AsyncCommands,
-    tracing_opentelemetry::OpenTelemetrySpanExt,
+    iso20022::{
+        TransactionType,
+        pacs008::{CreditTransferTransaction64, Pacs008Document},
+        validate::Validate,
+    },
+    message::{DataCache, payload::Transaction},
+    pseudonyms::transaction_relationship::CreatePseudonymRequest,
 };
+use warden_middleware::auth::Principal;
 
 use crate::{
-    error::AppError,
-    server::{publish::publish_message, routes::PACS008_001_12},
+    error::{AppError, ProblemDetails},
+    server::{
+        document::IsoDocument,
+        idempotency,
+        routes::{
+            PACS008_001_12,
+            processor::{
+                Submission, SubmissionResponse, authorize,
+                history::{self, HistoryDocument},
+                respond, throttle, v1,
+            },
+        },
+    },
     state::AppHandle,
     version::Version,
 };
 
-/// Submit a pacs.008.001.12 transaction
-#[utoipa::path(
-    post,
-    responses((
-        status = CREATED,
-        body = Pacs008Document
-    )),
-    operation_id = "post_pacs_008", // https://github.com/juhaku/utoipa/issues/1170
-    path = "/{version}/pacs008",
+submission_handlers! {
+    /// Submit a pacs.008.001.12 transaction
+    ///
+    /// Each credit transfer in `cdt_trf_tx_inf` is evaluated independently
+    handlers = (post_pacs008, post_pacs008_v1),
+    responses(
+        (CREATED, "all credit transfers were accepted"),
+        (MULTI_STATUS, "some credit transfers were not accepted"),
+    ),
+    problems(
+        (FORBIDDEN, "the caller is not an agent in every transaction"),
+        (CONFLICT, "the idempotency key was used with a different payload, or the transaction was already submitted"),
+        (UNPROCESSABLE_ENTITY, "the document failed validation, or its content cannot be processed"),
+    ),
+    operation_id = "post_pacs_008",
+    path = "/pacs008",
     params(
-        ("version" = Version, Path, description = "API version, e.g., v1, v2, v3")
+        ("Idempotency-Key" = Option<String>, Header, description = "Deduplicates retries of the same submission. Defaults to the message and end to end ids")
     ),
     tag = PACS008_001_12,
-    request_body(
-        content = Pacs008Document
-    ))
-]
-#[axum::debug_handler]
-#[tracing::instrument(
+    document = Pacs008Document,
     skip(state, transaction),
-    err(Debug),
-    fields(method = "POST", end_to_end_id, msg_id, tx_tp)
-)]
-pub(super) async fn post_pacs008(
+    async fn(
+        State(state): State<AppHandle>,
+        principal: Principal,
+        headers: HeaderMap,
+        IsoDocument(transaction): IsoDocument<Pacs008Document>,
+    ) {
+        |version| accept(&state, version, &principal, &headers, transaction).await
+    }
+}
+
+/// Validates, deduplicates and submits every transaction of the document, then renders the
+/// outcome in the shape of `version`
+async fn accept(
+    state: &AppHandle,
     version: Version,
-    State(state): State<AppHandle>,
-    axum::Json(transaction): axum::Json<Pacs008Document>,
-) -> Result<impl IntoResponse, AppError> {
+    principal: &Principal,
+    headers: &HeaderMap,
+    transaction: Pacs008Document,
+) -> Result<Response, AppError> {
     let tx_tp = TransactionType::PACS008.to_string();
     tracing::Span::current().record("tx_tp", &tx_tp);
-    let data_cache = build_data_cache(&transaction)?;
 
-    let tx_count = transaction.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf.len();
-    let msg_id = &transaction.f_i_to_f_i_cstmr_cdt_trf.grp_hdr.msg_id;
-
-    if transaction.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf.len() > 1 {
-        warn!(
-            msg_id,
-            tx_count,
-            "found more than 1 transaction for this message. Only the first will be evaluated"
-        );
-    }
+    let msg_id = transaction
+        .f_i_to_f_i_cstmr_cdt_trf
+        .grp_hdr
+        .msg_id
+        .to_string();
+    tracing::Span::current().record("msg_id", &msg_id);
 
-    // take the first - guaranteed by utoipa
-    trace!("extracting first credit transfer transaction info");
-    let cdt_trf_tx_inf = transaction
+    if transaction
         .f_i_to_f_i_cstmr_cdt_trf
         .cdt_trf_tx_inf
-        .first()
-        .expect("required cdt_trf_tx_inf missing");
+        .is_empty()
+    {
+        error!(%msg_id, "message has no credit transfer transactions");
+        return Err(AppError::bad_request(
+            "cdt_trf_tx_inf must contain at least one entry",
+        ));
+    }
 
-    let amount = cdt_trf_tx_inf.instd_amt.as_ref().map(|value| value.value);
+    transaction.validate().map_err(|violations| {
+        warn!(%msg_id, count = violations.len(), "document failed validation");
+        AppError::Invalid(violations)
+    })?;
+
+    authorize(
+        principal,
+        transaction
+            .f_i_to_f_i_cstmr_cdt_trf
+            .cdt_trf_tx_inf
+            .iter()
+            .map(|value| {
+                [value.dbtr_agt.as_ref(), value.cdtr_agt.as_ref()].map(|agt| {
+                    agt.and_then(|agt| agt.fin_instn_id.clr_sys_mmb_id.as_ref())
+                        .map(|value| value.mmb_id.as_str())
+                })
+            }),
+    )?;
 
-    let ccy = cdt_trf_tx_inf
-        .instd_amt
+    let debtor_agent = transaction.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0]
+        .dbtr_agt
         .as_ref()
-        .map(|value| value.ccy.as_str());
+        .and_then(|agt| agt.fin_instn_id.clr_sys_mmb_id.as_ref())
+        .map(|value| value.mmb_id.as_str());
+    throttle(state, principal, debtor_agent).await?;
+
+    let key = idempotency::key(
+        headers,
+        principal,
+        &tx_tp,
+        &msg_id,
+        transaction
+            .f_i_to_f_i_cstmr_cdt_trf
+            .cdt_trf_tx_inf
+            .iter()
+            .map(|value| value.pmt_id.end_to_end_id.as_str()),
+    )?;
+    let body = serde_json::to_value(&transaction).map_err(anyhow::Error::new)?;
 
-    let end_to_end_id = cdt_trf_tx_inf.pmt_id.end_to_end_id.as_str();
+    if let Some(response) = idempotency::claim(state, &key, &body).await? {
+        info!(%msg_id, "replaying previous submission");
+        return Ok(response);
+    }
 
-    tracing::Span::current().record("end_to_end_id", end_to_end_id);
-    let end_to_end_id = String::from(end_to_end_id);
+    let tx_count = transaction.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf.len();
+    tracing::Span::current().record("tx_count", tx_count);
 
-    let msg_id = &transaction.f_i_to_f_i_cstmr_cdt_trf.grp_hdr.msg_id;
-    tracing::Span::current().record("msg_id", msg_id);
+    debug!(%msg_id, tx_count, "evaluating credit transfer transactions");
 
-    let pmt_inf_id = cdt_trf_tx_inf.pmt_id.instr_id.as_ref().ok_or_else(|| {
-        error!("missing pmt_inf_id");
-        anyhow::anyhow!("missing pmt_inf_id id")
-    })?;
+    let submission = submit(state, transaction, &tx_tp, true).await;
 
-    debug!(%msg_id, %end_to_end_id, "extracted transaction identifiers");
+    let accepted = submission.accepted();
+    info!(%msg_id, tx_count, accepted, "processed credit transfer transactions");
 
-    let money = if let (Some(amt), Some(ccy)) = (amount, ccy) {
-        Some(Money::try_from((amt, ccy)).map_err(|_e| anyhow::anyhow!("invalid currency"))?)
-    } else {
-        trace!(msg_id, "transaction has no amount or currency");
-        None
-    };
-
-    let transaction_relationship = TransactionRelationship {
-        from: data_cache.dbtr_acct_id.to_string(),
-        to: data_cache.cdtr_acct_id.to_string(),
-        amt: money,
-        cre_dt_tm: data_cache.cre_dt_tm,
-        end_to_end_id: end_to_end_id.to_string(),
-        msg_id: msg_id.to_string(),
-        pmt_inf_id: pmt_inf_id.into(),
-        tx_tp: tx_tp.to_owned(),
-        ..Default::default()
-    };
+    respond(state, &key, version, submission).await
+}
 
-    let request = CreatePseudonymRequest {
-        transaction_relationship: Some(transaction_relationship),
-        debtor_id: data_cache.dbtr_id.to_string(),
-        debtor_account_id: data_cache.dbtr_acct_id.to_string(),
-        creditor_id: data_cache.cdtr_id.to_string(),
-        creditor_account_id: data_cache.cdtr_acct_id.to_string(),
-    };
+/// Stores every credit transfer of a pacs.008 message as its own document
+///
+/// When `publish` is false, the transactions are added to history without being queued for
+/// evaluation
+pub(super) async fn submit(
+    state: &AppHandle,
+    mut transaction: Pacs008Document,
+    tx_tp: &str,
+    publish: bool,
+) -> Submission {
+    let transactions = std::mem::take(&mut transaction.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf);
+    let mut submission = Submission::new(
+        transaction.f_i_to_f_i_cstmr_cdt_trf.grp_hdr.msg_id.as_str(),
+        transactions.len(),
+    );
+
+    for cdt_trf_tx_inf in transactions {
+        let end_to_end_id = cdt_trf_tx_inf.pmt_id.end_to_end_id.to_string();
+
+        // every credit transfer is stored and published as its own document
+        let mut document = transaction.clone();
+        document.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf = vec![cdt_trf_tx_inf];
+
+        let result = history::process_transaction(state, document, tx_tp, publish).await;
+        submission.record(end_to_end_id, result);
+    }
 
-    debug!(%msg_id, %end_to_end_id, "constructed transaction relationship");
+    submission
+}
 
-    let mut pseudonyms_client = state.mutate_pseudonym_client.clone();
+impl HistoryDocument for Pacs008Document {
+    const TABLE: &'static str = "pacs008";
+
+    fn pseudonym_request(
+        &self,
+        tx_tp: &str,
+    ) -> Result<(CreatePseudonymRequest, DataCache), AppError> {
+        let cdt_trf_tx_inf = self
+            .f_i_to_f_i_cstmr_cdt_trf
+            .cdt_trf_tx_inf
+            .first()
+            .ok_or_else(|| AppError::bad_request("missing cdt_trf_tx_inf"))?;
+        let data_cache = build_data_cache(self, cdt_trf_tx_inf)?;
+
+        let pmt_inf_id = cdt_trf_tx_inf.pmt_id.instr_id.as_ref().ok_or_else(|| {
+            error!("missing pmt_inf_id");
+            AppError::bad_request("missing pmt_id.instr_id")
+        })?;
 
-    trace!("updating pseudonyms");
+        let request = history::pseudonym_request(
+            &data_cache,
+            &self.f_i_to_f_i_cstmr_cdt_trf.grp_hdr.msg_id,
+            &cdt_trf_tx_inf.pmt_id.end_to_end_id,
+            pmt_inf_id.to_string(),
+            tx_tp,
+        )?;
 
-    let pseudonyms_fut = async {
-        let span = trace_span!(
-            "create.pseudonyms.account",
-            "otel.kind" = "client",
-            "rpc.service" = "pseudonyms"
-        );
-        pseudonyms_client
-            .create_pseudonym(request)
-            .instrument(span)
-            .await
-            .map_err(|e| {
-                error!(error = %e, "failed to create pseudonyms");
-                anyhow::anyhow!("could not create pseudonyms")
-            })
-    };
+        Ok((request, data_cache))
+    }
 
-    let (_, _) = tokio::try_join!(
-        pseudonyms_fut,
-        set_cache(&end_to_end_id, &state, &data_cache)
-    )?;
-    trace!("pseudonyms saved");
-
-    let id = Uuid::now_v7();
-    debug!(%id, "inserting transaction into history");
-
-    let span = trace_span!("create.transaction_history.pacs008");
-    span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
-    span.set_attribute("otel.kind", "client");
-    span.set_attribute(attribute::DB_OPERATION_NAME, "insert");
-    span.set_attribute(attribute::DB_COLLECTION_NAME, "pacs008");
-
-    trace!(id = ?id, "saving transaction history");
-    sqlx::query!(
-        "insert into pacs008 (id, document) values ($1, $2)",
-        id,
-        sqlx::types::Json(&transaction) as _
-    )
-    .execute(&state.services.postgres)
-    .instrument(span)
-    .await?;
-    info!(%id, %msg_id, "transaction added to history");
-
-    let payload = warden_core::message::Payload {
-        tx_tp: tx_tp.to_string(),
-        transaction: Some(warden_core::message::payload::Transaction::Pacs008(
-            transaction.clone(),
-        )),
-        data_cache: Some(data_cache),
-        ..Default::default()
-    };
+    async fn insert(&self, conn: &mut PgConnection, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "insert into pacs008 (id, document) values ($1, $2)",
+            id,
+            sqlx::types::Json(self) as _
+        )
+        .execute(conn)
+        .await?;
 
-    publish_message(&state, payload, msg_id).await?;
-    trace!(%msg_id, "published transaction to stream");
+        Ok(())
+    }
 
-    Ok((axum::http::StatusCode::CREATED, axum::Json(transaction)))
+    fn into_transaction(self) -> Transaction {
+        Transaction::Pacs008(self)
+    }
 }
 
-pub fn build_data_cache(transaction: &Pacs008Document) -> anyhow::Result<DataCache> {
+/// Builds the [DataCache] for one credit transfer of a pacs.008 message
+pub fn build_data_cache(
+    transaction: &Pacs008Document,
+    cdt_trf_tx_inf: &CreditTransferTransaction64,
+) -> Result<DataCache, AppError> {
     trace!("building data cache object");
-    let cdt_trf_tx_inf = transaction.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf.first();
-
-    let instd_amt = cdt_trf_tx_inf.and_then(|value| value.instd_amt.clone());
+    let instd_amt = cdt_trf_tx_inf.instd_amt.clone();
 
-    let intr_bk_sttlm_amt = cdt_trf_tx_inf.and_then(|value| value.intr_bk_sttlm_amt.clone());
+    let intr_bk_sttlm_amt = cdt_trf_tx_inf.intr_bk_sttlm_amt.clone();
 
-    let xchg_rate = cdt_trf_tx_inf.and_then(|value| value.xchg_rate);
+    let xchg_rate = cdt_trf_tx_inf.xchg_rate;
     let cre_dt_tm = transaction.f_i_to_f_i_cstmr_cdt_trf.grp_hdr.cre_dt_tm;
 
-    let dbtr_othr = cdt_trf_tx_inf.and_then(|value| {
-        value
-            .dbtr
-            .id
-            .as_ref()
-            .and_then(|value| value.prvt_id.othr.first())
-    });
+    let dbtr_othr = cdt_trf_tx_inf
+        .dbtr
+        .id
+        .as_ref()
+        .and_then(|value| value.prvt_id.othr.first());
 
     let debtor_id = dbtr_othr
         .and_then(|value| {
@@ -208,15 +253,13 @@ pub fn build_data_cache(transaction: &Pacs008Document) -> anyhow::Result<DataCac
                 .as_ref()
                 .map(|schme_nm| format!("{}{}", value.id, schme_nm.prtry))
         })
-        .ok_or_else(|| anyhow::anyhow!("missing debtor id"))?;
+        .ok_or_else(|| AppError::bad_request("missing debtor id"))?;
 
-    let cdtr_othr = cdt_trf_tx_inf.and_then(|value| {
-        value.cdtr.as_ref().and_then(|value| {
-            value
-                .id
-                .as_ref()
-                .and_then(|value| value.prvt_id.othr.first())
-        })
+    let cdtr_othr = cdt_trf_tx_inf.cdtr.as_ref().and_then(|value| {
+        value
+            .id
+            .as_ref()
+            .and_then(|value| value.prvt_id.othr.first())
     });
 
     let creditor_id = cdtr_othr
@@ -226,22 +269,18 @@ pub fn build_data_cache(transaction: &Pacs008Document) -> anyhow::Result<DataCac
                 .as_ref()
                 .map(|schme_nm| format!("{}{}", value.id, schme_nm.prtry))
         })
-        .ok_or_else(|| anyhow::anyhow!("missing creditor id"))?;
+        .ok_or_else(|| AppError::bad_request("missing creditor id"))?;
 
-    let dbtr_acct_othr = cdt_trf_tx_inf.and_then(|value| {
+    let dbtr_acct_othr = cdt_trf_tx_inf
+        .dbtr_acct
+        .as_ref()
+        .and_then(|value| value.id.as_ref().map(|value| &value.othr));
+    let dbtr_mmb_id = cdt_trf_tx_inf.dbtr_agt.as_ref().and_then(|value| {
         value
-            .dbtr_acct
+            .fin_instn_id
+            .clr_sys_mmb_id
             .as_ref()
-            .and_then(|value| value.id.as_ref().map(|value| value.othr.clone()))
-    });
-    let dbtr_mmb_id = cdt_trf_tx_inf.and_then(|value| {
-        value.dbtr_agt.as_ref().and_then(|value| {
-            value
-                .fin_instn_id
-                .clr_sys_mmb_id
-                .as_ref()
-                .map(|value| value.mmb_id.as_str())
-        })
+            .map(|value| value.mmb_id.as_str())
     });
 
     let debtor_acct_id = if let (Some(a), Some(b)) = (dbtr_acct_othr, dbtr_mmb_id) {
@@ -249,22 +288,18 @@ pub fn build_data_cache(transaction: &Pacs008Document) -> anyhow::Result<DataCac
     } else {
         None
     }
-    .ok_or_else(|| anyhow::anyhow!("missing debtor_acct_id"))?;
+    .ok_or_else(|| AppError::bad_request("missing debtor account id"))?;
 
-    let cdtr_acct_othr = cdt_trf_tx_inf.and_then(|value| {
+    let cdtr_acct_othr = cdt_trf_tx_inf
+        .cdtr_acct
+        .as_ref()
+        .and_then(|value| value.id.as_ref().map(|value| &value.othr));
+    let cdtr_mmb_id = cdt_trf_tx_inf.cdtr_agt.as_ref().and_then(|value| {
         value
-            .cdtr_acct
+            .fin_instn_id
+            .clr_sys_mmb_id
             .as_ref()
-            .and_then(|value| value.id.as_ref().map(|value| value.othr.clone()))
-    });
-    let cdtr_mmb_id = cdt_trf_tx_inf.and_then(|value| {
-        value.cdtr_agt.as_ref().and_then(|value| {
-            value
-                .fin_instn_id
-                .clr_sys_mmb_id
-                .as_ref()
-                .map(|value| value.mmb_id.as_str())
-        })
+            .map(|value| value.mmb_id.as_str())
     });
 
     let creditor_acct_id = if let (Some(a), Some(b)) = (cdtr_acct_othr, cdtr_mmb_id) {
@@ -272,7 +307,7 @@ pub fn build_data_cache(transaction: &Pacs008Document) -> anyhow::Result<DataCac
     } else {
         None
     }
-    .ok_or_else(|| anyhow::anyhow!("missing creditor_acct_id"))?;
+    .ok_or_else(|| AppError::bad_request("missing creditor account id"))?;
 
     let data_cache = DataCache {
         cdtr_id: creditor_id.to_string(),
@@ -288,30 +323,6 @@ pub fn build_data_cache(transaction: &Pacs008Document) -> anyhow::Result<DataCac
     Ok(data_cache)
 }
 
-#[instrument(skip(state), fields(end_to_end_id = end_to_end_id))]
-pub async fn set_cache(
-    end_to_end_id: &str,
-    state: &AppHandle,
-    data_cache: &DataCache,
-) -> anyhow::Result<()> {
-    trace!("updating cache");
-    let span = Span::current();
-    span.set_attribute(attribute::DB_SYSTEM_NAME, "valkey");
-    span.set_attribute(attribute::DB_OPERATION_NAME, "set");
-    span.set_attribute(attribute::DB_OPERATION_PARAMETER, end_to_end_id.to_string());
-    let mut cache_update = state.services.cache.get().await?;
-    let bytes = prost::Message::encode_to_vec(data_cache);
-    cache_update
-        .set_ex::<_, _, ()>(&end_to_end_id, bytes, state.app_config.cache_ttl)
-        .await
-        .map_err(|e| {
-            error!("cache: {e}");
-            anyhow::anyhow!("internal server error")
-        })?;
-
-    Ok(())
-}
-
 #[cfg(test)]
 mod tests {
     use axum::{
@@ -391,6 +402,380 @@ mod tests {
         post_clearance(app, end_to_end_id, ccy, debtor_fsp, creditor_fsp).await;
     }
 
+    #[sqlx::test]
+    async fn post_as_participant(pool: PgPool) {
+        let mut config = test_config();
+        config.misc["auth"] = serde_json::json!({
+            "enabled": true,
+            "api-keys": [
+                {
+                    "name": "fsp001",
+                    // sha-256 of "fsp001-key"
+                    "sha256": "e7c43e83f38cabfcdff025b4b132b1122f82cbfc285f577de0fa507fabfc240a",
+                    "role": "participant",
+                    "participant": "fsp001",
+                },
+                {
+                    "name": "fsp999",
+                    // sha-256 of "fsp999-key"
+                    "sha256": "0680b98713cec15b273dfcd89ea4a8f0044be5ec1d788f7751c9dec12d5007eb",
+                    "role": "participant",
+                    "participant": "fsp999",
+                },
+            ],
+        });
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &config,
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let post = |key: Option<&str>| {
+            let body = serde_json::to_vec(&server::test_pacs008()).unwrap();
+            let mut request = Request::builder()
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .uri("/api/v1/pacs008");
+            if let Some(key) = key {
+                request = request.header("X-API-Key", key);
+            }
+            request.body(Body::from(body)).unwrap()
+        };
+
+        let response = app.clone().oneshot(post(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app.clone().oneshot(post(Some("wrong"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        // the debtor agent of the test document is fsp001
+        let response = app.clone().oneshot(post(Some("fsp999-key"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app.clone().oneshot(post(Some("fsp001-key"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // only admins may browse history
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v1/transactions")
+                    .header("X-API-Key", "fsp001-key")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[sqlx::test]
+    async fn rate_limited(pool: PgPool) {
+        let mut config = test_config();
+        config.misc["rate-limit"] = serde_json::json!({
+            "enabled": true,
+            "burst": 1,
+            "per-second": 0.001,
+        });
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &config,
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        // buckets outlive the test, so every run gets its own debtor agent
+        let debtor_agent = generate_id();
+        let post = || {
+            let mut pacs = server::test_pacs008();
+            let inf = &mut pacs.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0];
+            inf.dbtr_agt
+                .as_mut()
+                .unwrap()
+                .fin_instn_id
+                .clr_sys_mmb_id
+                .as_mut()
+                .unwrap()
+                .mmb_id = debtor_agent.clone();
+
+            Request::builder()
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .uri("/api/v1/pacs008")
+                .body(Body::from(serde_json::to_vec(&pacs).unwrap()))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(post()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = app.oneshot(post()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key("retry-after"));
+    }
+
+    #[sqlx::test]
+    async fn rate_limit_must_refill(pool: PgPool) {
+        let mut config = test_config();
+        config.misc["rate-limit"] = serde_json::json!({
+            "enabled": true,
+            "burst": 1,
+            "per-second": 0.0,
+        });
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &config,
+        )
+        .await;
+        assert!(state.is_err());
+    }
+
+    #[sqlx::test]
+    async fn post_multiple(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool.clone(),
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let mut pacs = server::test_pacs008();
+        let mut second = pacs.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0].clone();
+        second.pmt_id.end_to_end_id = generate_id();
+        second.pmt_id.instr_id = Some(generate_id());
+        pacs.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf.push(second);
+
+        let body = serde_json::to_vec(&pacs).unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/pacs008")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+        let outcomes = body["transactions"].as_array().unwrap();
+
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes.iter().all(|value| value["accepted"] == true));
+
+        let rows = sqlx::query_scalar!(
+            "select count(*) from pacs008 where message_id = $1",
+            pacs.f_i_to_f_i_cstmr_cdt_trf.grp_hdr.msg_id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        assert_eq!(rows, Some(2));
+    }
+
+    #[sqlx::test]
+    async fn post_partially_accepted(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let mut pacs = server::test_pacs008();
+        let mut second = pacs.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0].clone();
+        second.pmt_id.end_to_end_id = generate_id();
+        second.pmt_id.instr_id = None;
+        pacs.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf.push(second);
+
+        let body = serde_json::to_vec(&pacs).unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/pacs008")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+        let outcomes = body["transactions"].as_array().unwrap();
+
+        assert_eq!(outcomes[0]["accepted"], true);
+        assert_eq!(outcomes[1]["accepted"], false);
+    }
+
+    #[sqlx::test]
+    async fn post_replay(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let mut pacs = server::test_pacs008();
+        let key = generate_id();
+
+        let request = |body: Vec<u8>| {
+            Request::builder()
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .header("Idempotency-Key", &key)
+                .uri("/api/v0/pacs008")
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let body = serde_json::to_vec(&pacs).unwrap();
+        let response = app.clone().oneshot(request(body.clone())).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let original = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        let response = app.clone().oneshot(request(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let replayed = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&original).unwrap(),
+            serde_json::from_slice::<serde_json::Value>(&replayed).unwrap()
+        );
+
+        pacs.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0]
+            .instd_amt
+            .as_mut()
+            .unwrap()
+            .value = 1.0;
+        let body = serde_json::to_vec(&pacs).unwrap();
+        let response = app.oneshot(request(body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[sqlx::test]
+    async fn post_replay_across_versions(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let body = serde_json::to_vec(&server::test_pacs008()).unwrap();
+        let request = |uri: &str| {
+            Request::builder()
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .uri(uri)
+                .body(Body::from(body.clone()))
+                .unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("/api/v0/pacs008"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        // the same message retried on another version is replayed rather than a conflict
+        let response = app.oneshot(request("/api/v1/pacs008")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+    }
+
     #[sqlx::test]
     async fn post_missing_e2e(pool: PgPool) {
         let config = test_config();
@@ -430,7 +815,7 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
     #[sqlx::test]
@@ -474,7 +859,87 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.headers()[axum::http::header::CONTENT_TYPE],
+            crate::error::PROBLEM_JSON
+        );
+
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+        assert_eq!(body["status"], 400);
+    }
+
+    #[sqlx::test]
+    async fn post_invalid(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool.clone(),
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let mut pacs = server::test_pacs008();
+        let cdt_trf_tx_inf = &mut pacs.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0];
+        let instd_amt = cdt_trf_tx_inf.instd_amt.as_mut().unwrap();
+        instd_amt.ccy = "XT".to_string();
+        instd_amt.value = -294.3;
+
+        let body = serde_json::to_vec(&pacs).unwrap();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/pacs008")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body_bytes).unwrap();
+
+        let paths: Vec<&str> = body["violations"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|violation| violation["path"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "$.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0].instd_amt.ccy",
+                "$.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0].instd_amt.value",
+            ]
+        );
+
+        // rejected before anything was written
+        let count = sqlx::query_scalar!("select count(*) from pacs008")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, Some(0));
     }
 
     async fn post_clearance(
@@ -546,8 +1011,8 @@ mod tests {
                         "clr_sys_mmb_id": {
                           "mmb_id": debtor_fsp
                         },
-                        "b_i_c_f_i": "BANKXXX",
-                        "l_e_i": "1234567890",
+                        "b_i_c_f_i": "BANKZAJJ",
+                        "l_e_i": "5493001KJTIIGC8Y1R12",
                         "nm": "Bank"
                       }
                     }
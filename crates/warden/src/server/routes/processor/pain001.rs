@@ -0,0 +1,417 @@
+use axum::{extract::State, http::HeaderMap, response::Response};
+use sqlx::PgConnection;
+use tracing::{debug, error, info, trace, warn};
+use uuid::Uuid;
+use warden_core::{
+    iso20022::{
+        TransactionType, pacs008,
+        pain001::{
+            BranchAndFinancialInstitutionIdentification8, CashAccount40,
+            CreditTransferTransaction61, Pain001Document, PartyIdentification272,
+        },
+        validate::Validate,
+    },
+    message::{DataCache, payload::Transaction},
+    pseudonyms::transaction_relationship::CreatePseudonymRequest,
+};
+use warden_middleware::auth::Principal;
+
+use crate::{
+    error::{AppError, ProblemDetails},
+    server::{
+        document::IsoDocument,
+        idempotency,
+        routes::{
+            PAIN001_001_12,
+            processor::{
+                Submission, SubmissionResponse, authorize,
+                history::{self, HistoryDocument},
+                respond, throttle, v1,
+            },
+        },
+    },
+    state::AppHandle,
+    version::Version,
+};
+
+submission_handlers! {
+    /// Submit a pain.001.001.12 customer credit transfer initiation
+    ///
+    /// Each credit transfer in every `pmt_inf` is evaluated independently
+    handlers = (post_pain001, post_pain001_v1),
+    responses(
+        (CREATED, "all credit transfers were accepted"),
+        (MULTI_STATUS, "some credit transfers were not accepted"),
+    ),
+    problems(
+        (FORBIDDEN, "the caller is not an agent in every transaction"),
+        (CONFLICT, "the idempotency key was used with a different payload, or the transaction was already submitted"),
+        (UNPROCESSABLE_ENTITY, "the document failed validation, or its content cannot be processed"),
+    ),
+    operation_id = "post_pain_001",
+    path = "/pain001",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Deduplicates retries of the same submission. Defaults to the message and end to end ids")
+    ),
+    tag = PAIN001_001_12,
+    document = Pain001Document,
+    skip(state, transaction),
+    async fn(
+        State(state): State<AppHandle>,
+        principal: Principal,
+        headers: HeaderMap,
+        IsoDocument(transaction): IsoDocument<Pain001Document>,
+    ) {
+        |version| accept(&state, version, &principal, &headers, transaction).await
+    }
+}
+
+/// Validates, deduplicates and submits every transaction of the document, then renders the
+/// outcome in the shape of `version`
+async fn accept(
+    state: &AppHandle,
+    version: Version,
+    principal: &Principal,
+    headers: &HeaderMap,
+    mut transaction: Pain001Document,
+) -> Result<Response, AppError> {
+    let tx_tp = TransactionType::PAIN001.to_string();
+    tracing::Span::current().record("tx_tp", &tx_tp);
+
+    let msg_id = transaction.cstmr_cdt_trf_initn.grp_hdr.msg_id.to_string();
+    tracing::Span::current().record("msg_id", &msg_id);
+
+    let pmt_inf = &transaction.cstmr_cdt_trf_initn.pmt_inf;
+    if pmt_inf.is_empty() {
+        error!(%msg_id, "message has no payment information");
+        return Err(AppError::bad_request(
+            "pmt_inf must contain at least one entry",
+        ));
+    }
+    if pmt_inf.iter().any(|value| value.cdt_trf_tx_inf.is_empty()) {
+        error!(%msg_id, "payment information has no credit transfer transactions");
+        return Err(AppError::bad_request(
+            "cdt_trf_tx_inf must contain at least one entry",
+        ));
+    }
+
+    transaction.validate().map_err(|violations| {
+        warn!(%msg_id, count = violations.len(), "document failed validation");
+        AppError::Invalid(violations)
+    })?;
+
+    authorize(
+        principal,
+        pmt_inf.iter().flat_map(|pmt_inf| {
+            pmt_inf.cdt_trf_tx_inf.iter().map(|value| {
+                [Some(&pmt_inf.dbtr_agt), value.cdtr_agt.as_ref()].map(|agt| {
+                    agt.and_then(|agt| agt.fin_instn_id.clr_sys_mmb_id.as_ref())
+                        .map(|value| value.mmb_id.as_str())
+                })
+            })
+        }),
+    )?;
+
+    let debtor_agent = pmt_inf[0]
+        .dbtr_agt
+        .fin_instn_id
+        .clr_sys_mmb_id
+        .as_ref()
+        .map(|value| value.mmb_id.as_str());
+    throttle(state, principal, debtor_agent).await?;
+
+    let key = idempotency::key(
+        headers,
+        principal,
+        &tx_tp,
+        &msg_id,
+        pmt_inf
+            .iter()
+            .flat_map(|value| value.cdt_trf_tx_inf.iter())
+            .map(|value| value.pmt_id.end_to_end_id.as_str()),
+    )?;
+    let body = serde_json::to_value(&transaction).map_err(anyhow::Error::new)?;
+
+    if let Some(response) = idempotency::claim(state, &key, &body).await? {
+        info!(%msg_id, "replaying previous submission");
+        return Ok(response);
+    }
+
+    let payment_information = std::mem::take(&mut transaction.cstmr_cdt_trf_initn.pmt_inf);
+    let tx_count = payment_information
+        .iter()
+        .map(|value| value.cdt_trf_tx_inf.len())
+        .sum::<usize>();
+    tracing::Span::current().record("tx_count", tx_count);
+
+    debug!(%msg_id, tx_count, "evaluating credit transfer transactions");
+
+    let mut submission = Submission::new(msg_id.as_str(), tx_count);
+
+    for mut pmt_inf in payment_information {
+        let transactions = std::mem::take(&mut pmt_inf.cdt_trf_tx_inf);
+
+        for cdt_trf_tx_inf in transactions {
+            let end_to_end_id = cdt_trf_tx_inf.pmt_id.end_to_end_id.to_string();
+
+            // every credit transfer is stored and published as its own document
+            let mut payment = pmt_inf.clone();
+            payment.cdt_trf_tx_inf = vec![cdt_trf_tx_inf];
+            let mut document = transaction.clone();
+            document.cstmr_cdt_trf_initn.pmt_inf = vec![payment];
+
+            let result = history::process_transaction(state, document, &tx_tp, true).await;
+            submission.record(end_to_end_id, result);
+        }
+    }
+
+    let accepted = submission.accepted();
+    info!(%msg_id, tx_count, accepted, "processed credit transfer transactions");
+
+    respond(state, &key, version, submission).await
+}
+
+impl HistoryDocument for Pain001Document {
+    const TABLE: &'static str = "pain001";
+
+    fn pseudonym_request(
+        &self,
+        tx_tp: &str,
+    ) -> Result<(CreatePseudonymRequest, DataCache), AppError> {
+        let pmt_inf = self
+            .cstmr_cdt_trf_initn
+            .pmt_inf
+            .first()
+            .ok_or_else(|| AppError::bad_request("missing pmt_inf"))?;
+        let cdt_trf_tx_inf = pmt_inf
+            .cdt_trf_tx_inf
+            .first()
+            .ok_or_else(|| AppError::bad_request("missing cdt_trf_tx_inf"))?;
+        let data_cache = build_data_cache(self, cdt_trf_tx_inf)?;
+
+        let request = history::pseudonym_request(
+            &data_cache,
+            &self.cstmr_cdt_trf_initn.grp_hdr.msg_id,
+            &cdt_trf_tx_inf.pmt_id.end_to_end_id,
+            pmt_inf.pmt_inf_id.to_string(),
+            tx_tp,
+        )?;
+
+        Ok((request, data_cache))
+    }
+
+    async fn insert(&self, conn: &mut PgConnection, id: Uuid) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "insert into pain001 (id, document) values ($1, $2)",
+            id,
+            sqlx::types::Json(self) as _
+        )
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+
+    fn into_transaction(self) -> Transaction {
+        Transaction::Pain001(self)
+    }
+}
+
+/// Builds the [DataCache] for one credit transfer of a pain.001 message
+///
+/// The debtor is taken from the payment information the transfer belongs to
+pub fn build_data_cache(
+    transaction: &Pain001Document,
+    cdt_trf_tx_inf: &CreditTransferTransaction61,
+) -> Result<DataCache, AppError> {
+    trace!("building data cache object");
+    let pmt_inf = transaction
+        .cstmr_cdt_trf_initn
+        .pmt_inf
+        .first()
+        .ok_or_else(|| AppError::bad_request("missing pmt_inf"))?;
+
+    let instd_amt = cdt_trf_tx_inf.amt.instd_amt.as_ref().map(|value| {
+        pacs008::ActiveOrHistoricCurrencyAndAmount {
+            ccy: value.ccy.to_string(),
+            value: value.value,
+        }
+    });
+
+    let xchg_rate = cdt_trf_tx_inf
+        .xchg_rate_inf
+        .as_ref()
+        .and_then(|value| value.xchg_rate);
+    let cre_dt_tm = transaction.cstmr_cdt_trf_initn.grp_hdr.cre_dt_tm;
+
+    let debtor_id =
+        party_id(Some(&pmt_inf.dbtr)).ok_or_else(|| AppError::bad_request("missing debtor id"))?;
+
+    let creditor_id = party_id(cdt_trf_tx_inf.cdtr.as_ref())
+        .ok_or_else(|| AppError::bad_request("missing creditor id"))?;
+
+    let debtor_acct_id = account_id(Some(&pmt_inf.dbtr_acct), Some(&pmt_inf.dbtr_agt))
+        .ok_or_else(|| AppError::bad_request("missing debtor account id"))?;
+
+    let creditor_acct_id = account_id(
+        cdt_trf_tx_inf.cdtr_acct.as_ref(),
+        cdt_trf_tx_inf.cdtr_agt.as_ref(),
+    )
+    .ok_or_else(|| AppError::bad_request("missing creditor account id"))?;
+
+    let data_cache = DataCache {
+        cdtr_id: creditor_id,
+        dbtr_id: debtor_id,
+        dbtr_acct_id: debtor_acct_id,
+        cdtr_acct_id: creditor_acct_id,
+        cre_dt_tm: Some(cre_dt_tm),
+        instd_amt,
+        intr_bk_sttlm_amt: None,
+        xchg_rate,
+    };
+
+    Ok(data_cache)
+}
+
+fn party_id(party: Option<&PartyIdentification272>) -> Option<String> {
+    let othr = party
+        .and_then(|value| value.id.as_ref())
+        .and_then(|value| value.prvt_id.othr.first())?;
+
+    othr.schme_nm
+        .as_ref()
+        .map(|schme_nm| format!("{}{}", othr.id, schme_nm.prtry))
+}
+
+fn account_id(
+    acct: Option<&CashAccount40>,
+    agt: Option<&BranchAndFinancialInstitutionIdentification8>,
+) -> Option<String> {
+    let othr = acct.and_then(|value| value.id.as_ref().map(|value| &value.othr))?;
+    let mmb_id = agt.and_then(|value| {
+        value
+            .fin_instn_id
+            .clr_sys_mmb_id
+            .as_ref()
+            .map(|value| value.mmb_id.as_str())
+    })?;
+
+    Some(format!("{}{mmb_id}", othr.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+    use warden_stack::cache::RedisManager;
+
+    use crate::{
+        server::{self, metrics_app, test_config},
+        state::{AppState, Services},
+    };
+
+    #[sqlx::test]
+    async fn post(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool.clone(),
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state).merge(metrics_app());
+
+        let pain = server::test_pain001();
+        let end_to_end_id = pain.cstmr_cdt_trf_initn.pmt_inf[0].cdt_trf_tx_inf[0]
+            .pmt_id
+            .end_to_end_id
+            .clone();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/pain001")
+                    .body(Body::from(serde_json::to_vec(&pain).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["transactions"][0]["end_to_end_id"], end_to_end_id);
+        assert_eq!(body["transactions"][0]["accepted"], true);
+
+        // stored, and queued for evaluation
+        let stored: i64 =
+            sqlx::query_scalar("select count(*) from pain001 where end_to_end_id = $1")
+                .bind(&end_to_end_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(stored, 1);
+
+        let queued: i64 = sqlx::query_scalar("select count(*) from outbox where sent_at is null")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(queued, 1);
+    }
+
+    #[sqlx::test]
+    async fn post_empty(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state).merge(metrics_app());
+
+        let mut pain = server::test_pain001();
+        pain.cstmr_cdt_trf_initn.pmt_inf.clear();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .header("Content-Type", "application/json")
+                    .uri("/api/v0/pain001")
+                    .body(Body::from(serde_json::to_vec(&pain).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
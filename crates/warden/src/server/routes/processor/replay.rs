@@ -0,0 +1,168 @@
+use opentelemetry_semantic_conventions::attribute;
+use serde::de::DeserializeOwned;
+use tracing::{Instrument, debug, error, info, info_span, trace_span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+use warden_core::iso20022::{
+    TransactionType, pacs008::Pacs008Document, pain001::Pain001Document, pain013::Pain013Document,
+};
+
+use crate::{
+    error::AppError, server::routes::processor::history::HistoryDocument, state::AppHandle,
+};
+
+/// Resends the parties of every stored credit transfer to the pseudonyms service
+///
+/// Links the parties of transactions recorded before each was linked with their own account.
+/// CreatePseudonym is idempotent, so this may be run again should it stop part way. Status
+/// reports name the same parties as their pacs.008, and are not resent
+pub async fn pseudonyms(state: &AppHandle, batch_size: usize) -> anyhow::Result<()> {
+    let mut failed = 0;
+
+    failed += replay::<Pacs008Document>(state, TransactionType::PACS008, batch_size).await?;
+    failed += replay::<Pain001Document>(state, TransactionType::PAIN001, batch_size).await?;
+    failed += replay::<Pain013Document>(state, TransactionType::PAIN013, batch_size).await?;
+
+    match failed {
+        0 => Ok(()),
+        _ => Err(anyhow::anyhow!(
+            "{failed} transaction(s) could not be replayed"
+        )),
+    }
+}
+
+/// Resends every document in the table of `D`, in the order they were stored. Returns how many
+/// failed
+async fn replay<D: DeserializeOwned + HistoryDocument>(
+    state: &AppHandle,
+    tx_tp: TransactionType,
+    batch_size: usize,
+) -> anyhow::Result<usize> {
+    let table = D::TABLE;
+    let tx_tp = tx_tp.to_string();
+    let query = format!("select id, document from {table} where id > $1 order by id limit $2");
+
+    let (mut replayed, mut failed) = (0, 0);
+    let mut after = Uuid::nil();
+
+    loop {
+        let span = info_span!("get.transaction_history");
+        span.set_attribute(attribute::DB_SYSTEM_NAME, "postgres");
+        span.set_attribute(attribute::DB_OPERATION_NAME, "select");
+        span.set_attribute(attribute::DB_COLLECTION_NAME, table.to_string());
+
+        let rows: Vec<(Uuid, sqlx::types::Json<serde_json::Value>)> = sqlx::query_as(&query)
+            .bind(after)
+            .bind(batch_size as i64)
+            .fetch_all(&state.services.postgres)
+            .instrument(span)
+            .await?;
+
+        let Some((last, _)) = rows.last() else {
+            break;
+        };
+        after = *last;
+
+        for (id, document) in rows {
+            let result = match serde_json::from_value::<D>(document.0) {
+                Ok(document) => resend(state, &document, &tx_tp).await,
+                Err(e) => Err(anyhow::Error::new(e).into()),
+            };
+
+            match result {
+                Ok(()) => replayed += 1,
+                Err(e) => {
+                    error!(%id, table, "could not replay transaction: {e}");
+                    failed += 1;
+                }
+            }
+        }
+
+        debug!(table, replayed, failed, "replayed batch");
+    }
+
+    info!(table, replayed, failed, "replayed transactions");
+
+    Ok(failed)
+}
+
+async fn resend<D: HistoryDocument>(
+    state: &AppHandle,
+    document: &D,
+    tx_tp: &str,
+) -> Result<(), AppError> {
+    let (request, _) = document.pseudonym_request(tx_tp)?;
+
+    let span = trace_span!(
+        "create.pseudonyms.account",
+        "otel.kind" = "client",
+        "rpc.service" = "pseudonyms"
+    );
+    state
+        .mutate_pseudonym_client
+        .clone()
+        .create_pseudonym(request)
+        .instrument(span)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use sqlx::PgPool;
+    use tower::ServiceExt;
+    use warden_stack::cache::RedisManager;
+
+    use crate::{
+        server::{self, test_config},
+        state::{AppState, Services},
+    };
+
+    #[sqlx::test]
+    async fn replay(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &config,
+        )
+        .await
+        .unwrap();
+        let app = server::router(state.clone());
+
+        for _ in 0..3 {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .header("Content-Type", "application/json")
+                        .uri("/api/v0/pacs008")
+                        .body(Body::from(
+                            serde_json::to_vec(&server::test_pacs008()).unwrap(),
+                        ))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        // pages through the history, and may be repeated
+        super::pseudonyms(&state, 2).await.unwrap();
+        super::pseudonyms(&state, 2).await.unwrap();
+    }
+}
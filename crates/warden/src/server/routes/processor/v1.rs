@@ -0,0 +1,67 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+use warden_core::message::AggregationResult;
+
+use crate::error::ProblemDetails;
+
+/// How far a transaction got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStatus {
+    /// Accepted and queued for evaluation
+    Accepted,
+    /// Accepted and evaluated before the response was sent
+    Evaluated,
+    /// Not accepted, see `problem`
+    Rejected,
+}
+
+/// Outcome of a single transaction within a submitted message
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TransactionOutcome {
+    /// End to end identifier of the transaction
+    pub end_to_end_id: String,
+    /// How far the transaction got
+    pub status: TransactionStatus,
+    /// Why the transaction was not accepted, including every failed validation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<ProblemDetails>,
+    /// Verdict of the evaluation, when the submission waited for it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evaluation: Option<AggregationResult>,
+}
+
+/// Result of submitting a message
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubmissionResponse {
+    /// Message identifier from the group header
+    pub msg_id: String,
+    /// Outcome for each transaction, in the order they were received
+    pub transactions: Vec<TransactionOutcome>,
+}
+
+impl From<super::TransactionOutcome> for TransactionOutcome {
+    fn from(value: super::TransactionOutcome) -> Self {
+        let status = match (value.accepted, &value.evaluation) {
+            (false, _) => TransactionStatus::Rejected,
+            (true, Some(_)) => TransactionStatus::Evaluated,
+            (true, None) => TransactionStatus::Accepted,
+        };
+
+        Self {
+            end_to_end_id: value.end_to_end_id,
+            status,
+            problem: value.problem,
+            evaluation: value.evaluation,
+        }
+    }
+}
+
+impl From<super::SubmissionResponse> for SubmissionResponse {
+    fn from(value: super::SubmissionResponse) -> Self {
+        Self {
+            msg_id: value.msg_id,
+            transactions: value.transactions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
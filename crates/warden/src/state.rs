@@ -1,13 +1,17 @@
 use async_nats::jetstream::Context;
 use sqlx::PgPool;
 use std::{ops::Deref, sync::Arc};
-use tonic::transport::Endpoint;
+use tokio::sync::Notify;
 use tracing::error;
 use warden_core::pseudonyms::transaction_relationship::mutate_pseudonym_client::MutatePseudonymClient;
 use warden_stack::{Configuration, cache::RedisManager};
 
-use crate::{cnfg::LocalConfig, error::AppError};
-use warden_middleware::grpc::interceptor::{Intercepted, MyInterceptor};
+use crate::cnfg::LocalConfig;
+use warden_middleware::{
+    auth::Authenticator,
+    grpc::interceptor::{Intercepted, MyInterceptor},
+    rate_limit::RateLimiter,
+};
 
 #[derive(Clone)]
 pub struct AppHandle(Arc<AppState>);
@@ -31,27 +35,45 @@ pub struct AppState {
     pub mutate_pseudonym_client: MutatePseudonymClient<Intercepted>,
     pub services: Services,
     pub app_config: LocalConfig,
+    /// Validates the credentials of API requests
+    pub authenticator: Arc<Authenticator>,
+    /// Limits how often each participant may submit
+    pub rate_limiter: RateLimiter,
+    /// Wakes the outbox relay when new messages are committed
+    pub outbox: Notify,
 }
 
 impl AppState {
     pub async fn create(
         services: Services,
         configuration: &Configuration,
-    ) -> Result<AppHandle, AppError> {
+    ) -> anyhow::Result<AppHandle> {
         let local_config: LocalConfig = serde_json::from_value(configuration.misc.clone())?;
 
-        let channel = Endpoint::new(local_config.pseudonyms_endpoint.to_string())?
-            .connect()
+        let channel = configuration
+            .grpc
+            .connect(local_config.pseudonyms_endpoint.to_string())
             .await
             .inspect_err(|e| error!("could not connect to pseudonyms service: {e}"))?;
 
-        let mutate_pseudonym_client =
-            MutatePseudonymClient::with_interceptor(channel, MyInterceptor);
+        let mutate_pseudonym_client = MutatePseudonymClient::with_interceptor(
+            channel,
+            MyInterceptor::new(&configuration.grpc)?,
+        );
+
+        let authenticator = Authenticator::new(&local_config.auth)
+            .inspect_err(|e| error!("could not set up authentication: {e}"))?;
+
+        let rate_limiter =
+            RateLimiter::new(services.cache.clone(), local_config.rate_limit.clone());
 
         Ok(AppHandle(Arc::new(Self {
             mutate_pseudonym_client,
+            rate_limiter,
             services,
             app_config: local_config,
+            authenticator: Arc::new(authenticator),
+            outbox: Notify::new(),
         })))
     }
 }
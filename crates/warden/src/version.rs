@@ -1,39 +1,49 @@
-use std::collections::HashMap;
+use std::fmt;
 
-use axum::{
-    RequestPartsExt,
-    extract::{FromRequestParts, Path},
-    http::{StatusCode, request::Parts},
-    response::{IntoResponse, Response},
-};
+use time::macros::datetime;
 use utoipa::ToSchema;
+use warden_middleware::version::{ApiVersion, Deprecation};
 
-#[derive(Debug, ToSchema)]
+/// A version of the HTTP API
+///
+/// Each version is served under its own prefix, e.g. `/api/v1`, and the version that handled a
+/// request is available to its handler through [Versioned](warden_middleware::version::Versioned)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
 pub enum Version {
     V0,
+    V1,
 }
 
-impl<S> FromRequestParts<S> for Version
-where
-    S: Send + Sync,
-{
-    type Rejection = Response;
-
-    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let params: Path<HashMap<String, String>> =
-            parts.extract().await.map_err(IntoResponse::into_response)?;
+impl Version {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Version::V0 => "v0",
+            Version::V1 => "v1",
+        }
+    }
+}
 
-        let version = params
-            .get("version")
-            .ok_or_else(|| (StatusCode::NOT_FOUND, "version param missing").into_response())?;
+impl ApiVersion for Version {
+    const ALL: &'static [Self] = &[Version::V0, Version::V1];
 
-        match version.as_str() {
-            "v0" => Ok(Version::V0),
-            _ => Err((StatusCode::NOT_FOUND, "unknown version").into_response()),
+    fn deprecation(&self) -> Option<Deprecation> {
+        match self {
+            Version::V0 => Some(Deprecation {
+                since: datetime!(2026-10-18 00:00 UTC),
+                sunset: Some(datetime!(2027-04-18 00:00 UTC)),
+                successor: "/api/v1",
+            }),
+            Version::V1 => None,
         }
     }
 }
 
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use axum::{
@@ -46,7 +56,7 @@ mod tests {
     use warden_stack::cache::RedisManager;
 
     use crate::{
-        server::{self, generate_id, test_config},
+        server::{self, generate_id, test_config, test_pacs008},
         state::{AppState, Services},
     };
 
@@ -304,4 +314,63 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::NOT_FOUND);
     }
+
+    #[sqlx::test]
+    async fn versions_differ(pool: PgPool) {
+        let config = test_config();
+
+        let cache = RedisManager::new(&config.cache).await.unwrap();
+        let client = async_nats::connect(&config.nats.hosts[0]).await.unwrap();
+        let jetstream = async_nats::jetstream::new(client);
+
+        let state = AppState::create(
+            Services {
+                postgres: pool,
+                cache,
+                jetstream,
+            },
+            &test_config(),
+        )
+        .await
+        .unwrap();
+        let app = server::router(state);
+
+        let post = |version: &str| {
+            let body = serde_json::to_vec(&test_pacs008()).unwrap();
+            Request::builder()
+                .method("POST")
+                .header("Content-Type", "application/json")
+                .uri(format!("/api/{version}/pacs008"))
+                .body(Body::from(body))
+                .unwrap()
+        };
+
+        let response = app.clone().oneshot(post("v0")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let headers = response.headers();
+        assert!(headers.get("deprecation").is_some());
+        assert!(headers.get("sunset").is_some());
+        assert_eq!(
+            headers.get("link").unwrap(),
+            r#"</api/v1>; rel="successor-version""#
+        );
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["transactions"][0]["accepted"], true);
+
+        let response = app.oneshot(post("v1")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert!(response.headers().get("deprecation").is_none());
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["transactions"][0]["status"], "accepted");
+        assert!(body["transactions"][0].get("accepted").is_none());
+    }
 }
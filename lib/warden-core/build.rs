@@ -6,6 +6,8 @@ enum Entity {
     Pseudonyms,
     #[cfg(feature = "configuration")]
     Configuration,
+    #[cfg(feature = "evaluation")]
+    Evaluation,
 }
 
 #[cfg(any(feature = "message", feature = "pseudonyms", feature = "configuration"))]
@@ -35,14 +37,15 @@ impl Entity {
             }
         }
 
+        #[cfg(feature = "evaluation")]
+        fn evaluation_protos() -> Vec<&'static str> {
+            vec!["proto/evaluation/evaluation.proto"]
+        }
+
         #[cfg(feature = "pseudonyms")]
         fn pseudonyms_protos() -> Vec<&'static str> {
-            vec![
-                "proto/pseudonyms/account.proto",
-                "proto/pseudonyms/entity.proto",
-                "proto/pseudonyms/account_holder.proto",
-                "proto/pseudonyms/transaction_relationship.proto",
-            ]
+            // imports account, entity and account_holder
+            vec!["proto/pseudonyms/transaction_relationship.proto"]
         }
 
         match self {
@@ -58,6 +61,10 @@ impl Entity {
             Entity::Configuration => {
                 res.extend(configuration_protos());
             }
+            #[cfg(feature = "evaluation")]
+            Entity::Evaluation => {
+                res.extend(evaluation_protos());
+            }
         }
         res
     }
@@ -78,6 +85,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(feature = "configuration")]
     protos.extend(Entity::Configuration.protos());
 
+    #[cfg(feature = "evaluation")]
+    protos.extend(Entity::Evaluation.protos());
+
     #[cfg(any(feature = "message", feature = "pseudonyms", feature = "configuration"))]
     build_proto(&protos)?;
 
@@ -135,6 +145,10 @@ fn add_serde(config: tonic_prost_build::Builder) -> tonic_prost_build::Builder {
                 ".google.protobuf.Value",
                 "#[serde(try_from = \"serde_json::Value\")] #[serde(into = \"crate::configuration::conv::GenericParameter\")]",
             )
+            .field_attribute(
+                ".configuration.rule.Config.time_frame_strategy",
+                "#[serde(default, with = \"crate::configuration::conv::time_frame_strategy_serde\")]",
+            )
             .field_attribute(
                 ".configuration.typology.Expression.operator",
                 "#[serde(with = \"crate::configuration::conv::operator_serde\")]",
@@ -166,8 +180,28 @@ fn add_openapi(config: tonic_prost_build::Builder) -> tonic_prost_build::Builder
             ".configuration.rule.DeleteRuleConfigurationRequest",
             "#[derive(utoipa::IntoParams)]",
         )
+        .type_attribute(
+            ".evaluation.GetEvaluationRequest",
+            "#[derive(utoipa::IntoParams)]",
+        )
         .field_attribute(
             ".iso20022.pacs008.FIToFICustomerCreditTransferV12.cdt_trf_tx_inf",
-            "#[schema(min_items = 1, max_items = 1)]",
+            "#[schema(min_items = 1)]",
+        )
+        .field_attribute(
+            ".iso20022.pain001.CustomerCreditTransferInitiationV12.pmt_inf",
+            "#[schema(min_items = 1)]",
+        )
+        .field_attribute(
+            ".iso20022.pain001.PaymentInstruction44.cdt_trf_tx_inf",
+            "#[schema(min_items = 1)]",
+        )
+        .field_attribute(
+            ".iso20022.pain013.CreditorPaymentActivationRequestV11.pmt_inf",
+            "#[schema(min_items = 1)]",
+        )
+        .field_attribute(
+            ".iso20022.pain013.PaymentInstruction42.cdt_trf_tx",
+            "#[schema(min_items = 1)]",
         )
 }
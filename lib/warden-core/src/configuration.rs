@@ -1,6 +1,9 @@
 #[cfg(feature = "serde")]
 pub(crate) mod conv;
 
+#[cfg(feature = "serde")]
+pub mod template;
+
 tonic::include_proto!("configuration");
 
 pub mod routing {
@@ -1,5 +1,5 @@
 use crate::{
-    configuration::typology::Operator,
+    configuration::{rule::TimeframeStrategy, typology::Operator},
     google::protobuf::{ListValue, NullValue, Struct, Value, value},
 };
 
@@ -143,6 +143,35 @@ pub(crate) mod operator_serde {
     }
 }
 
+pub(crate) mod time_frame_strategy_serde {
+    use super::TimeframeStrategy;
+    use serde::{self, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(strategy: &i32, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let strategy = TimeframeStrategy::try_from(*strategy).unwrap_or_default();
+        s.serialize_str(strategy.as_str_name())
+    }
+
+    /// A missing strategy is [TimeframeStrategy::Worst]
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<i32, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s: Option<String> = Option::deserialize(deserializer)?;
+
+        let strategy = match s {
+            Some(s) => TimeframeStrategy::from_str_name(&s.to_uppercase())
+                .ok_or_else(|| serde::de::Error::custom(format!("unsupported strategy: {s}")))?,
+            None => TimeframeStrategy::default(),
+        };
+
+        Ok(strategy as i32)
+    }
+}
+
 impl From<Operator> for String {
     fn from(value: Operator) -> Self {
         value.as_str_name().to_owned()
@@ -165,6 +194,27 @@ mod tests {
     use serde::{Deserialize, Serialize};
     use serde_json::{self, json};
 
+    #[derive(Deserialize, Serialize)]
+    struct StrategyWrap {
+        #[serde(default, with = "super::time_frame_strategy_serde")]
+        strategy: i32,
+    }
+
+    #[test]
+    fn time_frame_strategy_roundtrip() {
+        let wrap: StrategyWrap = serde_json::from_value(json!({ "strategy": "best" })).unwrap();
+        assert_eq!(wrap.strategy, TimeframeStrategy::Best as i32);
+        assert_eq!(
+            serde_json::to_value(&wrap).unwrap(),
+            json!({ "strategy": "BEST" })
+        );
+
+        let wrap: StrategyWrap = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(wrap.strategy, TimeframeStrategy::Worst as i32);
+
+        assert!(serde_json::from_value::<StrategyWrap>(json!({ "strategy": "average" })).is_err());
+    }
+
     #[derive(Deserialize, Serialize)]
     struct OpWrap {
         #[serde(with = "super::operator_serde")]
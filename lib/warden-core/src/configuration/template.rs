@@ -0,0 +1,620 @@
+//! Rules defined by a query template
+//!
+//! Rather than being written in code, a rule may carry a `query` in its parameters: a single
+//! `select` over the pseudonyms tables that returns one number, which is then banded like any
+//! other rule. Values from the transaction being evaluated are bound with placeholders such as
+//! `{debtor_account}`; only the placeholders in [Bind] are available.
+//!
+//! Templates are written by analysts rather than end users, so the checks here guard against
+//! mistakes rather than a determined attacker. Templates are also executed read only.
+
+use std::{collections::HashSet, fmt};
+
+use serde::Deserialize;
+
+use crate::configuration::rule::Timeframe;
+
+/// Tables a template may read from. `transaction_window` is a function over
+/// `transaction_aggregate`, e.g. `from transaction_window({debtor_account}, 'destination', ...)`
+pub const TABLES: &[&str] = &[
+    "transaction_relationship",
+    "account_holder",
+    "transaction_aggregate",
+    "transaction_window",
+];
+
+/// Words that have no place in a read only query
+const FORBIDDEN: &[&str] = &[
+    "alter",
+    "analyze",
+    "call",
+    "comment",
+    "copy",
+    "create",
+    "current_setting",
+    "dblink",
+    "deallocate",
+    "delete",
+    "do",
+    "drop",
+    "execute",
+    "grant",
+    "insert",
+    "into",
+    "listen",
+    "lock",
+    "merge",
+    "notify",
+    "prepare",
+    "refresh",
+    "reindex",
+    "reset",
+    "revoke",
+    "set",
+    "set_config",
+    "truncate",
+    "update",
+    "vacuum",
+];
+
+/// Words that end a table reference rather than name its alias
+const RESERVED: &[&str] = &[
+    "cross",
+    "except",
+    "full",
+    "group",
+    "having",
+    "inner",
+    "intersect",
+    "join",
+    "left",
+    "limit",
+    "natural",
+    "offset",
+    "on",
+    "order",
+    "right",
+    "union",
+    "using",
+    "where",
+    "window",
+];
+
+/// Parameters of a rule defined by a query template
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct QueryRule {
+    /// The template, see [QueryTemplate]
+    pub query: String,
+    /// Milliseconds bound to `{max_query_range}` when the rule has no time frames
+    #[serde(default)]
+    pub max_query_range: Option<f64>,
+}
+
+impl QueryRule {
+    /// Whether `parameters` define the rule with a query template
+    pub fn is_defined_by(parameters: &serde_json::Value) -> bool {
+        parameters.get("query").is_some()
+    }
+
+    /// Reads the rule from `parameters`
+    pub fn from_parameters(parameters: serde_json::Value) -> Result<Self, TemplateError> {
+        serde_json::from_value(parameters).map_err(|e| TemplateError::Parameters(e.to_string()))
+    }
+
+    /// Checks the template, and that the parameters it binds are set. `{max_query_range}` may
+    /// instead be bound to each of the rule's `time_frames`
+    pub fn template(&self, time_frames: &[Timeframe]) -> Result<QueryTemplate, TemplateError> {
+        let template = QueryTemplate::parse(&self.query)?;
+
+        if template.binds().contains(&Bind::MaxQueryRange)
+            && self.max_query_range.is_none()
+            && time_frames.is_empty()
+        {
+            return Err(TemplateError::Parameters(
+                "max_query_range is bound but neither it nor time_frames are set".to_owned(),
+            ));
+        }
+
+        Ok(template)
+    }
+}
+
+/// A value a template may bind
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bind {
+    /// `{debtor_account}`, the debtor's account
+    DebtorAccount,
+    /// `{creditor_account}`, the creditor's account
+    CreditorAccount,
+    /// `{debtor}`, the debtor
+    Debtor,
+    /// `{creditor}`, the creditor
+    Creditor,
+    /// `{cre_dt_tm}`, when the message being evaluated was created
+    CreDtTm,
+    /// `{tx_tp}`, the type of the message being evaluated
+    TxTp,
+    /// `{max_query_range}`, from the rule's parameters or each of its time frames
+    MaxQueryRange,
+}
+
+impl Bind {
+    /// Every value a template may bind
+    pub const ALL: &[Self] = &[
+        Self::DebtorAccount,
+        Self::CreditorAccount,
+        Self::Debtor,
+        Self::Creditor,
+        Self::CreDtTm,
+        Self::TxTp,
+        Self::MaxQueryRange,
+    ];
+
+    /// Placeholder name
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::DebtorAccount => "debtor_account",
+            Self::CreditorAccount => "creditor_account",
+            Self::Debtor => "debtor",
+            Self::Creditor => "creditor",
+            Self::CreDtTm => "cre_dt_tm",
+            Self::TxTp => "tx_tp",
+            Self::MaxQueryRange => "max_query_range",
+        }
+    }
+
+    /// Postgres type the value is bound as
+    pub fn sql_type(self) -> &'static str {
+        match self {
+            Self::DebtorAccount | Self::CreditorAccount | Self::Debtor | Self::Creditor => "text",
+            Self::TxTp => "text",
+            Self::CreDtTm => "timestamptz",
+            Self::MaxQueryRange => "float8",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|value| value.name().eq(name))
+    }
+}
+
+/// Why a template was rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    /// The template is empty
+    Empty,
+    /// The template is not a `select`
+    NotSelect,
+    /// A placeholder that is not in [Bind]
+    UnknownBind(String),
+    /// A `{` without its `}`
+    UnclosedBind,
+    /// A string literal without its closing quote
+    UnterminatedString,
+    /// A character or word that is not allowed
+    Forbidden(String),
+    /// A table that is not in [TABLES]
+    Table(String),
+    /// The parameters around the template are invalid
+    Parameters(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "query is empty"),
+            Self::NotSelect => write!(f, "query must be a select"),
+            Self::UnknownBind(name) => {
+                let names: Vec<_> = Bind::ALL.iter().map(|value| value.name()).collect();
+                write!(
+                    f,
+                    "unknown bind {{{name}}}, expected one of {}",
+                    names.join(", ")
+                )
+            }
+            Self::UnclosedBind => write!(f, "bind is missing its closing brace"),
+            Self::UnterminatedString => write!(f, "string literal is missing its closing quote"),
+            Self::Forbidden(value) => write!(f, "`{value}` is not allowed in a query"),
+            Self::Table(table) => write!(
+                f,
+                "table {table} is not allowed, expected one of {}",
+                TABLES.join(", ")
+            ),
+            Self::Parameters(reason) => write!(f, "invalid parameters: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Symbol(char),
+    /// A string literal or bind
+    Value,
+}
+
+/// A checked template, with its placeholders replaced by positional binds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryTemplate {
+    sql: String,
+    binds: Vec<Bind>,
+}
+
+impl QueryTemplate {
+    /// Checks `template` and replaces its placeholders with positional binds
+    ///
+    /// Comments, quoted identifiers, backslashes, `$` and `;` are rejected outright. Tables are
+    /// read from `from` and `join` clauses, so columns named there, as in
+    /// `extract(epoch from tr.cre_dt_tm)`, must be qualified.
+    pub fn parse(template: &str) -> Result<Self, TemplateError> {
+        let mut sql = String::with_capacity(template.len());
+        let mut binds = Vec::new();
+        let mut tokens = Vec::new();
+        let mut chars = template.trim().chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\'' => {
+                    sql.push(c);
+                    loop {
+                        match chars.next() {
+                            Some('\'') if chars.peek() == Some(&'\'') => {
+                                sql.push_str("''");
+                                chars.next();
+                            }
+                            Some('\'') => break,
+                            Some('\\') => return Err(TemplateError::Forbidden("\\".to_owned())),
+                            Some(c) => sql.push(c),
+                            None => return Err(TemplateError::UnterminatedString),
+                        }
+                    }
+                    sql.push('\'');
+                    tokens.push(Token::Value);
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(TemplateError::UnclosedBind),
+                        }
+                    }
+                    let bind = Bind::from_name(name.trim())
+                        .ok_or_else(|| TemplateError::UnknownBind(name.trim().to_owned()))?;
+                    let index = match binds.iter().position(|value| *value == bind) {
+                        Some(index) => index,
+                        None => {
+                            binds.push(bind);
+                            binds.len() - 1
+                        }
+                    };
+                    sql.push_str(&format!("${}::{}", index + 1, bind.sql_type()));
+                    tokens.push(Token::Value);
+                }
+                '-' if chars.peek() == Some(&'-') => {
+                    return Err(TemplateError::Forbidden("--".to_owned()));
+                }
+                '/' if chars.peek() == Some(&'*') => {
+                    return Err(TemplateError::Forbidden("/*".to_owned()));
+                }
+                ';' | '$' | '"' | '\\' => return Err(TemplateError::Forbidden(c.to_string())),
+                c if c.is_alphanumeric() || c == '_' => {
+                    let mut word = String::from(c);
+                    while let Some(c) =
+                        chars.next_if(|c| c.is_alphanumeric() || matches!(c, '_' | '.'))
+                    {
+                        word.push(c);
+                    }
+                    sql.push_str(&word);
+                    tokens.push(Token::Word(word.to_lowercase()));
+                }
+                c => {
+                    sql.push(c);
+                    if !c.is_whitespace() {
+                        tokens.push(Token::Symbol(c));
+                    }
+                }
+            }
+        }
+
+        check(&tokens)?;
+
+        Ok(Self { sql, binds })
+    }
+
+    /// The query, with `$n` in place of the placeholders
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// What to bind to `$1`, `$2` and so on
+    pub fn binds(&self) -> &[Bind] {
+        &self.binds
+    }
+}
+
+fn check(tokens: &[Token]) -> Result<(), TemplateError> {
+    match tokens.first() {
+        None => return Err(TemplateError::Empty),
+        Some(Token::Word(word)) if word == "select" || word == "with" => {}
+        Some(_) => return Err(TemplateError::NotSelect),
+    }
+
+    for token in tokens {
+        if let Token::Word(word) = token {
+            for part in word.split('.') {
+                if FORBIDDEN.contains(&part) || part.starts_with("pg_") || part.starts_with("lo_") {
+                    return Err(TemplateError::Forbidden(part.to_owned()));
+                }
+            }
+        }
+    }
+
+    // tables, the names of common table expressions and the aliases of either
+    let mut relations: HashSet<&str> = TABLES.iter().copied().collect();
+    for window in tokens.windows(3) {
+        if let [Token::Word(name), Token::Word(as_), Token::Symbol('(')] = window
+            && as_ == "as"
+        {
+            relations.insert(name);
+        }
+    }
+    let mut aliases = Vec::new();
+    for (i, window) in tokens.windows(2).enumerate() {
+        if let [before, Token::Word(name)] = window
+            && (matches!(before, Token::Word(word) if word == "from" || word == "join")
+                || *before == Token::Symbol(','))
+            && relations.contains(name.as_str())
+            && let Some(alias) = alias(tokens, skip_parens(tokens, i + 2))
+        {
+            aliases.push(alias);
+        }
+    }
+    relations.extend(aliases);
+
+    for (i, token) in tokens.iter().enumerate() {
+        if matches!(token, Token::Word(word) if word == "from" || word == "join") {
+            check_from(tokens, i + 1, &relations)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The alias given to the relation ending before `i`, if there is one
+fn alias(tokens: &[Token], mut i: usize) -> Option<&str> {
+    if matches!(tokens.get(i), Some(Token::Word(word)) if word == "as") {
+        i += 1;
+    }
+
+    match tokens.get(i) {
+        Some(Token::Word(word)) if !RESERVED.contains(&word.as_str()) && !word.contains('.') => {
+            Some(word)
+        }
+        _ => None,
+    }
+}
+
+/// The index after the parenthesised group starting at `i`, or `i` when there is none
+fn skip_parens(tokens: &[Token], mut i: usize) -> usize {
+    if tokens.get(i) != Some(&Token::Symbol('(')) {
+        return i;
+    }
+
+    let mut depth = 0;
+    for token in &tokens[i..] {
+        i += 1;
+        match token {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    i
+}
+
+/// Checks the comma separated relations starting at `i`
+fn check_from(
+    tokens: &[Token],
+    mut i: usize,
+    relations: &HashSet<&str>,
+) -> Result<(), TemplateError> {
+    loop {
+        match tokens.get(i) {
+            Some(Token::Word(word)) => {
+                let allowed = match word.split_once('.') {
+                    // a column, as in `extract(epoch from tr.cre_dt_tm)`
+                    Some((qualifier, _)) => relations.contains(qualifier),
+                    None => {
+                        relations.contains(word.as_str())
+                            || word.starts_with(|c: char| c.is_ascii_digit())
+                    }
+                };
+                if !allowed {
+                    return Err(TemplateError::Table(word.to_owned()));
+                }
+                // the arguments of a function such as `transaction_window`
+                i = skip_parens(tokens, i + 1);
+            }
+            // the relations of a subquery are checked on their own
+            Some(Token::Symbol('(')) => i = skip_parens(tokens, i),
+            _ => return Ok(()),
+        }
+
+        if matches!(tokens.get(i), Some(Token::Word(word)) if word == "as") {
+            i += 1;
+        }
+        if alias(tokens, i).is_some() {
+            i += 1;
+        }
+
+        match tokens.get(i) {
+            Some(Token::Symbol(',')) => i += 1,
+            _ => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binds_placeholders() {
+        let template = QueryTemplate::parse(
+            "select count(*) from transaction_relationship tr
+               where tr.destination = {debtor_account}
+                 and tr.tx_sts = 'ACCC'
+                 and extract(epoch from ({cre_dt_tm} - tr.cre_dt_tm)) * 1000 <= {max_query_range}
+                 and tr.cre_dt_tm <= {cre_dt_tm}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            template.binds(),
+            [Bind::DebtorAccount, Bind::CreDtTm, Bind::MaxQueryRange]
+        );
+        assert!(template.sql().contains("tr.destination = $1::text"));
+        assert!(template.sql().contains("($2::timestamptz - tr.cre_dt_tm)"));
+        assert!(template.sql().contains("<= $3::float8"));
+        assert!(template.sql().contains("tr.cre_dt_tm <= $2::timestamptz"));
+    }
+
+    #[test]
+    fn allows_whitelisted_tables() {
+        for query in [
+            "select count(*) from transaction_relationship",
+            "select count(*) from transaction_relationship as tr join account_holder ah on ah.source = tr.source",
+            "select count(*) from transaction_relationship tr, account_holder ah where ah.destination = tr.source",
+            "with recent as (select * from transaction_relationship) select count(*) from recent",
+            "select count(*) from (select * from account_holder) holders",
+            "select max(extract(epoch from tr.cre_dt_tm)) from transaction_relationship tr",
+            "select sum(w.count) from transaction_window({debtor_account}, 'destination', {tx_tp}, {cre_dt_tm} - interval '1 day', {cre_dt_tm}) w where w.tx_sts = 'ACCC'",
+        ] {
+            assert!(QueryTemplate::parse(query).is_ok(), "{query}");
+        }
+    }
+
+    #[test]
+    fn rejects_other_tables() {
+        for (query, table) in [
+            ("select count(*) from account", "account"),
+            ("select count(*) from public.entity", "public.entity"),
+            (
+                "select count(*) from transaction_relationship tr, entity e",
+                "entity",
+            ),
+            (
+                "select count(*) from transaction_relationship tr join entity on true",
+                "entity",
+            ),
+            ("select count(*) from (select 1) t, account", "account"),
+            (
+                "select count(*) from transaction_window('a', 'source', 'pacs.002.001.12', now(), now()) w, entity",
+                "entity",
+            ),
+        ] {
+            assert_eq!(
+                QueryTemplate::parse(query),
+                Err(TemplateError::Table(table.to_owned())),
+                "{query}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_unsafe_queries() {
+        assert_eq!(QueryTemplate::parse("  "), Err(TemplateError::Empty));
+        assert_eq!(
+            QueryTemplate::parse("delete from account_holder"),
+            Err(TemplateError::NotSelect)
+        );
+        assert_eq!(
+            QueryTemplate::parse("select 1; drop table account"),
+            Err(TemplateError::Forbidden(";".to_owned()))
+        );
+        assert_eq!(
+            QueryTemplate::parse("select 1 -- comment"),
+            Err(TemplateError::Forbidden("--".to_owned()))
+        );
+        assert_eq!(
+            QueryTemplate::parse("select pg_sleep(10)"),
+            Err(TemplateError::Forbidden("pg_sleep".to_owned()))
+        );
+        assert_eq!(
+            QueryTemplate::parse("select * into copied from account_holder"),
+            Err(TemplateError::Forbidden("into".to_owned()))
+        );
+        assert_eq!(
+            QueryTemplate::parse("select $1"),
+            Err(TemplateError::Forbidden("$".to_owned()))
+        );
+        assert_eq!(
+            QueryTemplate::parse("select e'\\''"),
+            Err(TemplateError::Forbidden("\\".to_owned()))
+        );
+        assert_eq!(
+            QueryTemplate::parse("select 'from account"),
+            Err(TemplateError::UnterminatedString)
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_binds() {
+        assert_eq!(
+            QueryTemplate::parse("select {password}"),
+            Err(TemplateError::UnknownBind("password".to_owned()))
+        );
+        assert_eq!(
+            QueryTemplate::parse("select {debtor"),
+            Err(TemplateError::UnclosedBind)
+        );
+    }
+
+    #[test]
+    fn ignores_string_contents() {
+        let template = QueryTemplate::parse(
+            "select count(*) from account_holder where source <> '{debtor}; from x'",
+        )
+        .unwrap();
+
+        assert!(template.binds().is_empty());
+    }
+
+    #[test]
+    fn requires_bound_parameters() {
+        let parameters = serde_json::json!({
+            "query": "select count(*) from transaction_relationship tr where extract(epoch from ({cre_dt_tm} - tr.cre_dt_tm)) * 1000 <= {max_query_range}",
+        });
+        let rule = QueryRule::from_parameters(parameters).unwrap();
+        assert!(matches!(
+            rule.template(&[]),
+            Err(TemplateError::Parameters(_))
+        ));
+        assert!(
+            rule.template(&[Timeframe {
+                threshold: 3600000.0
+            }])
+            .is_ok()
+        );
+
+        let parameters = serde_json::json!({
+            "query": "select count(*) from transaction_relationship tr where extract(epoch from ({cre_dt_tm} - tr.cre_dt_tm)) * 1000 <= {max_query_range}",
+            "max_query_range": 86400000,
+        });
+        let rule = QueryRule::from_parameters(parameters).unwrap();
+        assert!(rule.template(&[]).is_ok());
+    }
+}
@@ -1,7 +1,14 @@
+pub mod validate;
+
+#[cfg(feature = "xml")]
+pub mod xml;
+
 #[derive(Debug)]
 pub enum TransactionType {
     PACS008,
     PACS002,
+    PAIN001,
+    PAIN013,
 }
 
 impl std::fmt::Display for TransactionType {
@@ -12,6 +19,8 @@ impl std::fmt::Display for TransactionType {
             match self {
                 TransactionType::PACS002 => "pacs.002.001.12",
                 TransactionType::PACS008 => "pacs.008.001.12",
+                TransactionType::PAIN001 => "pain.001.001.12",
+                TransactionType::PAIN013 => "pain.013.001.11",
             }
         )
     }
@@ -27,6 +36,16 @@ pub mod pacs002 {
     tonic::include_proto!("iso20022.pacs002");
 }
 
+/// pain.001.001.12
+pub mod pain001 {
+    tonic::include_proto!("iso20022.pain001");
+}
+
+/// pain.013.001.11
+pub mod pain013 {
+    tonic::include_proto!("iso20022.pain013");
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,4 +61,16 @@ mod tests {
         let t = TransactionType::PACS002;
         assert_eq!(t.to_string(), "pacs.002.001.12");
     }
+
+    #[test]
+    fn display_pain001() {
+        let t = TransactionType::PAIN001;
+        assert_eq!(t.to_string(), "pain.001.001.12");
+    }
+
+    #[test]
+    fn display_pain013() {
+        let t = TransactionType::PAIN013;
+        assert_eq!(t.to_string(), "pain.013.001.11");
+    }
 }
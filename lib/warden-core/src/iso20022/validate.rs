@@ -0,0 +1,887 @@
+//! Business validation of ISO 20022 documents
+//!
+//! Enforces the facets the XSDs place on the elements warden relies on: text lengths,
+//! identifier patterns (IBAN, BIC, LEI, UETR), currency codes, amounts and the status code
+//! lists. Every violation is reported with the JSON path of the offending element.
+
+use std::{collections::HashSet, fmt::Write, sync::LazyLock};
+
+use crate::iso20022::{pacs002, pacs008, pain001, pain013};
+
+/// `ExternalPaymentTransactionStatus1Code`
+const TRANSACTION_STATUS: &[&str] = &[
+    "ACCC", "ACCP", "ACFC", "ACIS", "ACPD", "ACSC", "ACSP", "ACTC", "ACWC", "ACWP", "BLCK", "CANC",
+    "CPUC", "PATC", "PDNG", "PRES", "RCVD", "RJCT",
+];
+
+/// `ExternalPaymentGroupStatus1Code`
+const GROUP_STATUS: &[&str] = &[
+    "ACCC", "ACCP", "ACSC", "ACSP", "ACTC", "ACWC", "ACWP", "PART", "PDNG", "RCVD", "RJCT",
+];
+
+/// `ExternalStatusReason1Code`, read from the published list so that it can be replaced as the
+/// code sets are revised
+static STATUS_REASON: LazyLock<HashSet<&str>> = LazyLock::new(|| {
+    include_str!("external_status_reason.txt")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_whitespace().next())
+        .collect()
+});
+
+/// An element that does not satisfy its facets
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Violation {
+    /// JSON path of the element
+    #[cfg_attr(
+        feature = "openapi",
+        schema(example = "$.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0].instd_amt.ccy")
+    )]
+    pub path: String,
+    /// Facet that was violated
+    #[cfg_attr(feature = "openapi", schema(example = "must match [A-Z]{3}"))]
+    pub message: String,
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// A document that can be checked against its ISO 20022 facets
+pub trait Validate {
+    /// Reports every violation found in this element to the [Validator]
+    fn visit(&self, validator: &mut Validator);
+
+    /// Checks the whole element, returning every violation
+    fn validate(&self) -> Result<(), Vec<Violation>> {
+        let mut validator = Validator::default();
+        self.visit(&mut validator);
+        validator.finish()
+    }
+}
+
+/// Collects violations while walking a document
+#[derive(Debug)]
+pub struct Validator {
+    path: String,
+    violations: Vec<Violation>,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Self {
+            path: String::from("$"),
+            violations: Vec::new(),
+        }
+    }
+}
+
+impl Validator {
+    /// Returns the violations collected so far
+    pub fn finish(self) -> Result<(), Vec<Violation>> {
+        if self.violations.is_empty() {
+            Ok(())
+        } else {
+            Err(self.violations)
+        }
+    }
+
+    fn violation(&mut self, name: &str, message: impl Into<String>) {
+        self.violations.push(Violation {
+            path: format!("{}.{name}", self.path),
+            message: message.into(),
+        });
+    }
+
+    fn scoped(&mut self, segment: std::fmt::Arguments<'_>, f: impl FnOnce(&mut Self)) {
+        let len = self.path.len();
+        let _ = self.path.write_fmt(segment);
+        f(self);
+        self.path.truncate(len);
+    }
+
+    fn nested<T: Validate>(&mut self, name: &str, value: &T) {
+        self.scoped(format_args!(".{name}"), |validator| value.visit(validator));
+    }
+
+    fn optional<T: Validate>(&mut self, name: &str, value: Option<&T>) {
+        if let Some(value) = value {
+            self.nested(name, value);
+        }
+    }
+
+    fn each<T: Validate>(&mut self, name: &str, values: &[T]) {
+        for (i, value) in values.iter().enumerate() {
+            self.scoped(format_args!(".{name}[{i}]"), |validator| {
+                value.visit(validator)
+            });
+        }
+    }
+
+    /// `MaxNText` with a minimum length of one
+    fn text(&mut self, name: &str, value: &str, max: usize) {
+        let len = value.chars().count();
+        if len == 0 {
+            self.violation(name, "must not be empty");
+        } else if len > max {
+            self.violation(name, format!("must be at most {max} characters"));
+        }
+    }
+
+    fn optional_text(&mut self, name: &str, value: Option<&str>, max: usize) {
+        if let Some(value) = value {
+            self.text(name, value, max);
+        }
+    }
+
+    /// `Max15NumericText`
+    fn numeric_text(&mut self, name: &str, value: &str) {
+        if value.is_empty() || value.len() > 15 || !value.bytes().all(|b| b.is_ascii_digit()) {
+            self.violation(name, "must match [0-9]{1,15}");
+        }
+    }
+
+    /// `ActiveOrHistoricCurrencyCode`
+    fn currency(&mut self, name: &str, value: &str) {
+        if value.len() != 3 || !value.bytes().all(|b| b.is_ascii_uppercase()) {
+            self.violation(name, "must match [A-Z]{3}");
+        }
+    }
+
+    /// `CountryCode`
+    fn country(&mut self, name: &str, value: Option<&str>) {
+        if let Some(value) = value
+            && (value.len() != 2 || !value.bytes().all(|b| b.is_ascii_uppercase()))
+        {
+            self.violation(name, "must match [A-Z]{2}");
+        }
+    }
+
+    /// `DecimalNumber` and friends
+    ///
+    /// Documents carry decimals as doubles, so `value` is checked as the decimal it was read from:
+    /// rounded to `fraction_digits`, that decimal reads back as exactly `value`. Results of
+    /// arithmetic such as `0.1 + 0.2` are not such a decimal, and are rejected
+    fn decimal(&mut self, name: &str, value: f64, total_digits: usize, fraction_digits: usize) {
+        if !value.is_finite() {
+            self.violation(name, "must be a finite number");
+            return;
+        }
+
+        let repr = format!("{:.fraction_digits$}", value.abs());
+        if repr.parse::<f64>() != Ok(value.abs()) {
+            self.violation(
+                name,
+                format!("must have at most {fraction_digits} fraction digits"),
+            );
+            return;
+        }
+
+        let (integer, fraction) = repr.split_once('.').unwrap_or((&repr, ""));
+        let integer = integer.trim_start_matches('0');
+        let fraction = fraction.trim_end_matches('0');
+
+        if integer.len() + fraction.len() > total_digits {
+            self.violation(name, format!("must have at most {total_digits} digits"));
+        }
+    }
+
+    /// `ActiveCurrencyAndAmount` and `ActiveOrHistoricCurrencyAndAmount`
+    fn amount(&mut self, ccy: &str, value: f64) {
+        self.currency("ccy", ccy);
+        if value < 0.0 {
+            self.violation("value", "must not be negative");
+        } else {
+            self.decimal("value", value, 18, 5);
+        }
+    }
+
+    /// `BaseOneRate`, which must be positive when given
+    fn rate(&mut self, name: &str, value: Option<f64>) {
+        if let Some(value) = value {
+            if value <= 0.0 {
+                self.violation(name, "must be positive");
+            } else {
+                self.decimal(name, value, 11, 10);
+            }
+        }
+    }
+
+    /// `BICFIDec2014Identifier` and `AnyBICDec2014Identifier`
+    fn bic(&mut self, name: &str, value: Option<&str>) {
+        let Some(value) = value else {
+            return;
+        };
+
+        let bytes = value.as_bytes();
+        let valid = matches!(bytes.len(), 8 | 11)
+            && bytes[..4]
+                .iter()
+                .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+            && bytes[4..6].iter().all(u8::is_ascii_uppercase)
+            && bytes[6..]
+                .iter()
+                .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit());
+
+        if !valid {
+            self.violation(
+                name,
+                "must match [A-Z0-9]{4}[A-Z]{2}[A-Z0-9]{2}([A-Z0-9]{3})?",
+            );
+        }
+    }
+
+    /// `LEIIdentifier`
+    fn lei(&mut self, name: &str, value: Option<&str>) {
+        let Some(value) = value else {
+            return;
+        };
+
+        let bytes = value.as_bytes();
+        let valid = bytes.len() == 20
+            && bytes[..18]
+                .iter()
+                .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+            && bytes[18..].iter().all(u8::is_ascii_digit);
+
+        if !valid {
+            self.violation(name, "must match [A-Z0-9]{18}[0-9]{2}");
+        }
+    }
+
+    /// `IBAN2007Identifier`, including the ISO 7064 check digits
+    fn iban(&mut self, name: &str, value: &str) {
+        let bytes = value.as_bytes();
+        let valid = (5..=34).contains(&bytes.len())
+            && bytes[..2].iter().all(u8::is_ascii_uppercase)
+            && bytes[2..4].iter().all(u8::is_ascii_digit)
+            && bytes[4..].iter().all(u8::is_ascii_alphanumeric);
+
+        if !valid {
+            self.violation(name, "must match [A-Z]{2}[0-9]{2}[a-zA-Z0-9]{1,30}");
+            return;
+        }
+
+        let remainder = bytes[4..].iter().chain(&bytes[..4]).fold(0_u32, |acc, b| {
+            match b.to_ascii_uppercase() {
+                digit @ b'0'..=b'9' => (acc * 10 + u32::from(digit - b'0')) % 97,
+                letter => (acc * 100 + u32::from(letter - b'A' + 10)) % 97,
+            }
+        });
+
+        if remainder != 1 {
+            self.violation(name, "has invalid check digits");
+        }
+    }
+
+    /// `UUIDv4Identifier`
+    fn uetr(&mut self, name: &str, value: Option<&str>) {
+        let Some(value) = value else {
+            return;
+        };
+
+        let groups: Vec<&str> = value.split('-').collect();
+        let hex = |group: &str| {
+            group
+                .bytes()
+                .all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+        };
+        let valid = groups.len() == 5
+            && [8, 4, 4, 4, 12]
+                .iter()
+                .zip(&groups)
+                .all(|(len, group)| group.len() == *len && hex(group))
+            && groups[2].starts_with('4')
+            && groups[3].starts_with(['8', '9', 'a', 'b']);
+
+        if !valid {
+            self.violation(name, "must be a lower case version 4 UUID");
+        }
+    }
+
+    /// A value from a closed code list
+    fn code(&mut self, name: &str, value: Option<&str>, codes: &[&str]) {
+        if let Some(value) = value
+            && !codes.contains(&value)
+        {
+            self.violation(name, format!("must be one of {}", codes.join(", ")));
+        }
+    }
+
+    /// `ExternalStatusReason1Code`
+    fn status_reason(&mut self, name: &str, value: &str) {
+        if !STATUS_REASON.contains(value) {
+            self.violation(name, "must be an ExternalStatusReason1Code");
+        }
+    }
+}
+
+/// Elements shared by pacs.008, pain.001 and pain.013
+macro_rules! shared {
+    ($pkg:ident) => {
+        impl Validate for $pkg::ActiveOrHistoricCurrencyAndAmount {
+            fn visit(&self, validator: &mut Validator) {
+                validator.amount(&self.ccy, self.value);
+            }
+        }
+
+        impl Validate for $pkg::BranchAndFinancialInstitutionIdentification8 {
+            fn visit(&self, validator: &mut Validator) {
+                validator.nested("fin_instn_id", &self.fin_instn_id);
+            }
+        }
+
+        impl Validate for $pkg::FinancialInstitutionIdentification23 {
+            fn visit(&self, validator: &mut Validator) {
+                validator.bic("b_i_c_f_i", self.b_i_c_f_i.as_deref());
+                validator.lei("l_e_i", self.l_e_i.as_deref());
+                validator.optional_text("nm", self.nm.as_deref(), 140);
+                if let Some(clr_sys_mmb_id) = &self.clr_sys_mmb_id {
+                    validator.scoped(format_args!(".clr_sys_mmb_id"), |validator| {
+                        validator.text("mmb_id", &clr_sys_mmb_id.mmb_id, 35)
+                    });
+                }
+                if let Some(pstl_adr) = &self.pstl_adr {
+                    validator.scoped(format_args!(".pstl_adr"), |validator| {
+                        validator.country("ctry", pstl_adr.ctry.as_deref())
+                    });
+                }
+            }
+        }
+
+        impl Validate for $pkg::CashAccount40 {
+            fn visit(&self, validator: &mut Validator) {
+                if let Some(ccy) = &self.ccy {
+                    validator.currency("ccy", ccy);
+                }
+                validator.optional_text("nm", self.nm.as_deref(), 70);
+                validator.optional("id", self.id.as_ref());
+            }
+        }
+
+        impl Validate for $pkg::AccountIdentification4Choice {
+            fn visit(&self, validator: &mut Validator) {
+                // choices are generated with every arm required, an empty `othr` selects the IBAN
+                if self.othr.id.is_empty() {
+                    validator.iban("i_b_a_n", &self.i_b_a_n);
+                } else {
+                    validator.scoped(format_args!(".othr"), |validator| {
+                        validator.text("id", &self.othr.id, 34)
+                    });
+                }
+            }
+        }
+
+        impl Validate for $pkg::PartyIdentification272 {
+            fn visit(&self, validator: &mut Validator) {
+                validator.optional_text("nm", self.nm.as_deref(), 140);
+                validator.country("ctry_of_res", self.ctry_of_res.as_deref());
+                if let Some(pstl_adr) = &self.pstl_adr {
+                    validator.scoped(format_args!(".pstl_adr"), |validator| {
+                        validator.country("ctry", pstl_adr.ctry.as_deref())
+                    });
+                }
+                validator.optional("id", self.id.as_ref());
+            }
+        }
+
+        impl Validate for $pkg::Party52Choice {
+            fn visit(&self, validator: &mut Validator) {
+                validator.scoped(format_args!(".org_id"), |validator| {
+                    validator.bic("any_b_i_c", self.org_id.any_b_i_c.as_deref());
+                    validator.lei("l_e_i", self.org_id.l_e_i.as_deref());
+                    for (i, othr) in self.org_id.othr.iter().enumerate() {
+                        validator.scoped(format_args!(".othr[{i}]"), |validator| {
+                            validator.text("id", &othr.id, 256)
+                        });
+                    }
+                });
+                validator.scoped(format_args!(".prvt_id"), |validator| {
+                    for (i, othr) in self.prvt_id.othr.iter().enumerate() {
+                        validator.scoped(format_args!(".othr[{i}]"), |validator| {
+                            validator.text("id", &othr.id, 256)
+                        });
+                    }
+                });
+            }
+        }
+    };
+}
+
+shared!(pacs008);
+shared!(pain001);
+shared!(pain013);
+
+impl Validate for pacs008::ActiveCurrencyAndAmount {
+    fn visit(&self, validator: &mut Validator) {
+        validator.amount(&self.ccy, self.value);
+    }
+}
+
+impl Validate for pacs008::Pacs008Document {
+    fn visit(&self, validator: &mut Validator) {
+        validator.nested("f_i_to_f_i_cstmr_cdt_trf", &self.f_i_to_f_i_cstmr_cdt_trf);
+    }
+}
+
+impl Validate for pacs008::FiToFiCustomerCreditTransferV12 {
+    fn visit(&self, validator: &mut Validator) {
+        let grp_hdr = &self.grp_hdr;
+        validator.scoped(format_args!(".grp_hdr"), |validator| {
+            validator.text("msg_id", &grp_hdr.msg_id, 35);
+            validator.numeric_text("nb_of_txs", &grp_hdr.nb_of_txs);
+            if let Some(ctrl_sum) = grp_hdr.ctrl_sum {
+                validator.decimal("ctrl_sum", ctrl_sum, 18, 17);
+            }
+            validator.optional(
+                "ttl_intr_bk_sttlm_amt",
+                grp_hdr.ttl_intr_bk_sttlm_amt.as_ref(),
+            );
+            validator.optional("instg_agt", grp_hdr.instg_agt.as_ref());
+            validator.optional("instd_agt", grp_hdr.instd_agt.as_ref());
+        });
+        validator.each("cdt_trf_tx_inf", &self.cdt_trf_tx_inf);
+    }
+}
+
+impl Validate for pacs008::CreditTransferTransaction64 {
+    fn visit(&self, validator: &mut Validator) {
+        let pmt_id = &self.pmt_id;
+        validator.scoped(format_args!(".pmt_id"), |validator| {
+            validator.optional_text("instr_id", pmt_id.instr_id.as_deref(), 35);
+            validator.text("end_to_end_id", &pmt_id.end_to_end_id, 35);
+            validator.optional_text("tx_id", pmt_id.tx_id.as_deref(), 35);
+            validator.uetr("u_e_t_r", pmt_id.u_e_t_r.as_deref());
+            validator.optional_text("clr_sys_ref", pmt_id.clr_sys_ref.as_deref(), 35);
+        });
+
+        validator.optional("intr_bk_sttlm_amt", self.intr_bk_sttlm_amt.as_ref());
+        validator.optional("instd_amt", self.instd_amt.as_ref());
+        validator.rate("xchg_rate", self.xchg_rate);
+
+        for (i, chrgs_inf) in self.chrgs_inf.iter().enumerate() {
+            validator.scoped(format_args!(".chrgs_inf[{i}]"), |validator| {
+                validator.nested("amt", &chrgs_inf.amt);
+                validator.nested("agt", &chrgs_inf.agt);
+            });
+        }
+
+        for (name, agent) in [
+            ("prvs_instg_agt1", &self.prvs_instg_agt1),
+            ("prvs_instg_agt2", &self.prvs_instg_agt2),
+            ("prvs_instg_agt3", &self.prvs_instg_agt3),
+            ("instg_agt", &self.instg_agt),
+            ("instd_agt", &self.instd_agt),
+            ("intrmy_agt1", &self.intrmy_agt1),
+            ("intrmy_agt2", &self.intrmy_agt2),
+            ("intrmy_agt3", &self.intrmy_agt3),
+            ("dbtr_agt", &self.dbtr_agt),
+            ("cdtr_agt", &self.cdtr_agt),
+        ] {
+            validator.optional(name, agent.as_ref());
+        }
+
+        for (name, account) in [
+            ("dbtr_acct", &self.dbtr_acct),
+            ("dbtr_agt_acct", &self.dbtr_agt_acct),
+            ("cdtr_agt_acct", &self.cdtr_agt_acct),
+            ("cdtr_acct", &self.cdtr_acct),
+        ] {
+            validator.optional(name, account.as_ref());
+        }
+
+        validator.nested("dbtr", &self.dbtr);
+        for (name, party) in [
+            ("ultmt_dbtr", &self.ultmt_dbtr),
+            ("initg_pty", &self.initg_pty),
+            ("cdtr", &self.cdtr),
+            ("ultmt_cdtr", &self.ultmt_cdtr),
+        ] {
+            validator.optional(name, party.as_ref());
+        }
+    }
+}
+
+impl Validate for pacs002::Pacs002Document {
+    fn visit(&self, validator: &mut Validator) {
+        validator.nested("f_i_to_f_i_pmt_sts_rpt", &self.f_i_to_f_i_pmt_sts_rpt);
+    }
+}
+
+impl Validate for pacs002::FiToFiPaymentStatusReportV12 {
+    fn visit(&self, validator: &mut Validator) {
+        let grp_hdr = &self.grp_hdr;
+        validator.scoped(format_args!(".grp_hdr"), |validator| {
+            validator.text("msg_id", &grp_hdr.msg_id, 35);
+            validator.optional("instg_agt", grp_hdr.instg_agt.as_ref());
+            validator.optional("instd_agt", grp_hdr.instd_agt.as_ref());
+        });
+        validator.each("orgnl_grp_inf_and_sts", &self.orgnl_grp_inf_and_sts);
+        validator.each("tx_inf_and_sts", &self.tx_inf_and_sts);
+    }
+}
+
+impl Validate for pacs002::OriginalGroupHeader17 {
+    fn visit(&self, validator: &mut Validator) {
+        validator.text("orgnl_msg_id", &self.orgnl_msg_id, 35);
+        validator.text("orgnl_msg_nm_id", &self.orgnl_msg_nm_id, 35);
+        if let Some(orgnl_nb_of_txs) = &self.orgnl_nb_of_txs {
+            validator.numeric_text("orgnl_nb_of_txs", orgnl_nb_of_txs);
+        }
+        if let Some(orgnl_ctrl_sum) = self.orgnl_ctrl_sum {
+            validator.decimal("orgnl_ctrl_sum", orgnl_ctrl_sum, 18, 17);
+        }
+        validator.code("grp_sts", self.grp_sts.as_deref(), GROUP_STATUS);
+        validator.each("sts_rsn_inf", &self.sts_rsn_inf);
+
+        for (i, nb_of_txs_per_sts) in self.nb_of_txs_per_sts.iter().enumerate() {
+            validator.scoped(format_args!(".nb_of_txs_per_sts[{i}]"), |validator| {
+                validator.numeric_text("dtld_nb_of_txs", &nb_of_txs_per_sts.dtld_nb_of_txs);
+                validator.code(
+                    "dtld_sts",
+                    Some(&nb_of_txs_per_sts.dtld_sts),
+                    TRANSACTION_STATUS,
+                );
+                if let Some(dtld_ctrl_sum) = nb_of_txs_per_sts.dtld_ctrl_sum {
+                    validator.decimal("dtld_ctrl_sum", dtld_ctrl_sum, 18, 17);
+                }
+            });
+        }
+    }
+}
+
+impl Validate for pacs002::PaymentTransaction130 {
+    fn visit(&self, validator: &mut Validator) {
+        validator.optional_text("sts_id", self.sts_id.as_deref(), 35);
+        validator.optional_text("orgnl_instr_id", self.orgnl_instr_id.as_deref(), 35);
+        validator.optional_text(
+            "orgnl_end_to_end_id",
+            self.orgnl_end_to_end_id.as_deref(),
+            35,
+        );
+        validator.optional_text("orgnl_tx_id", self.orgnl_tx_id.as_deref(), 35);
+        validator.uetr("orgnl_u_e_t_r", self.orgnl_u_e_t_r.as_deref());
+        validator.code("tx_sts", self.tx_sts.as_deref(), TRANSACTION_STATUS);
+        validator.each("sts_rsn_inf", &self.sts_rsn_inf);
+
+        for (i, chrgs_inf) in self.chrgs_inf.iter().enumerate() {
+            validator.scoped(format_args!(".chrgs_inf[{i}]"), |validator| {
+                validator.scoped(format_args!(".amt"), |validator| {
+                    validator.amount(&chrgs_inf.amt.ccy, chrgs_inf.amt.value)
+                });
+                validator.nested("agt", &chrgs_inf.agt);
+            });
+        }
+
+        validator.optional("instg_agt", self.instg_agt.as_ref());
+        validator.optional("instd_agt", self.instd_agt.as_ref());
+    }
+}
+
+impl Validate for pacs002::StatusReasonInformation12 {
+    fn visit(&self, validator: &mut Validator) {
+        if let Some(rsn) = &self.rsn {
+            validator.scoped(format_args!(".rsn"), |validator| {
+                // choices are generated with every arm required, an empty `cd` selects `prtry`
+                if rsn.cd.is_empty() {
+                    validator.text("prtry", &rsn.prtry, 35);
+                } else {
+                    validator.status_reason("cd", &rsn.cd);
+                }
+            });
+        }
+        for (i, addtl_inf) in self.addtl_inf.iter().enumerate() {
+            validator.text(&format!("addtl_inf[{i}]"), addtl_inf, 105);
+        }
+    }
+}
+
+impl Validate for pacs002::BranchAndFinancialInstitutionIdentification6 {
+    fn visit(&self, validator: &mut Validator) {
+        let fin_instn_id = &self.fin_instn_id;
+        validator.scoped(format_args!(".fin_instn_id"), |validator| {
+            validator.bic("b_i_c_f_i", fin_instn_id.b_i_c_f_i.as_deref());
+            validator.lei("l_e_i", fin_instn_id.l_e_i.as_deref());
+            validator.optional_text("nm", fin_instn_id.nm.as_deref(), 140);
+            if let Some(clr_sys_mmb_id) = &fin_instn_id.clr_sys_mmb_id {
+                validator.scoped(format_args!(".clr_sys_mmb_id"), |validator| {
+                    validator.text("mmb_id", &clr_sys_mmb_id.mmb_id, 35)
+                });
+            }
+        });
+    }
+}
+
+impl Validate for pain001::Pain001Document {
+    fn visit(&self, validator: &mut Validator) {
+        validator.nested("cstmr_cdt_trf_initn", &self.cstmr_cdt_trf_initn);
+    }
+}
+
+impl Validate for pain001::CustomerCreditTransferInitiationV12 {
+    fn visit(&self, validator: &mut Validator) {
+        let grp_hdr = &self.grp_hdr;
+        validator.scoped(format_args!(".grp_hdr"), |validator| {
+            validator.text("msg_id", &grp_hdr.msg_id, 35);
+            validator.numeric_text("nb_of_txs", &grp_hdr.nb_of_txs);
+            if let Some(ctrl_sum) = grp_hdr.ctrl_sum {
+                validator.decimal("ctrl_sum", ctrl_sum, 18, 17);
+            }
+            validator.nested("initg_pty", &grp_hdr.initg_pty);
+            validator.optional("fwdg_agt", grp_hdr.fwdg_agt.as_ref());
+        });
+        validator.each("pmt_inf", &self.pmt_inf);
+    }
+}
+
+impl Validate for pain001::PaymentInstruction44 {
+    fn visit(&self, validator: &mut Validator) {
+        validator.text("pmt_inf_id", &self.pmt_inf_id, 35);
+        if let Some(nb_of_txs) = &self.nb_of_txs {
+            validator.numeric_text("nb_of_txs", nb_of_txs);
+        }
+        if let Some(ctrl_sum) = self.ctrl_sum {
+            validator.decimal("ctrl_sum", ctrl_sum, 18, 17);
+        }
+        validator.nested("dbtr", &self.dbtr);
+        validator.nested("dbtr_acct", &self.dbtr_acct);
+        validator.nested("dbtr_agt", &self.dbtr_agt);
+        validator.optional("dbtr_agt_acct", self.dbtr_agt_acct.as_ref());
+        validator.optional("ultmt_dbtr", self.ultmt_dbtr.as_ref());
+        validator.optional("chrgs_acct", self.chrgs_acct.as_ref());
+        validator.optional("chrgs_acct_agt", self.chrgs_acct_agt.as_ref());
+        validator.each("cdt_trf_tx_inf", &self.cdt_trf_tx_inf);
+    }
+}
+
+impl Validate for pain001::CreditTransferTransaction61 {
+    fn visit(&self, validator: &mut Validator) {
+        let pmt_id = &self.pmt_id;
+        validator.scoped(format_args!(".pmt_id"), |validator| {
+            validator.optional_text("instr_id", pmt_id.instr_id.as_deref(), 35);
+            validator.text("end_to_end_id", &pmt_id.end_to_end_id, 35);
+            validator.uetr("u_e_t_r", pmt_id.u_e_t_r.as_deref());
+        });
+
+        validator.scoped(format_args!(".amt"), |validator| {
+            validator.optional("instd_amt", self.amt.instd_amt.as_ref());
+        });
+
+        if let Some(xchg_rate_inf) = &self.xchg_rate_inf {
+            validator.scoped(format_args!(".xchg_rate_inf"), |validator| {
+                if let Some(unit_ccy) = &xchg_rate_inf.unit_ccy {
+                    validator.currency("unit_ccy", unit_ccy);
+                }
+                validator.rate("xchg_rate", xchg_rate_inf.xchg_rate);
+            });
+        }
+
+        for (name, agent) in [
+            ("intrmy_agt1", &self.intrmy_agt1),
+            ("intrmy_agt2", &self.intrmy_agt2),
+            ("intrmy_agt3", &self.intrmy_agt3),
+            ("cdtr_agt", &self.cdtr_agt),
+        ] {
+            validator.optional(name, agent.as_ref());
+        }
+
+        for (name, account) in [
+            ("intrmy_agt1_acct", &self.intrmy_agt1_acct),
+            ("intrmy_agt2_acct", &self.intrmy_agt2_acct),
+            ("intrmy_agt3_acct", &self.intrmy_agt3_acct),
+            ("cdtr_agt_acct", &self.cdtr_agt_acct),
+            ("cdtr_acct", &self.cdtr_acct),
+        ] {
+            validator.optional(name, account.as_ref());
+        }
+
+        for (name, party) in [
+            ("ultmt_dbtr", &self.ultmt_dbtr),
+            ("cdtr", &self.cdtr),
+            ("ultmt_cdtr", &self.ultmt_cdtr),
+        ] {
+            validator.optional(name, party.as_ref());
+        }
+    }
+}
+
+impl Validate for pain013::Pain013Document {
+    fn visit(&self, validator: &mut Validator) {
+        validator.nested("cdtr_pmt_actvtn_req", &self.cdtr_pmt_actvtn_req);
+    }
+}
+
+impl Validate for pain013::CreditorPaymentActivationRequestV11 {
+    fn visit(&self, validator: &mut Validator) {
+        let grp_hdr = &self.grp_hdr;
+        validator.scoped(format_args!(".grp_hdr"), |validator| {
+            validator.text("msg_id", &grp_hdr.msg_id, 35);
+            validator.numeric_text("nb_of_txs", &grp_hdr.nb_of_txs);
+            if let Some(ctrl_sum) = grp_hdr.ctrl_sum {
+                validator.decimal("ctrl_sum", ctrl_sum, 18, 17);
+            }
+            validator.nested("initg_pty", &grp_hdr.initg_pty);
+        });
+        validator.each("pmt_inf", &self.pmt_inf);
+    }
+}
+
+impl Validate for pain013::PaymentInstruction42 {
+    fn visit(&self, validator: &mut Validator) {
+        validator.optional_text("pmt_inf_id", self.pmt_inf_id.as_deref(), 35);
+        validator.nested("dbtr", &self.dbtr);
+        validator.optional("dbtr_acct", self.dbtr_acct.as_ref());
+        validator.nested("dbtr_agt", &self.dbtr_agt);
+        validator.optional("ultmt_dbtr", self.ultmt_dbtr.as_ref());
+        validator.each("cdt_trf_tx", &self.cdt_trf_tx);
+    }
+}
+
+impl Validate for pain013::CreditTransferTransaction60 {
+    fn visit(&self, validator: &mut Validator) {
+        let pmt_id = &self.pmt_id;
+        validator.scoped(format_args!(".pmt_id"), |validator| {
+            validator.optional_text("instr_id", pmt_id.instr_id.as_deref(), 35);
+            validator.text("end_to_end_id", &pmt_id.end_to_end_id, 35);
+            validator.uetr("u_e_t_r", pmt_id.u_e_t_r.as_deref());
+        });
+
+        validator.scoped(format_args!(".amt"), |validator| {
+            validator.optional("instd_amt", self.amt.instd_amt.as_ref());
+        });
+
+        validator.nested("cdtr_agt", &self.cdtr_agt);
+        validator.nested("cdtr", &self.cdtr);
+        validator.optional("cdtr_acct", self.cdtr_acct.as_ref());
+        validator.optional("ultmt_dbtr", self.ultmt_dbtr.as_ref());
+        validator.optional("ultmt_cdtr", self.ultmt_cdtr.as_ref());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(f: impl FnOnce(&mut Validator)) -> Vec<Violation> {
+        let mut validator = Validator::default();
+        f(&mut validator);
+        validator.finish().err().unwrap_or_default()
+    }
+
+    #[test]
+    fn validates_iban() {
+        assert!(check(|v| v.iban("i_b_a_n", "GB82WEST12345698765432")).is_empty());
+        assert!(check(|v| v.iban("i_b_a_n", "DE89370400440532013000")).is_empty());
+
+        let violations = check(|v| v.iban("i_b_a_n", "GB82WEST12345698765433"));
+        assert_eq!(violations[0].message, "has invalid check digits");
+
+        let violations = check(|v| v.iban("i_b_a_n", "value"));
+        assert_eq!(violations.len(), 1);
+    }
+
+    #[test]
+    fn validates_bic() {
+        assert!(check(|v| v.bic("b_i_c_f_i", Some("DEUTDEFF"))).is_empty());
+        assert!(check(|v| v.bic("b_i_c_f_i", Some("DEUTDEFF500"))).is_empty());
+        assert!(check(|v| v.bic("b_i_c_f_i", None)).is_empty());
+        assert_eq!(check(|v| v.bic("b_i_c_f_i", Some("BANKXXX"))).len(), 1);
+        assert_eq!(check(|v| v.bic("b_i_c_f_i", Some("DEUT1EFF"))).len(), 1);
+    }
+
+    #[test]
+    fn validates_amounts() {
+        assert!(check(|v| v.amount("XTS", 294.3)).is_empty());
+        assert!(check(|v| v.amount("XTS", 0.0)).is_empty());
+
+        let violations = check(|v| v.amount("XTS", -1.0));
+        assert_eq!(violations[0].path, "$.value");
+
+        let violations = check(|v| v.amount("XTS", 0.123456));
+        assert_eq!(violations[0].message, "must have at most 5 fraction digits");
+
+        // read from "0.3", unlike the sum
+        assert!(check(|v| v.amount("XTS", 0.3)).is_empty());
+        assert_eq!(check(|v| v.amount("XTS", 0.1 + 0.2)).len(), 1);
+        assert!(check(|v| v.amount("XTS", 12345678901.12345)).is_empty());
+        assert_eq!(check(|v| v.amount("XTS", 1e20)).len(), 1);
+
+        let violations = check(|v| v.amount("US", 10.0));
+        assert_eq!(violations[0].path, "$.ccy");
+    }
+
+    #[test]
+    fn validates_uetr() {
+        let uetr = "eb6305c9-1f7f-49de-aed0-16487c27b42d";
+        assert!(check(|v| v.uetr("u_e_t_r", Some(uetr))).is_empty());
+        assert_eq!(
+            check(|v| v.uetr("u_e_t_r", Some(&uetr.to_uppercase()))).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn validates_codes() {
+        assert!(check(|v| v.code("tx_sts", Some("ACCC"), TRANSACTION_STATUS)).is_empty());
+        assert_eq!(
+            check(|v| v.code("tx_sts", Some("DONE"), TRANSACTION_STATUS)).len(),
+            1
+        );
+        assert!(check(|v| v.status_reason("cd", "AC01")).is_empty());
+        assert!(check(|v| v.status_reason("cd", "DS0A")).is_empty());
+        assert_eq!(check(|v| v.status_reason("cd", "ZZ99")).len(), 1);
+        assert_eq!(check(|v| v.status_reason("cd", "ac01")).len(), 1);
+    }
+
+    #[test]
+    fn reads_status_reasons() {
+        assert!(STATUS_REASON.len() > 200);
+        assert!(STATUS_REASON.iter().all(|code| {
+            code.len() == 4
+                && code
+                    .bytes()
+                    .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        }));
+    }
+
+    #[test]
+    fn reports_every_violation_with_its_path() {
+        let document = pacs008::Pacs008Document {
+            f_i_to_f_i_cstmr_cdt_trf: pacs008::FiToFiCustomerCreditTransferV12 {
+                grp_hdr: pacs008::GroupHeader113 {
+                    msg_id: "m".repeat(36),
+                    nb_of_txs: "1".into(),
+                    ..Default::default()
+                },
+                cdt_trf_tx_inf: vec![pacs008::CreditTransferTransaction64 {
+                    pmt_id: pacs008::PaymentIdentification13 {
+                        end_to_end_id: "e2e".into(),
+                        ..Default::default()
+                    },
+                    instd_amt: Some(pacs008::ActiveOrHistoricCurrencyAndAmount {
+                        ccy: "XTSX".into(),
+                        value: -10.0,
+                    }),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        };
+
+        let violations = document.validate().unwrap_err();
+        let paths: Vec<&str> = violations.iter().map(|v| v.path.as_str()).collect();
+
+        assert_eq!(
+            paths,
+            [
+                "$.f_i_to_f_i_cstmr_cdt_trf.grp_hdr.msg_id",
+                "$.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0].instd_amt.ccy",
+                "$.f_i_to_f_i_cstmr_cdt_trf.cdt_trf_tx_inf[0].instd_amt.value",
+            ]
+        );
+    }
+}
@@ -0,0 +1,451 @@
+//! Parse canonical ISO 20022 XML into the generated message types
+//!
+//! Element and attribute names are mapped to protobuf fields by splitting them on upper case
+//! letters, e.g. `FIToFICstmrCdtTrf` becomes `f_i_to_f_i_cstmr_cdt_trf` and the `Ccy`
+//! attribute becomes `ccy`. Text content of an element that also has attributes goes to its
+//! `value` field. Code sets are matched against the suffix of the generated enum values.
+
+use std::{collections::HashMap, sync::LazyLock};
+
+use prost::Message;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, EnumDescriptor, FieldDescriptor, Kind, MessageDescriptor, Value,
+};
+use roxmltree::Node;
+
+use crate::{
+    google::{protobuf::Timestamp, r#type::Date},
+    iso20022::{TransactionType, pacs002, pacs008, pain001, pain013},
+};
+
+const NAMESPACE_PREFIX: &str = "urn:iso:std:iso:20022:tech:xsd:";
+
+static DESCRIPTOR_POOL: LazyLock<DescriptorPool> = LazyLock::new(|| {
+    DescriptorPool::decode(crate::FILE_DESCRIPTOR_SET).expect("file descriptor set is valid")
+});
+
+/// An ISO 20022 document that can be read from XML
+pub trait Document: Message + Default {
+    /// Fully qualified protobuf name of the document
+    const PROTO_NAME: &'static str;
+    /// Message definition identifier the XML namespace must carry
+    const TRANSACTION_TYPE: TransactionType;
+}
+
+macro_rules! document {
+    ($ty:ty, $name:literal, $tx_tp:expr) => {
+        impl Document for $ty {
+            const PROTO_NAME: &'static str = $name;
+            const TRANSACTION_TYPE: TransactionType = $tx_tp;
+        }
+    };
+}
+
+document!(
+    pacs008::Pacs008Document,
+    "iso20022.pacs008.Pacs008Document",
+    TransactionType::PACS008
+);
+document!(
+    pacs002::Pacs002Document,
+    "iso20022.pacs002.Pacs002Document",
+    TransactionType::PACS002
+);
+document!(
+    pain001::Pain001Document,
+    "iso20022.pain001.Pain001Document",
+    TransactionType::PAIN001
+);
+document!(
+    pain013::Pain013Document,
+    "iso20022.pain013.Pain013Document",
+    TransactionType::PAIN013
+);
+
+/// A document could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlError {
+    path: String,
+    line: u32,
+    column: u32,
+    reason: String,
+}
+
+impl XmlError {
+    /// Location of the offending element, e.g. `/Document/FIToFICstmrCdtTrf/GrpHdr/CreDtTm`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Line of the offending element, starting at 1
+    pub fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Column of the offending element, starting at 1
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+
+    /// What went wrong
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl std::fmt::Display for XmlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(
+                f,
+                "{} (line {}, column {})",
+                self.reason, self.line, self.column
+            )
+        } else {
+            write!(
+                f,
+                "{} at {} (line {}, column {})",
+                self.reason, self.path, self.line, self.column
+            )
+        }
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+/// Reads a document from UTF-8 encoded XML
+pub fn from_slice<T: Document>(xml: &[u8]) -> Result<T, XmlError> {
+    let xml = std::str::from_utf8(xml).map_err(|e| XmlError {
+        path: String::new(),
+        line: 1,
+        column: 1,
+        reason: format!("document is not valid utf-8: {e}"),
+    })?;
+
+    from_str(xml)
+}
+
+/// Reads a document from XML
+pub fn from_str<T: Document>(xml: &str) -> Result<T, XmlError> {
+    let document = roxmltree::Document::parse(xml).map_err(|e| {
+        let pos = e.pos();
+        XmlError {
+            path: String::new(),
+            line: pos.row,
+            column: pos.col,
+            reason: e.to_string(),
+        }
+    })?;
+    let reader = Reader {
+        document: &document,
+    };
+
+    let root = document.root_element();
+    let path = format!("/{}", root.tag_name().name());
+
+    if root.tag_name().name() != "Document" {
+        return Err(reader.error(root, &path, "expected a Document element"));
+    }
+
+    let expected = format!("{NAMESPACE_PREFIX}{}", T::TRANSACTION_TYPE);
+    if root.tag_name().namespace() != Some(expected.as_str()) {
+        return Err(reader.error(root, &path, format!("expected namespace {expected}")));
+    }
+
+    let descriptor = DESCRIPTOR_POOL
+        .get_message_by_name(T::PROTO_NAME)
+        .expect("document is in the descriptor pool");
+
+    let message = reader.message(root, descriptor, &path)?;
+
+    T::decode(message.encode_to_vec().as_slice())
+        .map_err(|e| reader.error(root, &path, e.to_string()))
+}
+
+struct Reader<'a, 'input> {
+    document: &'a roxmltree::Document<'input>,
+}
+
+impl Reader<'_, '_> {
+    fn error(&self, node: Node, path: &str, reason: impl Into<String>) -> XmlError {
+        let pos = self.document.text_pos_at(node.range().start);
+        XmlError {
+            path: path.to_string(),
+            line: pos.row,
+            column: pos.col,
+            reason: reason.into(),
+        }
+    }
+
+    fn message(
+        &self,
+        node: Node,
+        descriptor: MessageDescriptor,
+        path: &str,
+    ) -> Result<DynamicMessage, XmlError> {
+        let mut message = DynamicMessage::new(descriptor.clone());
+
+        // qualified attributes such as xsi:schemaLocation are not part of the message
+        for attribute in node
+            .attributes()
+            .filter(|value| value.namespace().is_none())
+        {
+            let path = format!("{path}/@{}", attribute.name());
+            let field = descriptor
+                .get_field_by_name(&field_name(attribute.name()))
+                .ok_or_else(|| self.error(node, &path, "unknown attribute"))?;
+            let value = self.scalar(node, &field.kind(), attribute.value(), &path)?;
+            message.set_field(&field, value);
+        }
+
+        let mut seen: HashMap<u32, usize> = HashMap::new();
+        let mut has_children = false;
+
+        for child in node.children().filter(Node::is_element) {
+            has_children = true;
+            let name = child.tag_name().name();
+
+            let Some(field) = descriptor.get_field_by_name(&field_name(name)) else {
+                return Err(self.error(child, &format!("{path}/{name}"), "unknown element"));
+            };
+
+            let count = seen.entry(field.number()).or_default();
+            *count += 1;
+
+            let path = if field.is_list() {
+                format!("{path}/{name}[{count}]")
+            } else {
+                format!("{path}/{name}")
+            };
+
+            if !field.is_list() && *count > 1 {
+                return Err(self.error(child, &path, "element may only occur once"));
+            }
+
+            let value = self.value(child, &field, &path)?;
+
+            if field.is_list() {
+                message
+                    .get_field_mut(&field)
+                    .as_list_mut()
+                    .expect("repeated field is a list")
+                    .push(value);
+            } else {
+                message.set_field(&field, value);
+            }
+        }
+
+        let text = node.text().map(str::trim).unwrap_or_default();
+        if !has_children && !text.is_empty() {
+            // simple content, e.g. <InstdAmt Ccy="XTS">10</InstdAmt>
+            let field = descriptor
+                .get_field_by_name("value")
+                .ok_or_else(|| self.error(node, path, "unexpected text content"))?;
+            let value = self.scalar(node, &field.kind(), text, path)?;
+            message.set_field(&field, value);
+        }
+
+        Ok(message)
+    }
+
+    fn value(&self, node: Node, field: &FieldDescriptor, path: &str) -> Result<Value, XmlError> {
+        let text = node.text().map(str::trim).unwrap_or_default();
+
+        match field.kind() {
+            Kind::Message(descriptor) => match descriptor.full_name() {
+                "google.protobuf.Timestamp" => {
+                    let timestamp = parse_timestamp(text)
+                        .ok_or_else(|| self.error(node, path, "invalid date time"))?;
+                    self.well_known(node, descriptor, timestamp, path)
+                }
+                "google.type.Date" => {
+                    let date = text
+                        .parse::<Date>()
+                        .map_err(|_e| self.error(node, path, "invalid date"))?;
+                    self.well_known(node, descriptor, date, path)
+                }
+                _ => self.message(node, descriptor, path).map(Value::Message),
+            },
+            kind => self.scalar(node, &kind, text, path),
+        }
+    }
+
+    fn well_known(
+        &self,
+        node: Node,
+        descriptor: MessageDescriptor,
+        value: impl Message,
+        path: &str,
+    ) -> Result<Value, XmlError> {
+        DynamicMessage::decode(descriptor, value.encode_to_vec().as_slice())
+            .map(Value::Message)
+            .map_err(|e| self.error(node, path, e.to_string()))
+    }
+
+    fn scalar(&self, node: Node, kind: &Kind, text: &str, path: &str) -> Result<Value, XmlError> {
+        let invalid = |expected: &str| self.error(node, path, format!("expected {expected}"));
+
+        let value = match kind {
+            Kind::String => Value::String(text.to_string()),
+            Kind::Bytes => Value::Bytes(text.as_bytes().to_vec().into()),
+            Kind::Bool => Value::Bool(text.parse().map_err(|_e| invalid("true or false"))?),
+            Kind::Double => Value::F64(text.parse().map_err(|_e| invalid("a decimal number"))?),
+            Kind::Float => Value::F32(text.parse().map_err(|_e| invalid("a decimal number"))?),
+            Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => {
+                Value::I32(text.parse().map_err(|_e| invalid("an integer"))?)
+            }
+            Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => {
+                Value::I64(text.parse().map_err(|_e| invalid("an integer"))?)
+            }
+            Kind::Uint32 | Kind::Fixed32 => {
+                Value::U32(text.parse().map_err(|_e| invalid("a positive integer"))?)
+            }
+            Kind::Uint64 | Kind::Fixed64 => {
+                Value::U64(text.parse().map_err(|_e| invalid("a positive integer"))?)
+            }
+            Kind::Enum(descriptor) => Value::EnumNumber(
+                code(descriptor, text)
+                    .ok_or_else(|| self.error(node, path, format!("unknown code {text}")))?,
+            ),
+            Kind::Message(_) => return Err(self.error(node, path, "expected an element")),
+        };
+
+        Ok(value)
+    }
+}
+
+/// `FIToFICstmrCdtTrf` -> `f_i_to_f_i_cstmr_cdt_trf`
+fn field_name(name: &str) -> String {
+    let mut field = String::with_capacity(name.len() * 2);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                field.push('_');
+            }
+            field.push(c.to_ascii_lowercase());
+        } else {
+            field.push(c);
+        }
+    }
+    field
+}
+
+/// Finds the enum value generated for an ISO code, e.g. `SLEV` in `ChargeBearerType1Code`
+fn code(descriptor: &EnumDescriptor, code: &str) -> Option<i32> {
+    let code = code.to_ascii_uppercase();
+    descriptor
+        .values()
+        .find(|value| {
+            value
+                .name()
+                .strip_suffix(code.as_str())
+                .is_some_and(|prefix| prefix.ends_with('_'))
+        })
+        .map(|value| value.number())
+}
+
+/// ISO date times may omit the offset, in which case UTC is assumed
+fn parse_timestamp(text: &str) -> Option<Timestamp> {
+    text.parse::<Timestamp>().ok().or_else(|| {
+        time::PrimitiveDateTime::parse(
+            text,
+            &time::format_description::well_known::Iso8601::DEFAULT,
+        )
+        .ok()
+        .map(|dt| Timestamp::from(dt.assume_utc()))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACS008: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:pacs.008.001.12">
+  <FIToFICstmrCdtTrf>
+    <GrpHdr>
+      <MsgId>msg-001</MsgId>
+      <CreDtTm>2025-08-10T16:00:34Z</CreDtTm>
+      <NbOfTxs>1</NbOfTxs>
+      <SttlmInf>
+        <SttlmMtd>CLRG</SttlmMtd>
+      </SttlmInf>
+    </GrpHdr>
+    <CdtTrfTxInf>
+      <PmtId>
+        <InstrId>instr-001</InstrId>
+        <EndToEndId>e2e-001</EndToEndId>
+      </PmtId>
+      <IntrBkSttlmAmt Ccy="XTS">294.3</IntrBkSttlmAmt>
+      <InstdAmt Ccy="XTS">294.3</InstdAmt>
+      <ChrgBr>DEBT</ChrgBr>
+    </CdtTrfTxInf>
+  </FIToFICstmrCdtTrf>
+</Document>"#;
+
+    #[test]
+    fn maps_element_names() {
+        assert_eq!(field_name("FIToFICstmrCdtTrf"), "f_i_to_f_i_cstmr_cdt_trf");
+        assert_eq!(field_name("IBAN"), "i_b_a_n");
+        assert_eq!(field_name("IntrmyAgt1"), "intrmy_agt1");
+        assert_eq!(field_name("Ccy"), "ccy");
+    }
+
+    #[test]
+    fn parses_pacs008() {
+        let document = from_str::<pacs008::Pacs008Document>(PACS008).unwrap();
+        let transfer = &document.f_i_to_f_i_cstmr_cdt_trf;
+
+        assert_eq!(transfer.grp_hdr.msg_id, "msg-001");
+        assert_eq!(transfer.cdt_trf_tx_inf.len(), 1);
+
+        let tx = &transfer.cdt_trf_tx_inf[0];
+        assert_eq!(tx.pmt_id.end_to_end_id, "e2e-001");
+
+        let instd_amt = tx.instd_amt.as_ref().unwrap();
+        assert_eq!(instd_amt.ccy, "XTS");
+        assert_eq!(instd_amt.value, 294.3);
+    }
+
+    #[test]
+    fn rejects_other_namespace() {
+        let xml = PACS008.replace("pacs.008.001.12", "pacs.008.001.08");
+        let err = from_str::<pacs008::Pacs008Document>(&xml).unwrap_err();
+
+        assert_eq!(err.path(), "/Document");
+        assert_eq!(err.line(), 2);
+    }
+
+    #[test]
+    fn points_at_invalid_element() {
+        let xml = PACS008.replace(
+            "<InstdAmt Ccy=\"XTS\">294.3</InstdAmt>",
+            "<InstdAmt Ccy=\"XTS\">lots</InstdAmt>",
+        );
+        let err = from_str::<pacs008::Pacs008Document>(&xml).unwrap_err();
+
+        assert_eq!(
+            err.path(),
+            "/Document/FIToFICstmrCdtTrf/CdtTrfTxInf[1]/InstdAmt"
+        );
+        assert_eq!(err.line(), 18);
+    }
+
+    #[test]
+    fn points_at_unknown_element() {
+        let xml = PACS008
+            .replace("<NbOfTxs>", "<NbOfTx>")
+            .replace("</NbOfTxs>", "</NbOfTx>");
+        let err = from_str::<pacs008::Pacs008Document>(&xml).unwrap_err();
+
+        assert_eq!(err.path(), "/Document/FIToFICstmrCdtTrf/GrpHdr/NbOfTx");
+        assert_eq!(err.reason(), "unknown element");
+    }
+
+    #[test]
+    fn rejects_malformed_xml() {
+        let err = from_str::<pacs008::Pacs008Document>("<Document>").unwrap_err();
+
+        assert!(err.path().is_empty());
+    }
+}
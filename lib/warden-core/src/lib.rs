@@ -26,6 +26,11 @@ pub mod iso20022;
 #[cfg(feature = "message")]
 pub mod message;
 
+/// Stored evaluations
+#[allow(missing_docs)]
+#[cfg(feature = "evaluation")]
+pub mod evaluation;
+
 /// Pseudonyms
 #[allow(missing_docs)]
 #[cfg(feature = "pseudonyms")]
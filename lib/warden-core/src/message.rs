@@ -1 +1,50 @@
 tonic::include_proto!("message");
+
+impl payload::Transaction {
+    /// The message id of the document and the end to end id of its first transaction
+    ///
+    /// Documents are published with a single transaction (or status, for pacs.002), so the pair
+    /// identifies what is being evaluated
+    pub fn evaluation_ids(&self) -> (&str, &str) {
+        match self {
+            payload::Transaction::Pacs008(document) => (
+                document.f_i_to_f_i_cstmr_cdt_trf.grp_hdr.msg_id.as_str(),
+                document
+                    .f_i_to_f_i_cstmr_cdt_trf
+                    .cdt_trf_tx_inf
+                    .first()
+                    .map(|value| value.pmt_id.end_to_end_id.as_str())
+                    .unwrap_or_default(),
+            ),
+            payload::Transaction::Pacs002(document) => (
+                document.f_i_to_f_i_pmt_sts_rpt.grp_hdr.msg_id.as_str(),
+                document
+                    .f_i_to_f_i_pmt_sts_rpt
+                    .tx_inf_and_sts
+                    .first()
+                    .and_then(|value| value.orgnl_end_to_end_id.as_deref())
+                    .unwrap_or_default(),
+            ),
+            payload::Transaction::Pain001(document) => (
+                document.cstmr_cdt_trf_initn.grp_hdr.msg_id.as_str(),
+                document
+                    .cstmr_cdt_trf_initn
+                    .pmt_inf
+                    .first()
+                    .and_then(|value| value.cdt_trf_tx_inf.first())
+                    .map(|value| value.pmt_id.end_to_end_id.as_str())
+                    .unwrap_or_default(),
+            ),
+            payload::Transaction::Pain013(document) => (
+                document.cdtr_pmt_actvtn_req.grp_hdr.msg_id.as_str(),
+                document
+                    .cdtr_pmt_actvtn_req
+                    .pmt_inf
+                    .first()
+                    .and_then(|value| value.cdt_trf_tx.first())
+                    .map(|value| value.pmt_id.end_to_end_id.as_str())
+                    .unwrap_or_default(),
+            ),
+        }
+    }
+}
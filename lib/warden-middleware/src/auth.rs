@@ -0,0 +1,341 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use axum::{
+    Router,
+    extract::{FromRequestParts, Request, State},
+    http::{HeaderMap, Method, StatusCode, header, request::Parts},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, jwk::JwkSet};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+/// Header an API key is sent in
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AuthConfig {
+    /// When false, every request is treated as coming from an admin
+    pub enabled: bool,
+    /// JWKS file holding the public keys bearer tokens are signed with
+    pub jwks: Option<PathBuf>,
+    /// Expected `iss` claim of bearer tokens
+    pub issuer: Option<String>,
+    /// Expected `aud` claim of bearer tokens
+    pub audience: Option<String>,
+    /// Keys accepted in the `X-API-Key` header
+    #[serde(default)]
+    pub api_keys: Vec<ApiKey>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiKey {
+    /// Identifies the holder of the key in logs
+    pub name: String,
+    /// Hex encoded SHA-256 digest of the key. The key itself is never configured
+    pub sha256: String,
+    pub role: Role,
+    /// `mmb_id` of the participant the key acts for
+    pub participant: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// May act for every participant and change configuration
+    Admin,
+    /// May only submit messages its participant is an agent in
+    Participant,
+}
+
+/// How the caller of a request was authenticated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Credential {
+    /// Authentication is disabled
+    Anonymous,
+    /// A bearer token
+    Token,
+    /// An `X-API-Key`
+    ApiKey,
+}
+
+/// The authenticated caller of a request
+#[derive(Debug, Clone)]
+pub struct Principal {
+    /// `sub` claim of the token, or the name of the API key
+    pub subject: String,
+    pub credential: Credential,
+    pub roles: Vec<Role>,
+    /// `mmb_id` of the participant the caller acts for
+    pub participant: Option<String>,
+}
+
+impl Principal {
+    pub fn is_admin(&self) -> bool {
+        self.roles.contains(&Role::Admin)
+    }
+
+    /// Whether the caller may submit a transaction between `agents`, given as their `mmb_id`
+    pub fn may_act_for<'a>(&self, agents: impl IntoIterator<Item = Option<&'a str>>) -> bool {
+        if self.is_admin() {
+            return true;
+        }
+
+        match self.participant.as_deref() {
+            Some(participant) if self.roles.contains(&Role::Participant) => agents
+                .into_iter()
+                .flatten()
+                .any(|mmb_id| mmb_id == participant),
+            _ => false,
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for Principal
+where
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // set by [require_authentication]
+        parts
+            .extensions
+            .get::<Principal>()
+            .cloned()
+            .ok_or_else(|| unauthorized("missing credentials"))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("could not read jwks: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid jwks: {0}")]
+    Jwks(#[from] serde_json::Error),
+    #[error("unusable key {kid}: {source}")]
+    Key {
+        kid: String,
+        #[source]
+        source: jsonwebtoken::errors::Error,
+    },
+}
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+    #[serde(default)]
+    roles: Vec<String>,
+    participant: Option<String>,
+}
+
+/// Validates the credentials of incoming requests
+pub struct Authenticator {
+    enabled: bool,
+    keys: HashMap<String, DecodingKey>,
+    issuer: Option<String>,
+    audience: Option<String>,
+    api_keys: HashMap<String, Principal>,
+}
+
+impl Authenticator {
+    pub fn new(config: &AuthConfig) -> Result<Self, AuthError> {
+        if !config.enabled {
+            warn!("authentication is disabled, every request is treated as coming from an admin");
+        }
+
+        let mut keys = HashMap::new();
+        if let Some(path) = &config.jwks {
+            let jwks: JwkSet = serde_json::from_slice(&std::fs::read(path)?)?;
+            for jwk in &jwks.keys {
+                let Some(kid) = jwk.common.key_id.clone() else {
+                    warn!("skipping jwk without a kid");
+                    continue;
+                };
+                let key = DecodingKey::from_jwk(jwk).map_err(|source| AuthError::Key {
+                    kid: kid.clone(),
+                    source,
+                })?;
+                keys.insert(kid, key);
+            }
+            debug!(count = keys.len(), "loaded signing keys");
+        }
+
+        let api_keys = config
+            .api_keys
+            .iter()
+            .map(|value| {
+                (
+                    value.sha256.to_ascii_lowercase(),
+                    Principal {
+                        subject: value.name.clone(),
+                        credential: Credential::ApiKey,
+                        roles: vec![value.role],
+                        participant: value.participant.clone(),
+                    },
+                )
+            })
+            .collect();
+
+        Ok(Self {
+            enabled: config.enabled,
+            keys,
+            issuer: config.issuer.clone(),
+            audience: config.audience.clone(),
+            api_keys,
+        })
+    }
+
+    /// Resolves the caller from a bearer token or an API key
+    pub fn verify(&self, headers: &HeaderMap) -> Result<Principal, &'static str> {
+        if !self.enabled {
+            return Ok(Principal {
+                subject: "anonymous".to_string(),
+                credential: Credential::Anonymous,
+                roles: vec![Role::Admin],
+                participant: None,
+            });
+        }
+
+        if let Some(value) = headers.get(header::AUTHORIZATION) {
+            let token = value
+                .to_str()
+                .ok()
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .ok_or("expected a bearer token")?;
+            return self.verify_token(token);
+        }
+
+        if let Some(value) = headers.get(API_KEY_HEADER) {
+            let digest = format!("{:x}", Sha256::digest(value.as_bytes()));
+            return self.api_keys.get(&digest).cloned().ok_or("unknown api key");
+        }
+
+        Err("missing credentials")
+    }
+
+    fn verify_token(&self, token: &str) -> Result<Principal, &'static str> {
+        let header = jsonwebtoken::decode_header(token).map_err(|_| "malformed token")?;
+
+        // keys are published for asymmetric signatures only
+        if matches!(
+            header.alg,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        ) {
+            return Err("unsupported signing algorithm");
+        }
+
+        let kid = header.kid.ok_or("token does not name its signing key")?;
+        let key = self.keys.get(&kid).ok_or("unknown signing key")?;
+
+        let mut validation = Validation::new(header.alg);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        let claims = jsonwebtoken::decode::<Claims>(token, key, &validation)
+            .map_err(|e| {
+                debug!(%kid, "token rejected: {e}");
+                "invalid token"
+            })?
+            .claims;
+
+        let roles = claims
+            .roles
+            .iter()
+            .filter_map(|role| match role.as_str() {
+                "admin" => Some(Role::Admin),
+                "participant" => Some(Role::Participant),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Principal {
+            subject: claims.sub,
+            credential: Credential::Token,
+            roles,
+            participant: claims.participant,
+        })
+    }
+}
+
+/// Rejects requests to `router` that carry no valid credentials
+///
+/// The caller is made available to handlers as a [Principal]
+pub fn require_authentication<S: Clone + Send + Sync + 'static>(
+    router: Router<S>,
+    authenticator: Arc<Authenticator>,
+) -> Router<S> {
+    router.layer(middleware::from_fn_with_state(
+        authenticator,
+        apply_authentication,
+    ))
+}
+
+/// Only lets admins through to routes of `router` that change state
+///
+/// Must be applied within [require_authentication]
+pub fn require_admin_for_writes<S: Clone + Send + Sync + 'static>(router: Router<S>) -> Router<S> {
+    router.layer(middleware::from_fn(apply_admin_for_writes))
+}
+
+/// Only lets admins through to the routes of `router`
+///
+/// Must be applied within [require_authentication]
+pub fn require_admin<S: Clone + Send + Sync + 'static>(router: Router<S>) -> Router<S> {
+    router.layer(middleware::from_fn(apply_admin))
+}
+
+async fn apply_authentication(
+    State(authenticator): State<Arc<Authenticator>>,
+    mut req: Request,
+    next: Next,
+) -> Response {
+    match authenticator.verify(req.headers()) {
+        Ok(principal) => {
+            debug!(subject = %principal.subject, "request authenticated");
+            req.extensions_mut().insert(principal);
+            next.run(req).await
+        }
+        Err(reason) => {
+            debug!(path = %req.uri().path(), reason, "request not authenticated");
+            unauthorized(reason)
+        }
+    }
+}
+
+async fn apply_admin_for_writes(principal: Principal, req: Request, next: Next) -> Response {
+    let read_only = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+
+    if !read_only && !principal.is_admin() {
+        warn!(subject = %principal.subject, method = %req.method(), path = %req.uri().path(), "write denied");
+        return (StatusCode::FORBIDDEN, "only admins may make changes").into_response();
+    }
+
+    next.run(req).await
+}
+
+async fn apply_admin(principal: Principal, req: Request, next: Next) -> Response {
+    if !principal.is_admin() {
+        warn!(subject = %principal.subject, method = %req.method(), path = %req.uri().path(), "admin route denied");
+        return (StatusCode::FORBIDDEN, "only admins may use this route").into_response();
+    }
+
+    next.run(req).await
+}
+
+fn unauthorized(reason: &'static str) -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        [(header::WWW_AUTHENTICATE, "Bearer")],
+        reason,
+    )
+        .into_response()
+}
@@ -1,19 +1,48 @@
 pub mod interceptor {
+    use std::{collections::HashMap, sync::Arc};
+
+    use secrecy::ExposeSecret;
+    use sha2::{Digest, Sha256};
     use tonic::{
         Status,
+        metadata::{Ascii, MetadataValue, errors::InvalidMetadataValue},
         service::{Interceptor, interceptor::InterceptedService},
         transport::Channel,
     };
-    use tracing::Span;
+    use tracing::{Span, error, trace, warn};
     use warden_stack::{
-        opentelemetry::global, tracing::telemetry::tonic::injector,
+        grpc::GrpcConfig,
+        opentelemetry::global,
+        tracing::telemetry::tonic::{extractor, injector},
         tracing_opentelemetry::OpenTelemetrySpanExt,
     };
 
+    /// Metadata key the service token is sent in
+    pub const SERVICE_TOKEN_HEADER: &str = "x-service-token";
+
     pub type Intercepted = InterceptedService<Channel, MyInterceptor>;
 
-    #[derive(Clone, Copy)]
-    pub struct MyInterceptor;
+    /// Attaches the trace context and service token to outgoing calls
+    #[derive(Clone, Default)]
+    pub struct MyInterceptor {
+        token: Option<MetadataValue<Ascii>>,
+    }
+
+    impl MyInterceptor {
+        pub fn new(config: &GrpcConfig) -> Result<Self, InvalidMetadataValue> {
+            let token = config
+                .token
+                .as_ref()
+                .map(|token| {
+                    let mut value: MetadataValue<Ascii> = token.expose_secret().parse()?;
+                    value.set_sensitive(true);
+                    Ok::<_, InvalidMetadataValue>(value)
+                })
+                .transpose()?;
+
+            Ok(Self { token })
+        }
+    }
 
     impl Interceptor for MyInterceptor {
         fn call(&mut self, mut request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
@@ -22,6 +51,84 @@ pub mod interceptor {
                 propagator.inject_context(&cx, &mut injector::MetadataMap(request.metadata_mut()))
             });
 
+            if let Some(token) = &self.token {
+                request
+                    .metadata_mut()
+                    .insert(SERVICE_TOKEN_HEADER, token.clone());
+            }
+
+            Ok(request)
+        }
+    }
+
+    /// The service that made a call, available in the extensions of the request
+    #[derive(Clone, Debug)]
+    pub struct Caller(pub Arc<str>);
+
+    /// Continues the trace of incoming calls and rejects those without a known service token
+    #[derive(Clone)]
+    pub struct VerifyCaller {
+        /// Caller names keyed by the digest of their token
+        callers: Arc<HashMap<String, Arc<str>>>,
+        allow_anonymous: bool,
+    }
+
+    impl VerifyCaller {
+        pub fn new(config: &GrpcConfig) -> Self {
+            match (config.callers.is_empty(), config.allow_anonymous) {
+                (true, true) => warn!("no grpc callers are configured, every caller is accepted"),
+                (true, false) => error!("no grpc callers are configured, every call is rejected"),
+                _ => {}
+            }
+
+            let callers = config
+                .callers
+                .iter()
+                .map(|caller| {
+                    (
+                        caller.sha256.to_ascii_lowercase(),
+                        caller.name.as_str().into(),
+                    )
+                })
+                .collect();
+
+            Self {
+                callers: Arc::new(callers),
+                allow_anonymous: config.allow_anonymous,
+            }
+        }
+    }
+
+    impl Interceptor for VerifyCaller {
+        fn call(&mut self, mut request: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
+            let span = Span::current();
+
+            let cx = global::get_text_map_propagator(|propagator| {
+                propagator.extract(&extractor::MetadataMap(request.metadata()))
+            });
+
+            if let Err(e) = span.set_parent(cx) {
+                error!("{e:?}");
+            };
+
+            if self.callers.is_empty() && self.allow_anonymous {
+                return Ok(request);
+            }
+
+            let token = request
+                .metadata()
+                .get(SERVICE_TOKEN_HEADER)
+                .ok_or_else(|| Status::unauthenticated("missing service token"))?;
+
+            let digest = format!("{:x}", Sha256::digest(token.as_bytes()));
+            let Some(caller) = self.callers.get(&digest).cloned() else {
+                warn!("call with an unknown service token");
+                return Err(Status::unauthenticated("unknown caller"));
+            };
+
+            trace!(%caller, "call authenticated");
+            request.extensions_mut().insert(Caller(caller));
+
             Ok(request)
         }
     }
@@ -1,6 +1,10 @@
+pub mod auth;
 pub mod grpc;
 mod metrics;
+#[cfg(feature = "rate-limit")]
+pub mod rate_limit;
 mod trace_layer;
+pub mod version;
 
 use metrics::*;
 use trace_layer::*;
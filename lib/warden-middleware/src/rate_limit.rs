@@ -0,0 +1,194 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    Router,
+    extract::{Request, State},
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use tokio::sync::Semaphore;
+use tracing::{debug, warn};
+use warden_stack::{cache::RedisManager, redis};
+
+/// Refills the bucket at `KEYS[1]` and takes a token from it
+///
+/// Redis' clock is used so that replicas agree on how much has been refilled. Returns whether a
+/// token was taken, and otherwise the milliseconds until one is available
+const TOKEN_BUCKET: &str = r#"
+local capacity = tonumber(ARGV[1])
+local rate = tonumber(ARGV[2])
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'at')
+local tokens = tonumber(bucket[1]) or capacity
+local at = tonumber(bucket[2]) or now
+tokens = math.min(capacity, tokens + math.max(0, now - at) * rate / 1000)
+
+local allowed = 0
+local wait = 0
+if tokens >= 1 then
+    tokens = tokens - 1
+    allowed = 1
+else
+    wait = math.ceil((1 - tokens) * 1000 / rate)
+end
+
+redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'at', now)
+redis.call('PEXPIRE', KEYS[1], math.ceil(capacity * 1000 / rate))
+return { allowed, wait }
+"#;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct RateLimitConfig {
+    /// When false, requests are never limited
+    pub enabled: bool,
+    /// Requests a caller may make in a burst
+    pub burst: u32,
+    /// Requests a caller may make per second once the burst is spent
+    #[serde(deserialize_with = "refill_rate")]
+    pub per_second: f64,
+    /// Requests handled at the same time before new ones are shed
+    pub max_in_flight: Option<usize>,
+}
+
+/// Rejects rates that are not positive: a bucket would never refill, and refilling it divides by
+/// the rate
+fn refill_rate<'de, D>(deserializer: D) -> Result<f64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = f64::deserialize(deserializer)?;
+    if value.is_finite() && value > 0.0 {
+        Ok(value)
+    } else {
+        Err(serde::de::Error::custom(format!(
+            "per-second must be a positive number, got {value}"
+        )))
+    }
+}
+
+/// A caller has spent its tokens
+#[derive(Debug, thiserror::Error)]
+#[error("rate limit exceeded, retry in {} second(s)", self.retry_after_secs())]
+pub struct RateLimited {
+    /// How long until the caller has a token again
+    pub retry_after: Duration,
+}
+
+impl RateLimited {
+    /// Value of the `Retry-After` header
+    pub fn retry_after_secs(&self) -> u64 {
+        self.retry_after.as_millis().div_ceil(1000).max(1) as u64
+    }
+}
+
+/// Token buckets shared by every replica through the cache
+#[derive(Clone)]
+pub struct RateLimiter {
+    cache: RedisManager,
+    config: RateLimitConfig,
+}
+
+impl RateLimiter {
+    pub fn new(cache: RedisManager, config: RateLimitConfig) -> Self {
+        if !config.enabled {
+            warn!("rate limiting is disabled");
+        }
+
+        Self { cache, config }
+    }
+
+    /// Takes a token from the bucket of `key`
+    ///
+    /// Requests are let through when the cache cannot be reached, as limiting them would turn a
+    /// cache outage into a full outage
+    pub async fn acquire(&self, key: &str) -> Result<(), RateLimited> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let decision = match self.take(key).await {
+            Ok(decision) => decision,
+            Err(e) => {
+                warn!(%key, "could not check rate limit: {e}");
+                metrics::counter!("rate_limit_requests_total", "outcome" => "error").increment(1);
+                return Ok(());
+            }
+        };
+
+        match decision {
+            (1, _) => {
+                metrics::counter!("rate_limit_requests_total", "outcome" => "allowed").increment(1);
+                Ok(())
+            }
+            (_, wait) => {
+                debug!(%key, wait, "rate limit exceeded");
+                metrics::counter!("rate_limit_requests_total", "outcome" => "limited").increment(1);
+                Err(RateLimited {
+                    retry_after: Duration::from_millis(wait.max(0) as u64),
+                })
+            }
+        }
+    }
+
+    async fn take(&self, key: &str) -> anyhow::Result<(i64, i64)> {
+        let mut connection = self.cache.get().await?;
+
+        let decision = redis::cmd("EVAL")
+            .arg(TOKEN_BUCKET)
+            .arg(1)
+            .arg(format!("rate-limit:{key}"))
+            .arg(self.config.burst)
+            .arg(self.config.per_second)
+            .query_async(&mut connection)
+            .await?;
+
+        Ok(decision)
+    }
+}
+
+impl IntoResponse for RateLimited {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(header::RETRY_AFTER, self.retry_after_secs().to_string())],
+            self.to_string(),
+        )
+            .into_response()
+    }
+}
+
+/// Rejects requests to `router` with `503 Service Unavailable` while `in_flight` has no permits
+/// left, rather than letting them queue up
+pub fn shed_load<S: Clone + Send + Sync + 'static>(
+    router: Router<S>,
+    in_flight: Arc<Semaphore>,
+) -> Router<S> {
+    router.layer(middleware::from_fn_with_state(
+        in_flight,
+        apply_load_shedding,
+    ))
+}
+
+async fn apply_load_shedding(
+    State(in_flight): State<Arc<Semaphore>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Ok(_permit) = in_flight.try_acquire_owned() else {
+        warn!(path = %req.uri().path(), "shedding load");
+        metrics::counter!("load_shed_requests_total").increment(1);
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, "1")],
+            "too many requests in flight",
+        )
+            .into_response();
+    };
+
+    next.run(req).await
+}
@@ -0,0 +1,224 @@
+use std::fmt;
+
+use axum::{
+    Extension, Router,
+    extract::{FromRequestParts, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, StatusCode, header, request::Parts},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+};
+use time::{OffsetDateTime, format_description::BorrowedFormatItem, macros::format_description};
+use tracing::trace;
+use utoipa::openapi::{Deprecated, OpenApi};
+use utoipa_axum::router::OpenApiRouter;
+#[cfg(feature = "redoc")]
+use utoipa_redoc::Servable;
+#[cfg(feature = "scalar")]
+use utoipa_scalar::Servable as _;
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+/// IMF-fixdate, the preferred format for dates in HTTP headers
+const HTTP_DATE: &[BorrowedFormatItem<'_>] = format_description!(
+    "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT"
+);
+
+/// A version of an HTTP API
+///
+/// Each version is served under its own prefix by [router], and the version that handled a
+/// request is available to its handler through [Versioned]
+pub trait ApiVersion: Copy + fmt::Display + Send + Sync + 'static {
+    /// Every version that is served, oldest first
+    const ALL: &'static [Self];
+
+    /// Path prefix the version is served under
+    fn prefix(&self) -> String {
+        format!("/api/{self}")
+    }
+
+    /// Set once a version is being phased out
+    fn deprecation(&self) -> Option<Deprecation> {
+        None
+    }
+}
+
+/// Extracts the version of the API that is handling a request
+#[derive(Debug, Clone, Copy)]
+pub struct Versioned<V>(pub V);
+
+impl<V, S> FromRequestParts<S> for Versioned<V>
+where
+    V: ApiVersion,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // set by the router of each version
+        parts
+            .extensions
+            .get::<V>()
+            .copied()
+            .map(Versioned)
+            .ok_or_else(|| (StatusCode::NOT_FOUND, "unknown version").into_response())
+    }
+}
+
+/// Serves the routes of every version of `V` under its prefix, each documented on its own
+///
+/// `routes` are the routes of a version, and `layer` is applied to them once they are nested,
+/// e.g. to authenticate them. Routes in `shared`, such as a health check, are served once and
+/// documented in every version. A deprecated version announces it in its responses and marks
+/// its operations as deprecated
+pub fn router<V: ApiVersion>(
+    openapi: OpenApi,
+    shared: OpenApiRouter,
+    mut routes: impl FnMut(V) -> OpenApiRouter,
+    mut layer: impl FnMut(Router) -> Router,
+) -> Router {
+    let (mut router, shared) = shared.split_for_parts();
+    let mut docs = Vec::with_capacity(V::ALL.len());
+
+    for version in V::ALL.iter().copied() {
+        let (versioned, mut api) = OpenApiRouter::with_openapi(openapi.clone())
+            .nest(&version.prefix(), routes(version).layer(Extension(version)))
+            .split_for_parts();
+        api.merge(shared.clone());
+
+        let versioned = layer(versioned);
+
+        let versioned = match version.deprecation() {
+            Some(deprecation) => {
+                deprecate_operations(&mut api);
+                deprecate(versioned, deprecation)
+            }
+            None => versioned,
+        };
+
+        router = router.merge(versioned);
+        docs.push((version, api));
+    }
+
+    document(router, &docs)
+}
+
+/// Serves the documentation of each version with the UIs that are enabled
+#[allow(unused_variables)]
+fn document<V: ApiVersion>(router: Router, docs: &[(V, OpenApi)]) -> Router {
+    #[cfg(feature = "swagger")]
+    let router = router.merge(docs.iter().fold(
+        utoipa_swagger_ui::SwaggerUi::new("/swagger-ui"),
+        |ui, (version, api)| ui.url(format!("/api-docs/{version}/swaggerdoc.json"), api.clone()),
+    ));
+
+    #[cfg(feature = "redoc")]
+    let router = docs.iter().fold(router, |router, (version, api)| {
+        router.merge(utoipa_redoc::Redoc::with_url(
+            format!("/redoc/{version}"),
+            api.clone(),
+        ))
+    });
+
+    #[cfg(feature = "rapidoc")]
+    let router = docs.iter().fold(router, |router, (version, api)| {
+        router.merge(
+            utoipa_rapidoc::RapiDoc::with_openapi(
+                format!("/api-docs/{version}/rapidoc.json"),
+                api.clone(),
+            )
+            .path(format!("/rapidoc/{version}")),
+        )
+    });
+
+    #[cfg(feature = "scalar")]
+    let router = docs.iter().fold(router, |router, (version, api)| {
+        router.merge(utoipa_scalar::Scalar::with_url(
+            format!("/scalar/{version}"),
+            api.clone(),
+        ))
+    });
+
+    router
+}
+
+/// Flags every operation of a version that is being phased out
+fn deprecate_operations(api: &mut OpenApi) {
+    for item in api.paths.paths.values_mut() {
+        for operation in [
+            &mut item.get,
+            &mut item.put,
+            &mut item.post,
+            &mut item.delete,
+            &mut item.patch,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            operation.deprecated = Some(Deprecated::True);
+        }
+    }
+}
+
+/// Lifecycle of an API version that is being phased out
+#[derive(Debug, Clone, Copy)]
+pub struct Deprecation {
+    /// When the version was deprecated
+    pub since: OffsetDateTime,
+    /// When the version is expected to stop being served
+    pub sunset: Option<OffsetDateTime>,
+    /// Path prefix of the version that replaces it, e.g. `/api/v1`
+    pub successor: &'static str,
+}
+
+/// Announces the deprecation of every route in `router`
+///
+/// Responses carry the `Deprecation` ([RFC 9745](https://www.rfc-editor.org/rfc/rfc9745)),
+/// `Sunset` ([RFC 8594](https://www.rfc-editor.org/rfc/rfc8594)) and successor `Link` headers
+pub fn deprecate<S: Clone + Send + Sync + 'static>(
+    router: Router<S>,
+    deprecation: Deprecation,
+) -> Router<S> {
+    router.layer(middleware::from_fn_with_state(
+        deprecation,
+        apply_deprecation,
+    ))
+}
+
+async fn apply_deprecation(
+    State(deprecation): State<Deprecation>,
+    req: Request,
+    next: Next,
+) -> Response {
+    trace!(path = %req.uri().path(), "request to a deprecated version");
+
+    let mut response = next.run(req).await;
+    insert_headers(response.headers_mut(), &deprecation);
+
+    response
+}
+
+fn insert_headers(headers: &mut HeaderMap, deprecation: &Deprecation) {
+    let since = format!("@{}", deprecation.since.unix_timestamp());
+    if let Ok(value) = HeaderValue::from_str(&since) {
+        headers.insert(DEPRECATION, value);
+    }
+
+    if let Some(value) = deprecation
+        .sunset
+        .and_then(|sunset| {
+            sunset
+                .to_offset(time::UtcOffset::UTC)
+                .format(HTTP_DATE)
+                .ok()
+        })
+        .and_then(|sunset| HeaderValue::from_str(&sunset).ok())
+    {
+        headers.insert(SUNSET, value);
+    }
+
+    let link = format!(r#"<{}>; rel="successor-version""#, deprecation.successor);
+    if let Ok(value) = HeaderValue::from_str(&link) {
+        headers.append(header::LINK, value);
+    }
+}
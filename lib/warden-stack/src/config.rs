@@ -42,6 +42,9 @@ pub struct Configuration {
     #[cfg(any(feature = "nats-core", feature = "nats-jetstream"))]
     #[serde(default)]
     pub nats: crate::nats::NatsConfig,
+    #[cfg(feature = "grpc")]
+    #[serde(default)]
+    pub grpc: crate::grpc::GrpcConfig,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -0,0 +1,119 @@
+use std::path::{Path, PathBuf};
+
+use secrecy::SecretString;
+use serde::Deserialize;
+use tonic::transport::{
+    Certificate, Channel, ClientTlsConfig, Endpoint, Identity, ServerTlsConfig,
+};
+
+use crate::ServiceError;
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+/// Settings for gRPC calls between services
+pub struct GrpcConfig {
+    /// Connections are plaintext when absent
+    pub tls: Option<TlsConfig>,
+    /// Token attached to the calls this service makes
+    pub token: Option<SecretString>,
+    /// Services allowed to call this one
+    #[serde(default)]
+    pub callers: Vec<Caller>,
+    /// Accept calls without a service token when no `callers` are configured. Every call is
+    /// rejected otherwise. Only meant for development
+    #[serde(default)]
+    pub allow_anonymous: bool,
+    /// Port gRPC is served on when `tls` is configured, by services that also serve an HTTP API.
+    /// That API stays on the application port, outside of mTLS
+    pub port: Option<u16>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+/// TLS settings, with every certificate and key PEM encoded
+pub struct TlsConfig {
+    /// Certificate this service presents to its peers
+    pub cert: PathBuf,
+    /// Private key of `cert`
+    pub key: PathBuf,
+    /// CA that peer certificates must be issued by
+    pub ca: PathBuf,
+    /// Whether callers must present a certificate issued by `ca` (mTLS)
+    #[serde(default = "mutual")]
+    pub mutual: bool,
+    /// Name servers are expected to present a certificate for, when it is not the endpoint host
+    pub domain: Option<String>,
+}
+
+pub(crate) fn mutual() -> bool {
+    true
+}
+
+#[derive(Deserialize, Clone, Debug)]
+/// A service that may make calls
+pub struct Caller {
+    /// Identifies the caller in logs
+    pub name: String,
+    /// Hex encoded SHA-256 digest of the caller's token. The token itself is never configured
+    pub sha256: String,
+}
+
+impl TlsConfig {
+    /// TLS settings for serving calls
+    pub fn server(&self) -> Result<ServerTlsConfig, ServiceError> {
+        let tls = ServerTlsConfig::new().identity(self.identity()?);
+
+        Ok(if self.mutual {
+            tls.client_ca_root(read_certificate(&self.ca)?)
+        } else {
+            tls
+        })
+    }
+
+    /// TLS settings for making calls
+    pub fn client(&self) -> Result<ClientTlsConfig, ServiceError> {
+        let tls = ClientTlsConfig::new()
+            .ca_certificate(read_certificate(&self.ca)?)
+            .identity(self.identity()?);
+
+        Ok(match &self.domain {
+            Some(domain) => tls.domain_name(domain),
+            None => tls,
+        })
+    }
+
+    fn identity(&self) -> Result<Identity, ServiceError> {
+        Ok(Identity::from_pem(read(&self.cert)?, read(&self.key)?))
+    }
+}
+
+impl GrpcConfig {
+    /// Connects to the service at `endpoint`, over TLS when configured
+    pub async fn connect(&self, endpoint: impl Into<String>) -> Result<Channel, ServiceError> {
+        let endpoint = endpoint.into();
+        let mut channel = Endpoint::from_shared(endpoint.clone())?;
+
+        if let Some(tls) = &self.tls {
+            // tonic only negotiates TLS for https endpoints
+            if !endpoint.starts_with("https://") {
+                return Err(ServiceError::Configuration(format!(
+                    "{endpoint} must use https when tls is configured"
+                )));
+            }
+            channel = channel.tls_config(tls.client()?)?;
+        }
+
+        Ok(channel.connect().await?)
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, ServiceError> {
+    std::fs::read(path).map_err(|source| ServiceError::Certificate {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn read_certificate(path: &Path) -> Result<Certificate, ServiceError> {
+    read(path).map(Certificate::from_pem)
+}
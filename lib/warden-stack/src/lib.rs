@@ -41,6 +41,10 @@ pub mod postgres;
 )]
 pub mod nats;
 
+#[cfg(feature = "grpc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "grpc")))]
+pub mod grpc;
+
 mod config;
 pub use config::*;
 
@@ -92,4 +96,15 @@ pub enum ServiceError {
     #[error(transparent)]
     /// When creating the tracing layer
     Loki(#[from] tracing_loki::Error),
+    #[cfg(feature = "grpc")]
+    #[error("could not read {path}: {source}")]
+    /// When loading TLS certificates and keys
+    Certificate {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    #[cfg(feature = "grpc")]
+    #[error(transparent)]
+    /// gRPC transport error
+    Transport(#[from] tonic::transport::Error),
 }